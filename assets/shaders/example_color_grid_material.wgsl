@@ -0,0 +1,11 @@
+#import bevy_pbr::forward_io::VertexOutput
+
+@group(1) @binding(0)
+var<storage, read> colors: array<vec4<f32>>;
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    let side = 4u;
+    let cell = vec2<u32>(clamp(in.uv, vec2(0.0), vec2(0.999)) * f32(side));
+    return colors[cell.y * side + cell.x];
+}
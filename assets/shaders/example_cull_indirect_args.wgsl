@@ -0,0 +1,29 @@
+struct DrawIndirectArgs {
+    vertex_count: u32,
+    instance_count: atomic<u32>,
+    first_vertex: u32,
+    first_instance: u32,
+}
+
+@group(0) @binding(0)
+var<storage, read> positions: array<vec4<f32>>;
+
+@group(0) @binding(1)
+var<storage, read_write> visible: array<u32>;
+
+@group(0) @binding(2)
+var<storage, read_write> args: DrawIndirectArgs;
+
+const CULL_RADIUS: f32 = 10.0;
+
+@compute @workgroup_size(1, 1, 1)
+fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let index = global_id.x;
+    if (index == 0u) {
+        args.vertex_count = 6u;
+    }
+    if (length(positions[index].xyz) <= CULL_RADIUS) {
+        let slot = atomicAdd(&args.instance_count, 1u);
+        visible[slot] = index;
+    }
+}
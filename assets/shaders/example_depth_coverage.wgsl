@@ -0,0 +1,17 @@
+@group(0) @binding(0) var depth: texture_depth_2d;
+@group(0) @binding(1) var<storage, read_write> coverage: array<atomic<u32>, 2>;
+
+@compute @workgroup_size(8, 8, 1)
+fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let dimensions = textureDimensions(depth);
+    if (global_id.x >= dimensions.x || global_id.y >= dimensions.y) {
+        return;
+    }
+    // Reversed Z, the far plane is cleared to zero
+    let value = textureLoad(depth, vec2<i32>(global_id.xy), 0);
+    if (value > 0.0) {
+        atomicAdd(&coverage[1], 1u);
+    } else {
+        atomicAdd(&coverage[0], 1u);
+    }
+}
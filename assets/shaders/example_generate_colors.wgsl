@@ -0,0 +1,9 @@
+@group(0) @binding(0)
+var<storage, read_write> colors: array<vec4<f32>>;
+
+@compute @workgroup_size(1, 1, 1)
+fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let index = global_id.x;
+    let t = f32(index) / f32(arrayLength(&colors));
+    colors[index] = vec4<f32>(t, 1.0 - t, 0.5, 1.0);
+}
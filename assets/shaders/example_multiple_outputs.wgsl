@@ -0,0 +1,19 @@
+@group(0) @binding(0)
+var<storage, read> values: array<f32>;
+
+@group(0) @binding(1)
+var<storage, read_write> doubled: array<f32>;
+
+@group(0) @binding(2)
+var<storage, read_write> squared: array<f32>;
+
+@compute @workgroup_size(64, 1, 1)
+fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let index = global_id.x;
+    if index >= arrayLength(&values) {
+        return;
+    }
+    let value = values[index];
+    doubled[index] = value * 2.0;
+    squared[index] = value * value;
+}
@@ -0,0 +1,13 @@
+#import plumber_example::utils::square
+
+@group(0) @binding(0)
+var<storage, read_write> buffer: array<f32>;
+
+@compute @workgroup_size(64, 1, 1)
+fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let index = global_id.x;
+    if index >= arrayLength(&buffer) {
+        return;
+    }
+    buffer[index] = square(buffer[index]);
+}
@@ -0,0 +1,5 @@
+#define_import_path plumber_example::utils
+
+fn square(value: f32) -> f32 {
+    return value * value;
+}
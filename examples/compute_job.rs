@@ -0,0 +1,36 @@
+use bevy::prelude::*;
+use bevy_node_plumber::prelude::*;
+
+const LEN: u32 = 256;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(NodePlumberPlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, print_result)
+        .run();
+}
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let values: Vec<f32> = (0..LEN).map(|i| i as f32).collect();
+    let handle = ComputeJob::new(
+        asset_server.load("shaders/example_compute_job.wgsl"),
+        "main",
+    )
+    .input(&values)
+    .dispatch(DispatchWorkgroupsStrategy::CoverExtent(LEN, 1, 1))
+    .run(&mut commands)
+    .unwrap();
+    commands.spawn(handle);
+}
+
+fn print_result(mut commands: Commands, query: Query<(Entity, &ComputeJobHandle)>) {
+    for (entity, handle) in query.iter() {
+        if let Ok(doubled) = handle.take_result::<f32>() {
+            println!("Doubled: {:?}", &doubled[..8]);
+            handle.despawn(&mut commands);
+            commands.entity(entity).despawn();
+        }
+    }
+}
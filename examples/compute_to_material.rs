@@ -0,0 +1,89 @@
+use bevy::prelude::*;
+use bevy::reflect::TypePath;
+use bevy::render::render_resource::{AsBindGroup, Buffer, ShaderRef};
+use bevy_node_plumber::prelude::*;
+use bevy_render::main_graph::node::CAMERA_DRIVER;
+use bevy_render::render_resource::BufferUsages;
+use std::sync::Arc;
+
+const CELLS: u64 = 16;
+const COLOR_SIZE: u64 = 16;
+
+/// Material that binds the buffer generated by the compute node.
+#[derive(Asset, TypePath, AsBindGroup, Clone, Debug)]
+struct ColorGridMaterial {
+    #[storage(0, read_only, buffer)]
+    colors: Buffer,
+}
+
+impl Material for ColorGridMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/example_color_grid_material.wgsl".into()
+    }
+}
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(MaterialPlugin::<ColorGridMaterial>::default())
+        .add_plugins(NodePlumberPlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, spawn_grid_when_generated)
+        .run();
+}
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let generate_node = builder::ComputeNodeBuilder::default()
+        .shader(asset_server.load("shaders/example_generate_colors.wgsl"))
+        .entry_point("main")
+        .dispatch_workgroups_strategy(DispatchWorkgroupsStrategy::Static(CELLS as u32, 1, 1))
+        .bind_resource()
+        .name("colors")
+        .binding(0)
+        .output()
+        .build_buffer("colors", CELLS * COLOR_SIZE, BufferUsages::STORAGE, false)
+        .add()
+        .build()
+        .unwrap();
+    let generate_entity = commands.spawn(generate_node.clone()).id();
+
+    let shared_colors = output::SharedBufferNode::default();
+    let trigger = graph::SubGraphTrigger::Manual(Arc::new(true.into()));
+    let sub_graph = builder::SubGraphBuilder::default()
+        .name("generate_colors".into())
+        .add_node("shared_colors", shared_colors.clone())
+        .add_node_provider("generate".into(), generate_entity, &generate_node)
+        .add_slot_edge("generate", "colors", "shared_colors", output::SLOT_NAME)
+        .trigger(trigger.clone())
+        .add_outer_output_node_edge(CAMERA_DRIVER)
+        .build()
+        .unwrap();
+
+    commands.spawn((sub_graph, trigger, shared_colors));
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_xyz(0.0, 0.0, 3.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..default()
+    });
+}
+
+/// The material can be created only after the buffer is published by the sub graph.
+fn spawn_grid_when_generated(
+    mut commands: Commands,
+    query: Query<&output::SharedBufferNode>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorGridMaterial>>,
+    mut spawned: Local<bool>,
+) {
+    if *spawned {
+        return;
+    }
+    let Some(colors) = query.iter().find_map(|shared| shared.buffer()) else {
+        return;
+    };
+    commands.spawn(MaterialMeshBundle {
+        mesh: meshes.add(shape::Quad::new(Vec2::splat(2.0)).into()),
+        material: materials.add(ColorGridMaterial { colors }),
+        ..default()
+    });
+    *spawned = true;
+}
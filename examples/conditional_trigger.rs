@@ -0,0 +1,70 @@
+use bevy::prelude::*;
+use bevy_node_plumber::prelude::*;
+use bevy_render::extract_resource::{ExtractResource, ExtractResourcePlugin};
+use bevy_render::render_graph::RenderGraph;
+use bevy_render::render_resource::BufferUsages;
+
+/// Gameplay toggle, the sub graph runs only while it's enabled.
+#[derive(Resource, ExtractResource, Clone, Default)]
+struct SimulationEnabled(bool);
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(NodePlumberPlugin)
+        .add_plugins(ExtractResourcePlugin::<SimulationEnabled>::default())
+        .init_resource::<SimulationEnabled>()
+        .add_systems(Startup, setup)
+        .add_systems(Update, (toggle_simulation, print_output_buffer))
+        .run();
+}
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let square_node = builder::ComputeNodeBuilder::in_place_buffer::<f32>(
+        asset_server.load("shaders/example_square_f32_buffer.wgsl"),
+        "main",
+        "buffer",
+        64,
+    )
+    .shader_imports(vec![asset_server.load("shaders/example_utils.wgsl")])
+    .build()
+    .unwrap();
+    let square_entity = commands.spawn(square_node.clone()).id();
+
+    let input_buffer = input::StorageBufferNode::default();
+    input_buffer.set((0..16).map(|i| i as f32).collect::<Vec<f32>>());
+    input_buffer.add_usages(BufferUsages::COPY_SRC);
+    let output_buffer = output::OutputBuffer::default();
+
+    let sub_graph = builder::SubGraphBuilder::default()
+        .name("conditional_trigger".into())
+        .add_node("input_buffer", input_buffer.clone())
+        .add_node("output_buffer", output_buffer.clone())
+        .add_node_provider("square".into(), square_entity, &square_node)
+        .add_node_edge(RenderGraph::INPUT_NODE_NAME, "input_buffer")
+        .add_slot_edge("input_buffer", input::SLOT_NAME, "square", "buffer")
+        .add_slot_edge("square", "buffer", "output_buffer", output::SLOT_NAME)
+        .trigger(graph::SubGraphTrigger::when_resource(
+            |enabled: &SimulationEnabled| enabled.0,
+        ))
+        .build()
+        .unwrap();
+
+    commands.spawn((sub_graph, output_buffer, input_buffer));
+    println!("Press Space to toggle the simulation");
+}
+
+fn toggle_simulation(keys: Res<Input<KeyCode>>, mut enabled: ResMut<SimulationEnabled>) {
+    if keys.just_pressed(KeyCode::Space) {
+        enabled.0 = !enabled.0;
+        println!("Simulation enabled: {}", enabled.0);
+    }
+}
+
+fn print_output_buffer(query: Query<&output::OutputBuffer>) {
+    for out in query.iter() {
+        if let Ok(floats) = out.take_buffer_as::<Vec<f32>>() {
+            println!("Squared: {:?}", floats);
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use bevy::prelude::*;
+use bevy_node_plumber::prelude::*;
+use bevy_render::render_graph::RenderGraph;
+use bevy_render::render_resource::{BufferDescriptor, BufferUsages};
+use std::mem::size_of;
+use std::sync::Arc;
+
+const INSTANCES: usize = 64;
+
+fn main() {
+    let mut app = App::new();
+    app.add_plugins(DefaultPlugins);
+    app.add_plugins(NodePlumberPlugin)
+        .add_systems(Startup, test_startup)
+        .add_systems(Update, print_draw_args);
+
+    app.run();
+}
+
+fn test_startup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let cull_node = builder::ComputeNodeBuilder::default()
+        .shader(asset_server.load("shaders/example_cull_indirect_args.wgsl"))
+        .entry_point("main")
+        .dispatch_workgroups_strategy(DispatchWorkgroupsStrategy::from_graph_context(|graph| {
+            let x = graph
+                .get_input_buffer("positions")
+                .map_or(1, |b| b.size() / size_of::<Vec4>() as u64);
+            (x as u32, 1, 1)
+        }));
+    let cull_node = cull_node
+        .bind_resource()
+        .name("positions")
+        .binding(0)
+        .input()
+        .buffer()
+        .add()
+        .bind_resource()
+        .name("visible")
+        .binding(1)
+        .output()
+        .buffer_from_graph_context(|graph| BufferDescriptor {
+            label: "visible_instances".into(),
+            size: graph.get_input_buffer("positions").map_or(0, |b| {
+                b.size() / size_of::<Vec4>() as u64 * size_of::<u32>() as u64
+            }),
+            usage: BufferUsages::STORAGE | BufferUsages::VERTEX,
+            mapped_at_creation: false,
+        })
+        .add()
+        .bind_resource()
+        .name("args")
+        .binding(2)
+        .output()
+        .indirect_args_buffer("draw_args", IndirectArgs::Draw, 1)
+        .add();
+    let cull_node = cull_node.build().unwrap();
+    let cull_entity = commands.spawn(cull_node.clone()).id();
+
+    let positions = input::StorageBufferNode::default();
+    positions.set(
+        (0..INSTANCES)
+            .map(|i| Vec4::new(i as f32, 0.0, 0.0, 1.0))
+            .collect::<Vec<Vec4>>(),
+    );
+    let draw_args = output::OutputBuffer::default();
+    let trigger = graph::SubGraphTrigger::Manual(Arc::new(true.into()));
+
+    let sub_graph = builder::SubGraphBuilder::default()
+        .name("cull_indirect_args_sub_graph".into())
+        .add_node("positions", positions.clone())
+        .add_node("draw_args", draw_args.clone())
+        .add_node_provider("cull_node".into(), cull_entity, &cull_node)
+        .add_node_edge(RenderGraph::INPUT_NODE_NAME, "positions")
+        .add_slot_edge("positions", input::SLOT_NAME, "cull_node", "positions")
+        .add_slot_edge("cull_node", "args", "draw_args", output::SLOT_NAME)
+        .trigger(trigger.clone())
+        .build()
+        .unwrap();
+
+    commands.spawn((sub_graph, trigger, draw_args, positions));
+}
+
+fn print_draw_args(query: Query<&output::OutputBuffer>) {
+    for out in query.iter() {
+        if let Ok(args) = out.take_buffer_as::<[u32; 4]>() {
+            println!(
+                "Draw indirect args: vertex_count {}, instance_count {}, first_vertex {}, first_instance {}",
+                args[0], args[1], args[2], args[3]
+            );
+        }
+    }
+}
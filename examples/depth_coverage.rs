@@ -0,0 +1,101 @@
+use bevy::core_pipeline::core_3d::Camera3dDepthTextureUsage;
+use bevy::prelude::*;
+use bevy_node_plumber::prelude::*;
+use bevy_render::main_graph::node::CAMERA_DRIVER;
+use bevy_render::render_graph::RenderGraph;
+use bevy_render::render_resource::TextureUsages;
+
+const WORKGROUP_SIZE: u32 = 8;
+
+fn main() {
+    let mut app = App::new();
+    app.add_plugins(DefaultPlugins);
+    // Multisampled depth texture requires `texture_depth_multisampled_2d` in the shader
+    app.insert_resource(Msaa::Off)
+        .add_plugins(NodePlumberPlugin)
+        .add_systems(Startup, test_startup)
+        .add_systems(Update, print_coverage);
+
+    app.run();
+}
+
+fn test_startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn(PbrBundle {
+        mesh: meshes.add(shape::Cube { size: 1.0 }.into()),
+        material: materials.add(Color::rgb(0.8, 0.7, 0.6).into()),
+        ..default()
+    });
+    commands.spawn(PointLightBundle {
+        transform: Transform::from_xyz(4.0, 8.0, 4.0),
+        ..default()
+    });
+    let camera = commands
+        .spawn(Camera3dBundle {
+            camera_3d: Camera3d {
+                depth_texture_usages: Camera3dDepthTextureUsage::from(
+                    TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+                ),
+                ..default()
+            },
+            transform: Transform::from_xyz(-2.0, 2.5, 5.0).looking_at(Vec3::ZERO, Vec3::Y),
+            ..default()
+        })
+        .id();
+
+    let coverage_node = builder::ComputeNodeBuilder::default()
+        .shader(asset_server.load("shaders/example_depth_coverage.wgsl"))
+        .entry_point("main")
+        .dispatch_workgroups_strategy(DispatchWorkgroupsStrategy::Static(
+            1280 / WORKGROUP_SIZE,
+            720 / WORKGROUP_SIZE,
+            1,
+        ))
+        .bind_resource()
+        .name("depth")
+        .binding(0)
+        .input()
+        .texture_view()
+        .add()
+        .bind_resource()
+        .name("coverage")
+        .binding(1)
+        .input_output()
+        .buffer()
+        .add();
+    let coverage_node = coverage_node.build().unwrap();
+    let coverage_entity = commands.spawn(coverage_node.clone()).id();
+
+    let histogram = histogram::HistogramNode::new(2);
+    let sub_graph = builder::SubGraphBuilder::default()
+        .name("depth_coverage_sub_graph".into())
+        .add_node("view_depth", input::ViewDepthTextureNode::new(Some(camera)))
+        .add_node_provider("coverage_node".into(), coverage_entity, &coverage_node)
+        .add_node_edge(RenderGraph::INPUT_NODE_NAME, "view_depth")
+        .add_slot_edge("view_depth", input::SLOT_NAME, "coverage_node", "depth")
+        .add_outer_input_node_edge(CAMERA_DRIVER);
+    let sub_graph = histogram
+        .add_to_sub_graph(sub_graph, "coverage", "coverage_node", "coverage")
+        .build()
+        .unwrap();
+
+    commands.spawn((sub_graph, histogram.output_buffer(), histogram));
+}
+
+fn print_coverage(query: Query<&histogram::HistogramNode>) {
+    for histogram in query.iter() {
+        if let Ok(counts) = histogram.take_bin_counts() {
+            let total = (counts[0] + counts[1]).max(1);
+            println!(
+                "Geometry covers {} of {} pixels ({:.1}%)",
+                counts[1],
+                total,
+                counts[1] as f32 * 100.0 / total as f32
+            );
+        }
+    }
+}
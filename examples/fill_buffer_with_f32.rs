@@ -2,7 +2,6 @@ use bevy::prelude::*;
 use bevy_node_plumber::prelude::*;
 use bevy_render::render_graph::RenderGraph;
 use bevy_render::render_resource::BufferUsages;
-use std::mem::size_of;
 use std::sync::Arc;
 
 fn main() {
@@ -28,22 +27,12 @@ fn main() {
 }
 
 fn test_startup(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let fill_buffer_node = builder::ComputeNodeBuilder::default()
-        .shader(asset_server.load("shaders/example_fill_f32_buffer.wgsl"))
-        .entry_point("main")
-        .dispatch_workgroups_strategy(DispatchWorkgroupsStrategy::FromGraphContext(|graph| {
-            let x = graph
-                .get_input_buffer("buffer")
-                .map_or(1, |b| b.size() / size_of::<f32>() as u64);
-            (x as u32, 1, 1)
-        }));
-    let fill_buffer_node = fill_buffer_node
-        .bind_resource()
-        .name("buffer")
-        .binding(0)
-        .input_output()
-        .buffer()
-        .add();
+    let fill_buffer_node = builder::ComputeNodeBuilder::in_place_buffer::<f32>(
+        asset_server.load("shaders/example_fill_f32_buffer.wgsl"),
+        "main",
+        "buffer",
+        1,
+    );
     let fill_buffer_node = fill_buffer_node.build().unwrap();
     let fill_buffer_entity = commands.spawn(fill_buffer_node.clone()).id();
 
@@ -0,0 +1,63 @@
+use bevy::prelude::*;
+use bevy_node_plumber::prelude::*;
+use bevy_render::main_graph::node::CAMERA_DRIVER;
+use bevy_render::render_graph::RenderGraph;
+use bevy_render::render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(NodePlumberPlugin)
+        .add_systems(Startup, setup)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: 640,
+            height: 480,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 255],
+        TextureFormat::Rgba8Unorm,
+    );
+    image.texture_descriptor.usage =
+        TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING;
+    let image = images.add(image);
+
+    commands.spawn(SpriteBundle {
+        texture: image.clone(),
+        ..default()
+    });
+    commands.spawn(Camera2dBundle::default());
+
+    let gradient_node = builder::RenderNodeBuilder::default()
+        .label("gradient")
+        .fragment_shader(asset_server.load("shaders/example_fullscreen_gradient.wgsl"))
+        .fragment_entry_point("fragment")
+        .target_format(TextureFormat::Rgba8Unorm)
+        .target_slot("target")
+        .build()
+        .unwrap();
+    let gradient_entity = commands.spawn(gradient_node.clone()).id();
+
+    let trigger = graph::SubGraphTrigger::Always;
+    let sub_graph = builder::SubGraphBuilder::default()
+        .name("fullscreen_fragment".into())
+        .add_node("target", input::InputTextureNode::from_image(image))
+        .add_node_provider("gradient".into(), gradient_entity, &gradient_node)
+        .add_node_edge(RenderGraph::INPUT_NODE_NAME, "target")
+        .add_slot_edge("target", input::SLOT_NAME, "gradient", "target")
+        .trigger(trigger.clone())
+        .add_outer_output_node_edge(CAMERA_DRIVER)
+        .build()
+        .unwrap();
+
+    commands.spawn((sub_graph, trigger));
+}
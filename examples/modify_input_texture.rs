@@ -8,8 +8,8 @@ fn main() {
     let mut app = App::new();
     app.add_plugins(DefaultPlugins);
     app.add_plugins(NodePlumberPlugin)
-        .add_systems(Startup, test_startup)
-        .add_systems(PreUpdate, update_node_on_shader_changed);
+        .add_plugins(compute::AutoReloadShadersPlugin)
+        .add_systems(Startup, test_startup);
 
     #[cfg(debug_assertions)]
     {
@@ -55,7 +55,7 @@ fn test_startup(
     let fill_texture_view_node = builder::ComputeNodeBuilder::default()
         .shader(asset_server.load("shaders/example_fill_texture_view.wgsl"))
         .entry_point("main")
-        .dispatch_workgroups_strategy(DispatchWorkgroupsStrategy::Static(640, 480, 1));
+        .dispatch_workgroups_strategy(DispatchWorkgroupsStrategy::CoverExtent(640, 480, 1));
     let fill_texture_view_node = fill_texture_view_node
         .bind_resource()
         .name("texture")
@@ -90,25 +90,3 @@ fn test_startup(
 
     commands.spawn((sub_graph, trigger));
 }
-
-fn update_node_on_shader_changed(
-    mut events: EventReader<AssetEvent<Shader>>,
-    mut query: Query<&mut compute::ComputeNode>,
-) {
-    let ids: Vec<AssetId<Shader>> = events
-        .read()
-        .filter_map(|event| {
-            if let AssetEvent::Modified { id } = event {
-                Some(*id)
-            } else {
-                None
-            }
-        })
-        .collect();
-
-    for mut compute_node in query.iter_mut() {
-        if ids.contains(&compute_node.pipeline_descriptor.shader.id()) {
-            compute_node.set_changed();
-        }
-    }
-}
@@ -0,0 +1,87 @@
+use bevy::prelude::*;
+use bevy_node_plumber::prelude::*;
+use bevy_render::render_graph::RenderGraph;
+use bevy_render::render_resource::BufferUsages;
+use std::sync::Arc;
+
+const LEN: usize = 256;
+const BUFFER_SIZE: u64 = (LEN * std::mem::size_of::<f32>()) as u64;
+
+/// Each read back buffer is a separate node, so it's spawned on its own entity.
+#[derive(Component)]
+struct Readback(&'static str);
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(NodePlumberPlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, print_outputs)
+        .run();
+}
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let output_usages = BufferUsages::STORAGE | BufferUsages::COPY_SRC;
+    let compute_node = builder::ComputeNodeBuilder::default()
+        .shader(asset_server.load("shaders/example_multiple_outputs.wgsl"))
+        .entry_point("main")
+        .dispatch_workgroups_strategy(DispatchWorkgroupsStrategy::PerBufferElement {
+            slot: "values".into(),
+            element_size: std::mem::size_of::<f32>() as u64,
+            workgroup_size: 64,
+        })
+        .bind_resource()
+        .name("values")
+        .binding(0)
+        .read_only()
+        .input()
+        .buffer()
+        .add()
+        .bind_resource()
+        .name("doubled")
+        .binding(1)
+        .output()
+        .build_buffer("doubled", BUFFER_SIZE, output_usages, false)
+        .add()
+        .bind_resource()
+        .name("squared")
+        .binding(2)
+        .output()
+        .build_buffer("squared", BUFFER_SIZE, output_usages, false)
+        .add()
+        .build()
+        .unwrap();
+    let compute_entity = commands.spawn(compute_node.clone()).id();
+
+    let values = input::StorageBufferNode::default();
+    values.set((0..LEN).map(|i| i as f32).collect::<Vec<f32>>());
+    let doubled = output::OutputBuffer::default();
+    let squared = output::OutputBuffer::default();
+    let trigger = graph::SubGraphTrigger::Manual(Arc::new(true.into()));
+
+    let sub_graph = builder::SubGraphBuilder::default()
+        .name("multiple_outputs".into())
+        .add_node("values", values.clone())
+        .add_node("doubled", doubled.clone())
+        .add_node("squared", squared.clone())
+        .add_node_provider("compute".into(), compute_entity, &compute_node)
+        .add_node_edge(RenderGraph::INPUT_NODE_NAME, "values")
+        .add_slot_edge("values", input::SLOT_NAME, "compute", "values")
+        .add_slot_edge("compute", "doubled", "doubled", output::SLOT_NAME)
+        .add_slot_edge("compute", "squared", "squared", output::SLOT_NAME)
+        .trigger(trigger.clone())
+        .build()
+        .unwrap();
+
+    commands.spawn((sub_graph, trigger, values));
+    commands.spawn((doubled, Readback("doubled")));
+    commands.spawn((squared, Readback("squared")));
+}
+
+fn print_outputs(query: Query<(&output::OutputBuffer, &Readback)>) {
+    for (out, readback) in query.iter() {
+        if let Ok(floats) = out.take_buffer_as::<Vec<f32>>() {
+            println!("{}: {:?}", readback.0, &floats[..8]);
+        }
+    }
+}
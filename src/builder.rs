@@ -1,18 +1,28 @@
 use crate::graph::{
-    Edge, ProviderDescriptor, ProviderState, SubGraph, SubGraphDeployState, SubGraphTrigger,
+    Edge, ProviderDescriptor, ProviderState, RequiredOutputUsages, RequiredUsages,
+    RunNestedGraphNode, SubGraph, SubGraphDeployState, SubGraphOutput, SubGraphOutputNode,
+    SubGraphTrigger,
 };
-use crate::prelude::compute::ComputeNodeState;
+use crate::node::render::RenderNodeState;
+use crate::node::{add_or_replace_graph_node, Workgroups};
+use crate::prelude::compute::{ComputeNodePass, ComputeNodeState};
 use crate::prelude::*;
-use crate::resource::BindResourceCreationStrategy;
+use crate::resource::{
+    BindResourceCreationStrategy, DynamicOffset, ExternalTextureView, GraphContextFn, IndirectArgs,
+    MissingInputMode, ResourceLifetime, SharedBindGroupLayout, SharedBindings,
+};
 use crate::NodeProvider;
+use bevy::core_pipeline::fullscreen_vertex_shader;
 use bevy::prelude::*;
 use bevy::utils::HashMap;
 use bevy_render::render_graph::{
     NodeLabel, RenderGraph, RenderGraphError, SlotInfo, SlotLabel, SlotType,
 };
 use bevy_render::render_resource::{
-    BindGroupLayout, BufferAddress, BufferUsages, ComputePipelineDescriptor, PushConstantRange,
-    ShaderDefVal,
+    BindGroupLayout, BindGroupLayoutEntry, BlendState, BufferAddress, BufferUsages,
+    ColorTargetState, ColorWrites, ComputePipelineDescriptor, FragmentState, PrimitiveState,
+    PushConstantRange, RenderPipelineDescriptor, ShaderDefVal, ShaderStages, TextureFormat,
+    TextureUsages, VertexState,
 };
 use bevy_render::{render_graph, render_resource};
 use std::any::TypeId;
@@ -47,6 +57,67 @@ pub enum BuilderError {
     RenderGraphError(#[from] RenderGraphError),
 }
 
+/// Failed bind resources are kept by the cloned builders, so the errors are cloned
+/// with the same variant. `RenderGraphError` is not `Clone`, its variants are copied.
+impl Clone for BuilderError {
+    fn clone(&self) -> Self {
+        match self {
+            Self::ValueNotDefined(name) => Self::ValueNotDefined(name),
+            Self::ValidationError(message) => Self::ValidationError(message.clone()),
+            Self::RenderGraphError(err) => Self::RenderGraphError(match err {
+                RenderGraphError::InvalidNode(label) => {
+                    RenderGraphError::InvalidNode(label.clone())
+                }
+                RenderGraphError::InvalidOutputNodeSlot(label) => {
+                    RenderGraphError::InvalidOutputNodeSlot(label.clone())
+                }
+                RenderGraphError::InvalidInputNodeSlot(label) => {
+                    RenderGraphError::InvalidInputNodeSlot(label.clone())
+                }
+                RenderGraphError::WrongNodeType => RenderGraphError::WrongNodeType,
+                RenderGraphError::MismatchedNodeSlots {
+                    output_node,
+                    output_slot,
+                    input_node,
+                    input_slot,
+                } => RenderGraphError::MismatchedNodeSlots {
+                    output_node: *output_node,
+                    output_slot: *output_slot,
+                    input_node: *input_node,
+                    input_slot: *input_slot,
+                },
+                RenderGraphError::EdgeAlreadyExists(edge) => {
+                    RenderGraphError::EdgeAlreadyExists(edge.clone())
+                }
+                RenderGraphError::EdgeDoesNotExist(edge) => {
+                    RenderGraphError::EdgeDoesNotExist(edge.clone())
+                }
+                RenderGraphError::UnconnectedNodeInputSlot { node, input_slot } => {
+                    RenderGraphError::UnconnectedNodeInputSlot {
+                        node: *node,
+                        input_slot: *input_slot,
+                    }
+                }
+                RenderGraphError::UnconnectedNodeOutputSlot { node, output_slot } => {
+                    RenderGraphError::UnconnectedNodeOutputSlot {
+                        node: *node,
+                        output_slot: *output_slot,
+                    }
+                }
+                RenderGraphError::NodeInputSlotAlreadyOccupied {
+                    node,
+                    input_slot,
+                    occupied_by_node,
+                } => RenderGraphError::NodeInputSlotAlreadyOccupied {
+                    node: *node,
+                    input_slot: *input_slot,
+                    occupied_by_node: *occupied_by_node,
+                },
+            }),
+        }
+    }
+}
+
 impl From<&'static str> for BuilderError {
     fn from(value: &'static str) -> Self {
         Self::ValueNotDefined(value)
@@ -57,33 +128,174 @@ pub type BuildResult<T> = Result<T, BuilderError>;
 pub type BuildResultFn<P, T> = Box<dyn FnOnce(P, BuildResult<T>) -> P>;
 pub type BuildFn<P, T> = Box<dyn FnOnce(P, T) -> P>;
 
-#[derive(Default)]
+/// Bind resource declared in the node builder. The resources without an explicit group are
+/// placed into the bind group of the node when the node is built.
+#[derive(Clone, Debug)]
+struct DeclaredBindResource {
+    info: BindResourceCreationInfo,
+    explicit_group: bool,
+}
+
+impl DeclaredBindResource {
+    fn resolve(mut self, default_group: u32) -> BindResourceCreationInfo {
+        if !self.explicit_group {
+            self.info.group = default_group;
+        }
+        self.info
+    }
+}
+
+/// Resolves the groups of the declared bind resources, or returns the first error.
+fn resolve_bind_resources(
+    bind_resources: Vec<BuildResult<DeclaredBindResource>>,
+    bind_group_index: Option<u32>,
+) -> BuildResult<Vec<BindResourceCreationInfo>> {
+    let default_group = bind_group_index.unwrap_or(0);
+    bind_resources
+        .into_iter()
+        .map(|result| result.map(|declared| declared.resolve(default_group)))
+        .collect()
+}
+
+#[derive(Default, Clone)]
 pub struct ComputeNodeBuilder {
     label: Option<Cow<'static, str>>,
+    debug_name: Option<Cow<'static, str>>,
 
     // Pipeline
     bind_group_index: Option<u32>,
     bind_group_layout: Option<Vec<BindGroupLayout>>,
+    shared_layout: Option<SharedBindGroupLayout>,
     push_constant_ranges: Option<Vec<PushConstantRange>>,
     shader: Option<Handle<Shader>>,
+    shader_imports: Option<Vec<Handle<Shader>>>,
     shader_defs: Option<Vec<ShaderDefVal>>,
     entry_point: Option<Cow<'static, str>>,
 
-    bind_resources: Vec<BuildResult<BindResourceCreationInfo>>,
+    bind_resources: Vec<BuildResult<DeclaredBindResource>>,
 
     dispatch_workgroups_strategy: Option<DispatchWorkgroupsStrategy>,
+    dispatch_chunking: Option<DispatchChunking>,
+    push_constants_strategy: Option<PushConstantsStrategy>,
+    bind_resource_count_shader_def: Option<String>,
+    memoize: Option<bool>,
+    missing_input_mode: Option<MissingInputMode>,
+    additional_passes: Vec<ComputeNodePass>,
+    derive_layout: bool,
+    expected_coverage: Option<(Workgroups, Workgroups)>,
 }
 
 impl ComputeNodeBuilder {
     option_into_setter!(label: Cow<'static, str>);
+    option_into_setter!(debug_name: Cow<'static, str>);
     option_setter!(bind_group_index: u32);
     option_setter!(bind_group_layout: Vec<BindGroupLayout>);
+    option_setter!(shared_layout: SharedBindGroupLayout);
     option_setter!(push_constant_ranges: Vec<PushConstantRange>);
     option_setter!(shader: Handle<Shader>);
+    option_setter!(shader_imports: Vec<Handle<Shader>>);
     option_setter!(shader_defs: Vec<ShaderDefVal>);
     option_into_setter!(entry_point: Cow<'static, str>);
     option_setter!(dispatch_workgroups_strategy: DispatchWorkgroupsStrategy);
+    option_setter!(dispatch_chunking: DispatchChunking);
+    option_setter!(push_constants_strategy: PushConstantsStrategy);
+    option_into_setter!(bind_resource_count_shader_def: String);
+    option_setter!(memoize: bool);
+    option_setter!(missing_input_mode: MissingInputMode);
+
+    /// Copy of the configuration with other shader defs, e.g. for the nodes that share the shader
+    /// source with different constants. Each built node queues its own pipeline.
+    pub fn with_shader_defs(&self, shader_defs: Vec<ShaderDefVal>) -> Self {
+        self.clone().shader_defs(shader_defs)
+    }
+
+    /// Derives the bind group layout from the declared bind resources instead of reflecting it
+    /// from the shader. The layout is created when the pipeline is queued in the Render World.
+    pub fn derive_layout(mut self) -> Self {
+        self.derive_layout = true;
+        self
+    }
+
+    /// Workgroup size declared in the shader and the number of elements the `Static` dispatch
+    /// is intended to cover. The build warns if the invocations don't cover the elements or
+    /// if whole workgroups are dispatched in excess, e.g. when the count was not ceil-divided.
+    pub fn expected_coverage(
+        mut self,
+        workgroup_size: Workgroups,
+        element_count: Workgroups,
+    ) -> Self {
+        self.expected_coverage = Some((workgroup_size, element_count));
+        self
+    }
 
+    /// Workgroups are computed by the function on each run, e.g. from the input buffer sizes.
+    pub fn dispatch_workgroups_from_graph_context(
+        self,
+        workgroups_from_graph_context: impl Fn(&render_graph::RenderGraphContext) -> (u32, u32, u32)
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        self.dispatch_workgroups_strategy(DispatchWorkgroupsStrategy::from_graph_context(
+            workgroups_from_graph_context,
+        ))
+    }
+
+    /// Dispatches another entry point of the shader after the previous passes, with the same
+    /// bind groups. The bind group layout must be set, because the reflected layouts of the
+    /// entry points are not compatible with each other.
+    pub fn add_pass(
+        mut self,
+        entry_point: impl Into<Cow<'static, str>>,
+        dispatch_workgroups_strategy: DispatchWorkgroupsStrategy,
+    ) -> Self {
+        self.additional_passes.push(ComputeNodePass {
+            entry_point: entry_point.into(),
+            dispatch_workgroups_strategy,
+        });
+        self
+    }
+
+    /// Preset for the single `input_output` buffer modified in place,
+    /// dispatched with one invocation per element of type `T`.
+    pub fn in_place_buffer<T>(
+        shader: Handle<Shader>,
+        entry_point: impl Into<Cow<'static, str>>,
+        name: impl Into<Cow<'static, str>>,
+        workgroup_size: u32,
+    ) -> Self {
+        let name = name.into();
+        Self::default()
+            .shader(shader)
+            .entry_point(entry_point)
+            .dispatch_workgroups_strategy(DispatchWorkgroupsStrategy::PerBufferElement {
+                slot: name.clone(),
+                element_size: std::mem::size_of::<T>() as u64,
+                workgroup_size,
+            })
+            .bind_resource()
+            .name(name)
+            .binding(0)
+            .input_output()
+            .buffer()
+            .add()
+    }
+
+    /// Adds the shared bindings as inputs of the node. The per-node resources without
+    /// an explicit group go to the next bind group, unless the bind group index is set.
+    pub fn shared_bindings(mut self, shared: &SharedBindings) -> Self {
+        self.bind_resources
+            .extend(shared.bind_resource_info().map(|info| {
+                Ok(DeclaredBindResource {
+                    info,
+                    explicit_group: true,
+                })
+            }));
+        self.bind_group_index.get_or_insert(shared.group() + 1);
+        self
+    }
+
+    /// Declares the bind resource, in the bind group of the node unless the group is set.
     pub fn bind_resource(self) -> AddBindResourceInfoBuilder<Self> {
         AddBindResourceInfoBuilder::new(
             self,
@@ -94,64 +306,559 @@ impl ComputeNodeBuilder {
         )
     }
 
+    /// Same as `build`, but reports the failed bind resources, the shared bindings and
+    /// the missing mandatory values together. The validations of the complete configuration
+    /// run only when these pass.
+    pub fn build_all(mut self) -> Result<compute::ComputeNode, Vec<BuilderError>> {
+        let mut errors = Vec::new();
+        let mut bind_resource = Vec::with_capacity(self.bind_resources.len());
+        let default_group = self.bind_group_index.unwrap_or(0);
+        for result in self.bind_resources.drain(..) {
+            match result {
+                Ok(declared) => bind_resource.push(declared.resolve(default_group)),
+                Err(err) => errors.push(err),
+            }
+        }
+        if let Err(err) = validate_unique_bindings(&bind_resource) {
+            errors.push(err);
+        }
+        let mandatory = [
+            ("shader", self.shader.is_some()),
+            ("entry_point", self.entry_point.is_some()),
+            (
+                "dispatch_workgroups_strategy",
+                self.dispatch_workgroups_strategy.is_some(),
+            ),
+        ];
+        errors.extend(
+            mandatory
+                .into_iter()
+                .filter(|(_, defined)| !defined)
+                .map(|(name, _)| BuilderError::ValueNotDefined(name)),
+        );
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        self.bind_resources = bind_resource
+            .into_iter()
+            .map(|info| {
+                Ok(DeclaredBindResource {
+                    info,
+                    explicit_group: true,
+                })
+            })
+            .collect();
+        self.build().map_err(|err| vec![err])
+    }
+
     pub fn build(mut self) -> BuildResult<compute::ComputeNode> {
-        let bind_resource: BuildResult<Vec<BindResourceCreationInfo>> =
-            self.bind_resources.drain(..).collect();
+        let bind_resource = resolve_bind_resources(
+            std::mem::take(&mut self.bind_resources),
+            self.bind_group_index,
+        )?;
+        validate_unique_bindings(&bind_resource)?;
+        let explicit_layouts = usize::from(self.bind_group_layout.is_some())
+            + usize::from(self.shared_layout.is_some());
+        if explicit_layouts > 1 {
+            return Err(BuilderError::ValidationError(
+                "Bind group layout cannot be both defined and shared".to_string(),
+            ));
+        }
+        let layout_entries = if self.derive_layout {
+            if explicit_layouts > 0 {
+                return Err(BuilderError::ValidationError(
+                    "Bind group layout cannot be both defined and derived".to_string(),
+                ));
+            }
+            derive_layout_entries(&bind_resource)?
+        } else {
+            default()
+        };
+        let has_layout = explicit_layouts > 0 || self.derive_layout;
+        validate_dynamic_offsets(&bind_resource, has_layout)?;
+        if !self.additional_passes.is_empty() && !has_layout {
+            return Err(BuilderError::ValidationError(
+                "Additional passes require the bind group layout shared by the entry points"
+                    .to_string(),
+            ));
+        }
+        let strategies = self.dispatch_workgroups_strategy.iter().chain(
+            self.additional_passes
+                .iter()
+                .map(|pass| &pass.dispatch_workgroups_strategy),
+        );
+        for strategy in strategies {
+            let DispatchWorkgroupsStrategy::Indirect { buffer_slot, .. } = strategy else {
+                continue;
+            };
+            let is_input_buffer = bind_resource.iter().any(|info| {
+                info.name == *buffer_slot
+                    && matches!(
+                        info.direction,
+                        BindResourceDirection::Input(SlotType::Buffer)
+                            | BindResourceDirection::InputOutput(SlotType::Buffer)
+                    )
+            });
+            if !is_input_buffer {
+                return Err(BuilderError::ValidationError(format!(
+                    "Indirect dispatch slot `{}` must be declared as an input buffer",
+                    buffer_slot
+                )));
+            }
+            if self.dispatch_chunking.is_some() {
+                return Err(BuilderError::ValidationError(
+                    "Dispatch chunking cannot be used with indirect dispatch".to_string(),
+                ));
+            }
+        }
+
+        if let (
+            Some(DispatchWorkgroupsStrategy::Static(x, y, z)),
+            Some((workgroup_size, element_count)),
+        ) = (&self.dispatch_workgroups_strategy, self.expected_coverage)
+        {
+            if let Some(issue) = DispatchWorkgroupsStrategy::coverage_issue(
+                (*x, *y, *z),
+                workgroup_size,
+                element_count,
+            ) {
+                warn!(
+                    "Static dispatch of the compute node {:?} doesn't match the expected coverage: {}",
+                    self.debug_name.as_ref().or(self.label.as_ref()),
+                    issue
+                );
+            }
+        }
+
+        if let Some(MissingInputMode::Placeholder { buffer_size }) = self.missing_input_mode {
+            if buffer_size == 0 || buffer_size % 4 != 0 {
+                return Err(BuilderError::ValidationError(format!(
+                    "Placeholder buffer size must be a non-zero multiple of 4, got {}",
+                    buffer_size
+                )));
+            }
+        }
+
+        let mut push_constant_ranges = self.push_constant_ranges.unwrap_or_default();
+        if let Some(chunking) = &self.dispatch_chunking {
+            let range = chunking.push_constant_range();
+            if push_constant_ranges.is_empty() {
+                push_constant_ranges.push(PushConstantRange {
+                    stages: ShaderStages::COMPUTE,
+                    range,
+                });
+            } else if !push_constant_ranges.iter().any(|r| {
+                r.stages.contains(ShaderStages::COMPUTE)
+                    && r.range.start <= range.start
+                    && r.range.end >= range.end
+            }) {
+                return Err(BuilderError::ValidationError(format!(
+                    "Dispatch chunking requires a compute push constant range covering {:?}",
+                    range
+                )));
+            }
+        }
+
+        if let Some(strategy) = &self.push_constants_strategy {
+            let bytes = match strategy {
+                PushConstantsStrategy::Static(bytes) => bytes.as_slice(),
+                // Checked on every run, only the declaration can be validated there
+                PushConstantsStrategy::FromGraphContext(_) => &[],
+            };
+            PushConstantsStrategy::validate(bytes, &push_constant_ranges)
+                .map_err(BuilderError::ValidationError)?;
+            if let Some(chunking) = &self.dispatch_chunking {
+                chunking
+                    .validate_push_constants(bytes)
+                    .map_err(BuilderError::ValidationError)?;
+            }
+        }
 
         Ok(compute::ComputeNode {
+            debug_name: self.debug_name.or_else(|| self.label.clone()),
             label: self.label.clone(),
             bind_group_index: self.bind_group_index.unwrap_or(0),
             pipeline_descriptor: ComputePipelineDescriptor {
                 label: self.label,
                 layout: self.bind_group_layout.unwrap_or_default(),
-                push_constant_ranges: self.push_constant_ranges.unwrap_or_default(),
+                push_constant_ranges,
                 shader: self.shader.ok_or(BuilderError::ValueNotDefined("shader"))?,
                 shader_defs: self.shader_defs.unwrap_or_default(),
                 entry_point: self
                     .entry_point
                     .ok_or(BuilderError::ValueNotDefined("entry_point"))?,
             },
-            binding_resource_info: bind_resource?,
+            shader_imports: self.shader_imports.unwrap_or_default(),
+            binding_resource_info: bind_resource,
             dispatch_workgroups_strategy: self.dispatch_workgroups_strategy.ok_or(
                 BuilderError::ValueNotDefined("dispatch_workgroups_strategy"),
             )?,
+            dispatch_chunking: self.dispatch_chunking,
+            push_constants_strategy: self.push_constants_strategy,
+            bind_resource_count_shader_def: self.bind_resource_count_shader_def,
+            memoize: self.memoize.unwrap_or_default(),
+            missing_input_mode: self.missing_input_mode.unwrap_or_default(),
+            additional_passes: self.additional_passes,
+            layout_entries,
+            shared_layout: self.shared_layout,
+            bind_resource_sizes: default(),
+            dispatched_workgroups: default(),
+            dispatched_dynamic_offsets: default(),
+            workgroup_sizes: default(),
+            bind_group_count: default(),
+            switch: default(),
             state: ComputeNodeState::Creating,
         })
     }
 }
 
+fn validate_unique_bindings(bind_resources: &[BindResourceCreationInfo]) -> BuildResult<()> {
+    for (index, info) in bind_resources.iter().enumerate() {
+        let shared = bind_resources[..index]
+            .iter()
+            .any(|other| other.group == info.group && other.binding == info.binding);
+        if shared {
+            return Err(BuilderError::ValidationError(format!(
+                "Bind resource `{}` shares the binding {} of the group {} with another resource",
+                info.name, info.binding, info.group
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Reflected layouts never have dynamic offsets, so they must be declared explicitly.
+fn validate_dynamic_offsets(
+    bind_resources: &[BindResourceCreationInfo],
+    has_layout: bool,
+) -> BuildResult<()> {
+    let dynamic = bind_resources
+        .iter()
+        .find(|info| info.dynamic_offset.is_some());
+    match (dynamic, has_layout) {
+        (Some(info), false) => Err(BuilderError::ValidationError(format!(
+            "Bind resource `{}` has a dynamic offset, the bind group layout must be defined",
+            info.name
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Layout entries of the bind groups from zero to the last declared group, visible to the
+/// compute stage.
+fn derive_layout_entries(
+    bind_resources: &[BindResourceCreationInfo],
+) -> BuildResult<Vec<Vec<BindGroupLayoutEntry>>> {
+    let group_count = bind_resources
+        .iter()
+        .map(|info| info.group + 1)
+        .max()
+        .unwrap_or(0);
+    let mut groups: Vec<Vec<BindGroupLayoutEntry>> = vec![default(); group_count as usize];
+    for info in bind_resources {
+        let entries = &mut groups[info.group as usize];
+        if entries.iter().any(|entry| entry.binding == info.binding) {
+            return Err(BuilderError::ValidationError(format!(
+                "Bind resource `{}` shares the binding {} of the group {} with another resource",
+                info.name, info.binding, info.group
+            )));
+        }
+        let entry = info.layout_entry(ShaderStages::COMPUTE).ok_or_else(|| {
+            BuilderError::ValidationError(format!(
+                "Layout entry of the bind resource `{}` cannot be derived, the layout must be defined",
+                info.name
+            ))
+        })?;
+        entries.push(entry);
+    }
+    Ok(groups)
+}
+
+#[derive(Default)]
+pub struct RenderNodeBuilder {
+    label: Option<Cow<'static, str>>,
+    debug_name: Option<Cow<'static, str>>,
+
+    // Pipeline
+    bind_group_index: Option<u32>,
+    bind_group_layout: Option<Vec<BindGroupLayout>>,
+    vertex: Option<VertexState>,
+    fragment_shader: Option<Handle<Shader>>,
+    fragment_entry_point: Option<Cow<'static, str>>,
+    shader_defs: Option<Vec<ShaderDefVal>>,
+    primitive: Option<PrimitiveState>,
+    target_format: Option<TextureFormat>,
+    blend: Option<BlendState>,
+
+    bind_resources: Vec<BuildResult<DeclaredBindResource>>,
+
+    target_slot: Option<Cow<'static, str>>,
+    clear_color: Option<Color>,
+    vertex_count: Option<u32>,
+}
+
+impl RenderNodeBuilder {
+    option_into_setter!(label: Cow<'static, str>);
+    option_into_setter!(debug_name: Cow<'static, str>);
+    option_setter!(bind_group_index: u32);
+    option_setter!(bind_group_layout: Vec<BindGroupLayout>);
+    option_setter!(vertex: VertexState);
+    option_setter!(fragment_shader: Handle<Shader>);
+    option_into_setter!(fragment_entry_point: Cow<'static, str>);
+    option_setter!(shader_defs: Vec<ShaderDefVal>);
+    option_setter!(primitive: PrimitiveState);
+    option_setter!(target_format: TextureFormat);
+    option_setter!(blend: BlendState);
+    option_into_setter!(target_slot: Cow<'static, str>);
+    option_setter!(clear_color: Color);
+    option_setter!(vertex_count: u32);
+
+    /// Declares the bind resource, in the bind group of the node unless the group is set.
+    pub fn bind_resource(self) -> AddBindResourceInfoBuilder<Self> {
+        AddBindResourceInfoBuilder::new(
+            self,
+            Box::new(|mut parent, result| -> Self {
+                parent.bind_resources.push(result);
+                parent
+            }),
+        )
+    }
+
+    /// Vertex state defaults to the Bevy fullscreen triangle, drawn with 3 vertices.
+    pub fn build(mut self) -> BuildResult<render::RenderNode> {
+        let bind_resource = resolve_bind_resources(
+            std::mem::take(&mut self.bind_resources),
+            self.bind_group_index,
+        )?;
+        validate_unique_bindings(&bind_resource)?;
+        validate_dynamic_offsets(&bind_resource, self.bind_group_layout.is_some())?;
+        let shader_defs = self.shader_defs.unwrap_or_default();
+        let mut vertex = self
+            .vertex
+            .unwrap_or_else(fullscreen_vertex_shader::fullscreen_shader_vertex_state);
+        vertex.shader_defs.extend(shader_defs.iter().cloned());
+
+        Ok(render::RenderNode {
+            debug_name: self.debug_name.or_else(|| self.label.clone()),
+            label: self.label.clone(),
+            pipeline_descriptor: RenderPipelineDescriptor {
+                label: self.label,
+                layout: self.bind_group_layout.unwrap_or_default(),
+                push_constant_ranges: default(),
+                vertex,
+                primitive: self.primitive.unwrap_or_default(),
+                depth_stencil: None,
+                multisample: default(),
+                fragment: Some(FragmentState {
+                    shader: self
+                        .fragment_shader
+                        .ok_or(BuilderError::ValueNotDefined("fragment_shader"))?,
+                    shader_defs,
+                    entry_point: self
+                        .fragment_entry_point
+                        .ok_or(BuilderError::ValueNotDefined("fragment_entry_point"))?,
+                    targets: vec![Some(ColorTargetState {
+                        format: self
+                            .target_format
+                            .ok_or(BuilderError::ValueNotDefined("target_format"))?,
+                        blend: self.blend,
+                        write_mask: ColorWrites::ALL,
+                    })],
+                }),
+            },
+            binding_resource_info: bind_resource,
+            target_slot: self
+                .target_slot
+                .ok_or(BuilderError::ValueNotDefined("target_slot"))?,
+            clear_color: self.clear_color,
+            vertex_count: self.vertex_count.unwrap_or(3),
+            bind_resource_sizes: default(),
+            bind_group_count: default(),
+            switch: default(),
+            state: RenderNodeState::Creating,
+        })
+    }
+}
+
 pub struct AddBindResourceInfoBuilder<P> {
     parent: P,
-    build_fn: BuildResultFn<P, BindResourceCreationInfo>,
+    build_fn: BuildResultFn<P, DeclaredBindResource>,
 
     name: Option<Cow<'static, str>>,
+    group: Option<u32>,
     binding: Option<u32>,
+    read_only: bool,
+    uniform: bool,
+    dynamic_offset: Option<DynamicOffset>,
+    clear_before_dispatch: bool,
+    lifetime: ResourceLifetime,
 
     direction: Option<BuildResult<BindResourceDirection>>,
 }
 
 impl<P> AddBindResourceInfoBuilder<P> {
-    fn new(parent: P, build_fn: BuildResultFn<P, BindResourceCreationInfo>) -> Self {
+    fn new(parent: P, build_fn: BuildResultFn<P, DeclaredBindResource>) -> Self {
         Self {
             parent,
             build_fn,
             name: None,
+            group: None,
             binding: None,
+            read_only: false,
+            uniform: false,
+            dynamic_offset: None,
+            clear_before_dispatch: false,
+            lifetime: ResourceLifetime::Persistent,
             direction: None,
         }
     }
 
+    /// Marks the input buffer as read-only storage, `var<storage, read>` in the shader.
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    /// Marks the input buffer as uniform, `var<uniform>` in the shader, e.g. for the output
+    /// of `UniformBufferNode`. Buffer inputs are bound as storage buffers otherwise.
+    pub fn uniform(mut self) -> Self {
+        self.uniform = true;
+        self
+    }
+
+    /// Fills the output buffer with zeros before each dispatch or render pass of the node,
+    /// the buffer must have `COPY_DST` usage.
+    pub fn clear_before_dispatch(mut self) -> Self {
+        self.clear_before_dispatch = true;
+        self
+    }
+
+    /// Output resource is created anew on each run instead of being reused across frames,
+    /// e.g. for scratch buffers that must not keep the results of the previous run.
+    pub fn transient(mut self) -> Self {
+        self.lifetime = ResourceLifetime::Transient;
+        self
+    }
+
     option_into_setter!(name: Cow<'static, str>);
+    option_setter!(group: u32);
     option_setter!(binding: u32);
+    option_setter!(dynamic_offset: DynamicOffset);
 
     pub fn add(self) -> P {
         let r = || {
-            Ok(BindResourceCreationInfo {
-                name: self.name.ok_or(BuilderError::ValueNotDefined("name"))?,
-                binding: self.binding.unwrap_or(0),
-                direction: self
-                    .direction
-                    .ok_or(BuilderError::ValueNotDefined("direction"))??,
+            let direction = self
+                .direction
+                .ok_or(BuilderError::ValueNotDefined("direction"))??;
+            if self.read_only
+                && !matches!(direction, BindResourceDirection::Input(SlotType::Buffer))
+            {
+                return Err(BuilderError::ValidationError(
+                    "Only input buffers can be read-only".to_string(),
+                ));
+            }
+            if self.uniform {
+                if !matches!(direction, BindResourceDirection::Input(SlotType::Buffer)) {
+                    return Err(BuilderError::ValidationError(
+                        "Only input buffers can be uniform".to_string(),
+                    ));
+                }
+                if self.read_only {
+                    return Err(BuilderError::ValidationError(
+                        "Uniform buffer cannot be read-only storage".to_string(),
+                    ));
+                }
+            }
+            if matches!(
+                direction,
+                BindResourceDirection::Input(SlotType::Entity)
+                    | BindResourceDirection::InputOutput(SlotType::Entity)
+            ) {
+                return Err(BuilderError::ValidationError(
+                    "Entity slots cannot be bound as a GPU resource".to_string(),
+                ));
+            }
+            if self.dynamic_offset.is_some()
+                && !matches!(
+                    direction,
+                    BindResourceDirection::Input(SlotType::Buffer)
+                        | BindResourceDirection::InputOutput(SlotType::Buffer)
+                )
+            {
+                return Err(BuilderError::ValidationError(
+                    "Only input buffers can have a dynamic offset".to_string(),
+                ));
+            }
+            let name = self.name.ok_or(BuilderError::ValueNotDefined("name"))?;
+            if self.clear_before_dispatch {
+                match &direction {
+                    BindResourceDirection::Output(BindResourceCreationDescriptor::Buffer(
+                        BindResourceCreationStrategy::Static(descriptor),
+                    )) if !descriptor.usage.contains(BufferUsages::COPY_DST) => {
+                        return Err(BuilderError::ValidationError(format!(
+                            "Cleared buffer `{}` must have COPY_DST usage",
+                            name
+                        )));
+                    }
+                    BindResourceDirection::Output(BindResourceCreationDescriptor::Buffer(_))
+                    | BindResourceDirection::InputOutput(SlotType::Buffer) => {}
+                    _ => {
+                        return Err(BuilderError::ValidationError(
+                            "Only buffers can be cleared".to_string(),
+                        ));
+                    }
+                }
+            }
+            if self.lifetime == ResourceLifetime::Transient {
+                match &direction {
+                    BindResourceDirection::Output(
+                        BindResourceCreationDescriptor::ExternalTextureView(_),
+                    ) => {
+                        return Err(BuilderError::ValidationError(format!(
+                            "External texture view `{}` is not created by the node and cannot be transient",
+                            name
+                        )));
+                    }
+                    BindResourceDirection::Output(descriptor) if descriptor.is_deferred() => {
+                        return Err(BuilderError::ValidationError(format!(
+                            "Deferred resource `{}` is created once and cannot be transient",
+                            name
+                        )));
+                    }
+                    BindResourceDirection::Output(_) => {}
+                    _ => {
+                        return Err(BuilderError::ValidationError(format!(
+                            "Only output resources can be transient, `{}` is an input",
+                            name
+                        )));
+                    }
+                }
+            }
+            // Output buffers are written by the shader, the other usages depend on the consumers
+            if let BindResourceDirection::Output(BindResourceCreationDescriptor::Buffer(
+                BindResourceCreationStrategy::Static(descriptor),
+            )) = &direction
+            {
+                if !descriptor.usage.contains(BufferUsages::STORAGE) {
+                    return Err(BuilderError::ValidationError(format!(
+                        "Output buffer `{}` must have STORAGE usage to be written by the shader, got {:?}",
+                        name, descriptor.usage
+                    )));
+                }
+            }
+            Ok(DeclaredBindResource {
+                info: BindResourceCreationInfo {
+                    name,
+                    group: self.group.unwrap_or(0),
+                    binding: self.binding.unwrap_or(0),
+                    direction,
+                    read_only: self.read_only,
+                    uniform: self.uniform,
+                    dynamic_offset: self.dynamic_offset,
+                    clear_before_dispatch: self.clear_before_dispatch,
+                    lifetime: self.lifetime,
+                },
+                explicit_group: self.group.is_some(),
             })
         };
 
@@ -250,14 +957,147 @@ impl<P: 'static> SetBindResourceDescriptorBuilder<P> {
 
     pub fn buffer_from_graph_context(
         self,
-        buffer_from_graph_context: fn(
-            &render_graph::RenderGraphContext,
-        ) -> render_resource::BufferDescriptor<'static>,
+        buffer_from_graph_context: impl Fn(&render_graph::RenderGraphContext) -> render_resource::BufferDescriptor<'static>
+            + Send
+            + Sync
+            + 'static,
     ) -> P {
         (self.build_fn)(
             self.parent,
             Ok(BindResourceCreationDescriptor::Buffer(
-                BindResourceCreationStrategy::FromGraphContext(buffer_from_graph_context),
+                BindResourceCreationStrategy::FromGraphContext(GraphContextFn::new(
+                    buffer_from_graph_context,
+                )),
+            )),
+        )
+    }
+
+    pub fn sampler_from_graph_context(
+        self,
+        sampler_from_graph_context: impl Fn(&render_graph::RenderGraphContext) -> render_resource::SamplerDescriptor<'static>
+            + Send
+            + Sync
+            + 'static,
+    ) -> P {
+        (self.build_fn)(
+            self.parent,
+            Ok(BindResourceCreationDescriptor::Sampler(
+                BindResourceCreationStrategy::FromGraphContext(GraphContextFn::new(
+                    sampler_from_graph_context,
+                )),
+            )),
+        )
+    }
+
+    pub fn texture_from_graph_context(
+        self,
+        texture_from_graph_context: impl Fn(&render_graph::RenderGraphContext) -> render_resource::TextureDescriptor<'static>
+            + Send
+            + Sync
+            + 'static,
+    ) -> P {
+        (self.build_fn)(
+            self.parent,
+            Ok(BindResourceCreationDescriptor::Texture(
+                BindResourceCreationStrategy::FromGraphContext(GraphContextFn::new(
+                    texture_from_graph_context,
+                )),
+            )),
+        )
+    }
+
+    /// Storage buffer with `INDIRECT` usage for `count` indirect command arguments written by
+    /// the shader, it can be used by indirect draw or dispatch commands of the following nodes.
+    pub fn indirect_args_buffer(self, label: &'static str, args: IndirectArgs, count: u64) -> P {
+        self.build_buffer(
+            label,
+            args.size() * count,
+            BufferUsages::STORAGE
+                | BufferUsages::INDIRECT
+                | BufferUsages::COPY_SRC
+                | BufferUsages::COPY_DST,
+            false,
+        )
+    }
+
+    /// The buffer descriptor is evaluated once on the first run, for example to size the buffer
+    /// from an actual input, and the created buffer is reused after that.
+    pub fn deferred_buffer(
+        self,
+        buffer_from_first_graph_context: impl Fn(&render_graph::RenderGraphContext) -> render_resource::BufferDescriptor<'static>
+            + Send
+            + Sync
+            + 'static,
+    ) -> P {
+        (self.build_fn)(
+            self.parent,
+            Ok(BindResourceCreationDescriptor::Buffer(
+                BindResourceCreationStrategy::Deferred(GraphContextFn::new(
+                    buffer_from_first_graph_context,
+                )),
+            )),
+        )
+    }
+
+    /// Texture with `STORAGE_BINDING` usage, bound with the view created from the descriptor.
+    /// The access must match the storage texture declaration in the shader.
+    pub fn storage_texture(
+        self,
+        texture: render_resource::TextureDescriptor<'static>,
+        view: render_resource::TextureViewDescriptor<'static>,
+        access: render_resource::StorageTextureAccess,
+    ) -> P {
+        let descriptor = if texture
+            .usage
+            .contains(render_resource::TextureUsages::STORAGE_BINDING)
+        {
+            Ok(BindResourceCreationDescriptor::StorageTexture {
+                texture: BindResourceCreationStrategy::Static(texture),
+                view,
+                access,
+            })
+        } else {
+            Err(BuilderError::ValidationError(format!(
+                "Storage texture `{:?}` must have STORAGE_BINDING usage",
+                texture.label
+            )))
+        };
+        (self.build_fn)(self.parent, descriptor)
+    }
+
+    /// Sampled textures bound as a binding array, one texture is created for each descriptor.
+    /// Requires `TEXTURE_BINDING_ARRAY` device feature.
+    pub fn texture_array(self, textures: Vec<render_resource::TextureDescriptor<'static>>) -> P {
+        let descriptor = if textures.is_empty() {
+            Err(BuilderError::ValidationError(
+                "Texture array must have at least one texture".to_string(),
+            ))
+        } else if let Some(texture) = textures.iter().find(|texture| {
+            !texture
+                .usage
+                .contains(render_resource::TextureUsages::TEXTURE_BINDING)
+        }) {
+            Err(BuilderError::ValidationError(format!(
+                "Texture array element `{:?}` must have TEXTURE_BINDING usage",
+                texture.label
+            )))
+        } else {
+            Ok(BindResourceCreationDescriptor::TextureArray(
+                textures
+                    .into_iter()
+                    .map(BindResourceCreationStrategy::Static)
+                    .collect(),
+            ))
+        };
+        (self.build_fn)(self.parent, descriptor)
+    }
+
+    /// Binds a texture view that is created and owned outside of the crate.
+    pub fn external_texture_view(self, texture_view: render_resource::TextureView) -> P {
+        (self.build_fn)(
+            self.parent,
+            Ok(BindResourceCreationDescriptor::ExternalTextureView(
+                ExternalTextureView(texture_view),
             )),
         )
     }
@@ -329,19 +1169,25 @@ impl<P> SetSlotTypeBuilder<P> {
 #[derive(Default)]
 pub struct SubGraphBuilder {
     name: Option<Cow<'static, str>>,
+    runner_name: Option<Cow<'static, str>>,
 
     graph: RenderGraph,
     providers: HashMap<Entity, ProviderDescriptor>,
+    node_names: Vec<Cow<'static, str>>,
     node_edges: Vec<(NodeLabel, NodeLabel)>,
     slot_edges: Vec<(NodeLabel, SlotLabel, NodeLabel, SlotLabel)>,
     graph_inputs: HashMap<Cow<'static, str>, SlotType>,
     outer_edges: Vec<Edge>,
+    graph_outputs: Vec<SubGraphOutput>,
     trigger: Option<SubGraphTrigger>,
+    ready_for_extraction: Option<bool>,
 }
 
 impl SubGraphBuilder {
     option_setter!(name: Cow<'static, str>);
+    option_into_setter!(runner_name: Cow<'static, str>);
     option_setter!(trigger: SubGraphTrigger);
+    option_setter!(ready_for_extraction: bool);
 
     pub fn add_node_provider<T: NodeProvider + 'static>(
         mut self,
@@ -350,6 +1196,7 @@ impl SubGraphBuilder {
         provider: &T,
     ) -> Self {
         provider.add_node_to_graph(&mut self.graph, node_name.clone());
+        self.node_names.push(node_name.clone());
         self.providers.insert(
             provider_entity,
             ProviderDescriptor {
@@ -366,10 +1213,38 @@ impl SubGraphBuilder {
         node_name: impl Into<Cow<'static, str>>,
         node: T,
     ) -> Self {
+        let node_name = node_name.into();
+        self.node_names.push(node_name.clone());
         self.graph.add_node(node_name, node);
         self
     }
 
+    /// Registers the pre-built graph as a sub graph nested in this sub graph, and adds the
+    /// [`RunNestedGraphNode`] with the same name that runs it. The node has the input slots
+    /// of the nested graph, so it's connected with the usual node and slot edges.
+    pub fn add_sub_graph(self, name: impl Into<Cow<'static, str>>, graph: RenderGraph) -> Self {
+        let name = name.into();
+        let node = RunNestedGraphNode::new(name.clone(), &graph);
+        let mut builder = self.add_node(name.clone(), node);
+        builder.graph.add_sub_graph(name, graph);
+        builder
+    }
+
+    /// Same as `add_node`, but replaces the node with the same name instead of reporting
+    /// the duplicate name on build. The edges of the replaced node are kept.
+    pub fn replace_node<T: render_graph::Node>(
+        mut self,
+        node_name: impl Into<Cow<'static, str>>,
+        node: T,
+    ) -> Self {
+        let node_name = node_name.into();
+        if !self.node_names.contains(&node_name) {
+            self.node_names.push(node_name.clone());
+        }
+        add_or_replace_graph_node(&mut self.graph, node_name, node);
+        self
+    }
+
     pub fn add_node_edge(
         mut self,
         output_node: impl Into<NodeLabel>,
@@ -396,6 +1271,24 @@ impl SubGraphBuilder {
         self
     }
 
+    /// Connects the sources of the shared bindings to the node that declares them.
+    pub fn connect_shared_bindings(
+        mut self,
+        shared: &SharedBindings,
+        node_name: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        let node_name = node_name.into();
+        for binding in &shared.bindings {
+            self = self.add_slot_edge(
+                NodeLabel::Name(binding.source_node.clone()),
+                binding.source_slot.clone(),
+                NodeLabel::Name(node_name.clone()),
+                binding.name.clone(),
+            );
+        }
+        self
+    }
+
     pub fn add_outer_input_node_edge(mut self, output_node: impl Into<NodeLabel>) -> Self {
         self.outer_edges.push(Edge::InputNodeEdge {
             output_node: output_node.into(),
@@ -427,7 +1320,130 @@ impl SubGraphBuilder {
         self
     }
 
+    /// Publishes the output slot of the inner node as the output slot of the runner node with
+    /// `output_slot_name`, so the nodes outside of the sub graph can receive it.
+    /// The runner node outputs the values of the previous run, the resources cached by the inner
+    /// nodes remain the same between the runs.
+    pub fn add_outer_output_slot_edge(
+        mut self,
+        output_node: impl Into<NodeLabel>,
+        output_slot: impl Into<SlotLabel>,
+        output_slot_name: Cow<'static, str>,
+        output_slot_type: SlotType,
+    ) -> Self {
+        let output = SubGraphOutput {
+            slot: SlotInfo::new(output_slot_name.clone(), output_slot_type),
+            value: default(),
+            fallback: default(),
+        };
+        let capture_node_name = format!("outer_output.{}", output_slot_name);
+        self.node_names.push(capture_node_name.clone().into());
+        self.graph.add_node(
+            capture_node_name.clone(),
+            SubGraphOutputNode(output.clone()),
+        );
+        self.graph_outputs.push(output);
+        self.add_slot_edge(
+            output_node,
+            output_slot,
+            NodeLabel::Name(capture_node_name.into()),
+            output_slot_name,
+        )
+    }
+
+    /// Missing nodes and slots are reported by the Render Graph when the edge is added.
+    fn validate_slot_types(
+        &self,
+        out_node: &NodeLabel,
+        out_slot: &SlotLabel,
+        in_node: &NodeLabel,
+        in_slot: &SlotLabel,
+    ) -> BuildResult<()> {
+        let (Ok(out_state), Ok(in_state)) = (
+            self.graph.get_node_state(out_node.clone()),
+            self.graph.get_node_state(in_node.clone()),
+        ) else {
+            return Ok(());
+        };
+        let (Some(out_info), Some(in_info)) = (
+            out_state.output_slots.get_slot(out_slot.clone()),
+            in_state.input_slots.get_slot(in_slot.clone()),
+        ) else {
+            return Ok(());
+        };
+        if out_info.slot_type != in_info.slot_type {
+            return Err(BuilderError::ValidationError(format!(
+                "Slot edge from `{}`.`{}` ({:?}) to `{}`.`{}` ({:?}) connects mismatched slot types",
+                out_state.name.as_deref().unwrap_or(out_state.type_name),
+                out_info.name,
+                out_info.slot_type,
+                in_state.name.as_deref().unwrap_or(in_state.type_name),
+                in_info.name,
+                in_info.slot_type,
+            )));
+        }
+        Ok(())
+    }
+
+    /// `COPY_SRC` usage of the provider outputs copied by the `OutputBuffer`,
+    /// `MultiOutputBuffer`, `OutputTexture` and `CopyNode` nodes.
+    fn required_output_usages(&self) -> Vec<RequiredOutputUsages> {
+        self.slot_edges
+            .iter()
+            .filter_map(|(out_node, out_slot, in_node, in_slot)| {
+                let in_state = self.graph.get_node_state(in_node.clone()).ok()?;
+                let in_slot = in_state.input_slots.get_slot(in_slot.clone())?;
+                let copied = if in_state.node::<output::MultiOutputBuffer>().is_ok() {
+                    true
+                } else if in_state.node::<output::OutputBuffer>().is_ok()
+                    || in_state.node::<output::OutputTexture>().is_ok()
+                {
+                    in_slot.name == output::SLOT_NAME
+                } else if in_state.node::<copy::CopyNode>().is_ok() {
+                    in_slot.name == copy::SLOT_NAME
+                } else {
+                    false
+                };
+                let usages = match (copied, in_slot.slot_type) {
+                    (true, SlotType::Buffer) => RequiredUsages::Buffer(BufferUsages::COPY_SRC),
+                    (true, SlotType::TextureView) => {
+                        RequiredUsages::Texture(TextureUsages::COPY_SRC)
+                    }
+                    _ => return None,
+                };
+                let out_state = self.graph.get_node_state(out_node.clone()).ok()?;
+                let node_name = out_state.name.clone()?;
+                let (entity, _) = self
+                    .providers
+                    .iter()
+                    .find(|(_, provider)| provider.name == node_name)?;
+                let slot = out_state.output_slots.get_slot(out_slot.clone())?;
+                Some(RequiredOutputUsages {
+                    entity: *entity,
+                    node_name,
+                    slot: slot.name.clone(),
+                    usages,
+                })
+            })
+            .collect()
+    }
+
     pub fn build(mut self) -> BuildResult<SubGraph> {
+        let mut names = self.node_names.clone();
+        names.sort_unstable();
+        let mut duplicates: Vec<String> = names
+            .windows(2)
+            .filter(|pair| pair[0] == pair[1])
+            .map(|pair| format!("`{}`", pair[0]))
+            .collect();
+        duplicates.dedup();
+        if !duplicates.is_empty() {
+            return Err(BuilderError::ValidationError(format!(
+                "Sub graph nodes must have unique names, duplicated: {}",
+                duplicates.join(", ")
+            )));
+        }
+
         self.graph.set_input(
             self.graph_inputs
                 .drain()
@@ -443,15 +1459,26 @@ impl SubGraphBuilder {
         }
 
         for (out_node, out_slot, in_node, in_slot) in &self.slot_edges {
+            self.validate_slot_types(out_node, out_slot, in_node, in_slot)?;
             self.graph
                 .try_add_slot_edge(out_node, out_slot, in_node, in_slot)?;
         }
 
+        let required_output_usages = self.required_output_usages();
+        let name = self.name.ok_or(BuilderError::ValueNotDefined("name"))?;
         Ok(SubGraph {
-            name: self.name.ok_or(BuilderError::ValueNotDefined("name"))?,
+            runner_name: self.runner_name.unwrap_or_else(|| name.clone()),
+            name,
             providers: self.providers,
             graph: SubGraphDeployState::Queued(self.outer_edges, self.graph),
             trigger: self.trigger.unwrap_or_default(),
+            first_run: default(),
+            missing: default(),
+            stuck: default(),
+            outputs: self.graph_outputs,
+            switch: default(),
+            ready_for_extraction: self.ready_for_extraction.unwrap_or(true),
+            required_output_usages,
         })
     }
 }
@@ -0,0 +1,700 @@
+use crate::graph::{ProviderState, SubGraph};
+use bevy::diagnostic::{Diagnostic, DiagnosticId, Diagnostics, RegisterDiagnostic};
+use bevy::prelude::*;
+use bevy::utils::{get_short_name, HashMap};
+use bevy_render::render_graph::{self, RenderGraph, SlotInfos};
+use bevy_render::render_resource::{Buffer, BufferDescriptor, BufferUsages, MapMode};
+use bevy_render::renderer::{RenderDevice, RenderQueue};
+use bevy_render::settings::WgpuFeatures;
+use bevy_render::{Render, RenderApp, RenderSet};
+use std::any::type_name;
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::Write;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const MAX_HISTORY_LENGTH: usize = 20;
+
+#[derive(Debug, Default)]
+struct ProviderUpdateDurations {
+    cache_update: Duration,
+    sub_graphs_update: Duration,
+}
+
+/// Duration of the Render World update systems of the `T` node provider type.
+/// The resource is shared between Main and Render worlds.
+#[derive(Resource, Debug)]
+pub struct NodeProviderTimings<T> {
+    durations: Arc<Mutex<ProviderUpdateDurations>>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for NodeProviderTimings<T> {
+    fn default() -> Self {
+        Self {
+            durations: default(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Clone for NodeProviderTimings<T> {
+    fn clone(&self) -> Self {
+        Self {
+            durations: self.durations.clone(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T: 'static> NodeProviderTimings<T> {
+    /// Diagnostic with the update duration of the provider type in milliseconds.
+    pub fn diagnostic_id() -> DiagnosticId {
+        let mut hasher = DefaultHasher::new();
+        type_name::<T>().hash(&mut hasher);
+        DiagnosticId::from_u128(0x6e6f_6465_706c_756d_0000_0000_0000_0000 | hasher.finish() as u128)
+    }
+
+    pub fn update_duration(&self) -> Duration {
+        let durations = self.lock();
+        durations.cache_update + durations.sub_graphs_update
+    }
+
+    pub(crate) fn set_cache_update(&self, duration: Duration) {
+        self.lock().cache_update = duration;
+    }
+
+    pub(crate) fn set_sub_graphs_update(&self, duration: Duration) {
+        self.lock().sub_graphs_update = duration;
+    }
+
+    pub(crate) fn register(app: &mut App) {
+        app.register_diagnostic(
+            Diagnostic::new(
+                Self::diagnostic_id(),
+                format!("{} update", get_short_name(type_name::<T>())),
+                MAX_HISTORY_LENGTH,
+            )
+            .with_suffix("ms"),
+        );
+        app.add_systems(Last, Self::measure_system);
+    }
+
+    fn measure_system(mut diagnostics: Diagnostics, timings: Res<Self>) {
+        diagnostics.add_measurement(Self::diagnostic_id(), || {
+            timings.update_duration().as_secs_f64() * 1000.0
+        });
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ProviderUpdateDurations> {
+        self.durations
+            .lock()
+            .expect("Node provider timings mutex is poisoned")
+    }
+}
+
+/// Opt-in plugin that logs the status of the sub graphs with `SubGraphStatusLog` component.
+pub struct SubGraphStatusLogPlugin;
+
+impl Plugin for SubGraphStatusLogPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Last, SubGraphStatusLog::update_system);
+    }
+}
+
+/// Sub graph name, deployment state and the states of its providers, refreshed each frame and
+/// logged when changed. The report can also be displayed by the app, for example in a text.
+#[derive(Component, Debug, Clone, Default)]
+pub struct SubGraphStatusLog {
+    report: String,
+}
+
+impl SubGraphStatusLog {
+    pub fn report(&self) -> &str {
+        &self.report
+    }
+
+    pub fn format(sub_graph: &SubGraph) -> String {
+        let mut report = format!(
+            "Sub graph `{}`: {}",
+            sub_graph.name(),
+            if sub_graph.is_missing() {
+                "missing from the Render Graph"
+            } else if sub_graph.is_deployed() {
+                "deployed"
+            } else {
+                "waiting for providers"
+            }
+        );
+        let mut providers: Vec<_> = sub_graph.providers().values().collect();
+        providers.sort_by(|a, b| a.name().cmp(b.name()));
+        for provider in providers {
+            let state = match provider.state() {
+                ProviderState::Created => "created".to_string(),
+                ProviderState::Updating => "updating".to_string(),
+                ProviderState::CanCreateNode => "ready".to_string(),
+                ProviderState::Err(err) => format!("error: {}", err),
+            };
+            let _ = write!(report, "\n  `{}`: {}", provider.name(), state);
+        }
+        report
+    }
+
+    fn update_system(mut query: Query<(&SubGraph, &mut Self)>) {
+        for (sub_graph, mut log) in query.iter_mut() {
+            let report = Self::format(sub_graph);
+            if log.report != report {
+                info!("{}", &report);
+                log.report = report;
+            }
+        }
+    }
+}
+
+/// Opt-in plugin that measures the GPU duration of the compute nodes with timestamp queries.
+/// Requires `WgpuFeatures::TIMESTAMP_QUERY` enabled in the `WgpuSettings`, otherwise it only
+/// logs a warning and the nodes are not measured.
+pub struct GpuTimingsPlugin {
+    /// Maximum number of the measured node runs per frame, the rest are not measured.
+    pub max_node_runs: u32,
+}
+
+impl Default for GpuTimingsPlugin {
+    fn default() -> Self {
+        Self { max_node_runs: 64 }
+    }
+}
+
+impl Plugin for GpuTimingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NodeTimings>();
+    }
+
+    fn finish(&self, app: &mut App) {
+        let timings = app.world.resource::<NodeTimings>().clone();
+        let render_app = app
+            .get_sub_app_mut(RenderApp)
+            .expect("Cannot find Render Plugin");
+        render_app.insert_resource(timings);
+        let supported = render_app
+            .world
+            .get_resource::<RenderDevice>()
+            .is_some_and(|device| device.features().contains(WgpuFeatures::TIMESTAMP_QUERY));
+        if !supported {
+            warn!("Timestamp queries are not supported by the render device, GPU timings are disabled");
+            return;
+        }
+        let query_set = TimestampQuerySet::new(
+            render_app.world.resource::<RenderDevice>(),
+            render_app.world.resource::<RenderQueue>(),
+            self.max_node_runs,
+        );
+        render_app.insert_resource(query_set);
+        render_app.add_systems(
+            Render,
+            TimestampQuerySet::resolve_system.in_set(RenderSet::Cleanup),
+        );
+    }
+}
+
+/// Last measured GPU durations of the node runs keyed by the node label, e.g. the debug name of
+/// the compute node. The resource is shared between Main and Render worlds.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct NodeTimings(Arc<Mutex<HashMap<Cow<'static, str>, Duration>>>);
+
+impl NodeTimings {
+    pub fn get(&self, label: &str) -> Option<Duration> {
+        self.0
+            .lock()
+            .expect("Node timings mutex is poisoned")
+            .get(label)
+            .copied()
+    }
+
+    pub fn snapshot(&self) -> HashMap<Cow<'static, str>, Duration> {
+        self.0
+            .lock()
+            .expect("Node timings mutex is poisoned")
+            .clone()
+    }
+
+    fn update(&self, durations: impl IntoIterator<Item = (Cow<'static, str>, Duration)>) {
+        self.0
+            .lock()
+            .expect("Node timings mutex is poisoned")
+            .extend(durations);
+    }
+}
+
+#[derive(Debug, Default)]
+enum TimestampReadback {
+    /// Timestamps of the labeled node runs are being written in the current frame.
+    #[default]
+    Recording,
+    WaitingForMap(Vec<Cow<'static, str>>),
+    Mapped(Vec<Cow<'static, str>>),
+}
+
+/// Timestamp queries written around the compute passes, present in the Render World only when
+/// the device supports them. The timestamps are resolved after the frame is submitted and read
+/// back asynchronously, the nodes are not measured until the previous frame is read back.
+#[derive(Resource)]
+pub struct TimestampQuerySet {
+    query_set: wgpu::QuerySet,
+    capacity: u32,
+    resolve_buffer: Buffer,
+    readback_buffer: Buffer,
+    period: f32,
+    labels: Mutex<Vec<Cow<'static, str>>>,
+    readback: Arc<Mutex<TimestampReadback>>,
+}
+
+impl TimestampQuerySet {
+    fn new(render_device: &RenderDevice, render_queue: &RenderQueue, max_node_runs: u32) -> Self {
+        let capacity = max_node_runs.max(1);
+        let size = u64::from(capacity) * 2 * std::mem::size_of::<u64>() as u64;
+        Self {
+            query_set: render_device
+                .wgpu_device()
+                .create_query_set(&wgpu::QuerySetDescriptor {
+                    label: Some("node_timestamps"),
+                    ty: wgpu::QueryType::Timestamp,
+                    count: capacity * 2,
+                }),
+            capacity,
+            resolve_buffer: render_device.create_buffer(&BufferDescriptor {
+                label: Some("node_timestamps_resolve"),
+                size,
+                usage: BufferUsages::QUERY_RESOLVE | BufferUsages::COPY_SRC,
+                mapped_at_creation: false,
+            }),
+            readback_buffer: render_device.create_buffer(&BufferDescriptor {
+                label: Some("node_timestamps_readback"),
+                size,
+                usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            }),
+            period: render_queue.get_timestamp_period(),
+            labels: default(),
+            readback: default(),
+        }
+    }
+
+    /// Writes the begin timestamp of the labeled run, returns the index of the end timestamp.
+    /// `None` if the run is not measured.
+    pub(crate) fn write_begin(
+        &self,
+        command_encoder: &mut wgpu::CommandEncoder,
+        label: Cow<'static, str>,
+    ) -> Option<u32> {
+        let readback = self
+            .readback
+            .lock()
+            .expect("Timestamp readback mutex is poisoned");
+        if !matches!(*readback, TimestampReadback::Recording) {
+            return None;
+        }
+        let mut labels = self
+            .labels
+            .lock()
+            .expect("Timestamp labels mutex is poisoned");
+        let index = labels.len() as u32;
+        if index >= self.capacity {
+            return None;
+        }
+        labels.push(label);
+        command_encoder.write_timestamp(&self.query_set, index * 2);
+        Some(index * 2 + 1)
+    }
+
+    pub(crate) fn write_end(&self, command_encoder: &mut wgpu::CommandEncoder, index: u32) {
+        command_encoder.write_timestamp(&self.query_set, index);
+    }
+
+    fn resolve_system(
+        query_set: Res<Self>,
+        timings: Res<NodeTimings>,
+        render_device: Res<RenderDevice>,
+        render_queue: Res<RenderQueue>,
+    ) {
+        let mut readback = query_set
+            .readback
+            .lock()
+            .expect("Timestamp readback mutex is poisoned");
+        match std::mem::take(&mut *readback) {
+            TimestampReadback::Recording => {}
+            TimestampReadback::WaitingForMap(labels) => {
+                *readback = TimestampReadback::WaitingForMap(labels);
+                return;
+            }
+            TimestampReadback::Mapped(labels) => {
+                timings.update(query_set.read_durations(labels));
+                return;
+            }
+        }
+        let labels = std::mem::take(
+            &mut *query_set
+                .labels
+                .lock()
+                .expect("Timestamp labels mutex is poisoned"),
+        );
+        if labels.is_empty() {
+            return;
+        }
+        let count = labels.len() as u32 * 2;
+        let size = u64::from(count) * std::mem::size_of::<u64>() as u64;
+        let mut command_encoder =
+            render_device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("node_timestamps_resolve"),
+            });
+        command_encoder.resolve_query_set(
+            &query_set.query_set,
+            0..count,
+            &query_set.resolve_buffer,
+            0,
+        );
+        command_encoder.copy_buffer_to_buffer(
+            &query_set.resolve_buffer,
+            0,
+            &query_set.readback_buffer,
+            0,
+            size,
+        );
+        render_queue.submit([command_encoder.finish()]);
+        *readback = TimestampReadback::WaitingForMap(labels);
+        let state = query_set.readback.clone();
+        render_device.map_buffer(
+            &query_set.readback_buffer.slice(..size),
+            MapMode::Read,
+            move |result| {
+                let mut state = state.lock().expect("Timestamp readback mutex is poisoned");
+                let TimestampReadback::WaitingForMap(labels) = std::mem::take(&mut *state) else {
+                    return;
+                };
+                match result {
+                    Ok(()) => *state = TimestampReadback::Mapped(labels),
+                    Err(err) => error!("Cannot map the node timestamps: {}", err),
+                }
+            },
+        );
+    }
+
+    fn read_durations(&self, labels: Vec<Cow<'static, str>>) -> Vec<(Cow<'static, str>, Duration)> {
+        let durations = {
+            let size = labels.len() as u64 * 2 * std::mem::size_of::<u64>() as u64;
+            let mapped = self.readback_buffer.slice(..size).get_mapped_range();
+            let timestamps: Vec<u64> = bytemuck::pod_collect_to_vec(&mapped);
+            labels
+                .into_iter()
+                .zip(timestamps.chunks_exact(2))
+                .map(|(label, pair)| {
+                    let ticks = pair[1].wrapping_sub(pair[0]);
+                    let nanos = ticks as f64 * f64::from(self.period);
+                    (label, Duration::from_nanos(nanos as u64))
+                })
+                .collect()
+        };
+        self.readback_buffer.unmap();
+        durations
+    }
+}
+
+/// Opt-in plugin that reports the estimated GPU memory allocated by the nodes: the output
+/// resources of the compute and render nodes and the buffers of the input nodes.
+/// The nodes are reported after their first run while they are in the Render Graph.
+pub struct GpuMemoryUsagePlugin;
+
+impl Plugin for GpuMemoryUsagePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NodeMemoryUsage>();
+        app.register_diagnostic(
+            Diagnostic::new(
+                NodeMemoryUsage::DIAGNOSTIC_ID,
+                "nodes GPU memory",
+                MAX_HISTORY_LENGTH,
+            )
+            .with_suffix("MiB"),
+        );
+        app.add_systems(Last, NodeMemoryUsage::measure_system);
+    }
+
+    fn finish(&self, app: &mut App) {
+        let usage = app.world.resource::<NodeMemoryUsage>().clone();
+        let render_app = app
+            .get_sub_app_mut(RenderApp)
+            .expect("Cannot find Render Plugin");
+        render_app.insert_resource(usage);
+        render_app.add_systems(
+            Render,
+            NodeMemoryUsage::publish_system.in_set(RenderSet::Cleanup),
+        );
+    }
+}
+
+/// Estimated GPU memory in bytes keyed by `sub_graph_name/node_name`. Every node records its
+/// resources when it runs, the records are kept until the node is removed from the Render Graph,
+/// so the memoized, disabled and rarely triggered nodes are reported as well.
+/// Published after each frame is rendered, the resource is shared between Main and Render worlds.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct NodeMemoryUsage {
+    /// Keyed by the address of the node in the Render Graph, that is stable while it's added.
+    nodes: Arc<Mutex<HashMap<usize, u64>>>,
+    published: Arc<Mutex<HashMap<Cow<'static, str>, u64>>>,
+}
+
+impl NodeMemoryUsage {
+    /// Diagnostic with the total memory of the nodes in MiB.
+    pub const DIAGNOSTIC_ID: DiagnosticId =
+        DiagnosticId::from_u128(0x6e6f_6465_706c_756d_6d65_6d6f_7279_0001);
+
+    pub fn get(&self, label: &str) -> Option<u64> {
+        self.lock_published().get(label).copied()
+    }
+
+    pub fn snapshot(&self) -> HashMap<Cow<'static, str>, u64> {
+        self.lock_published().clone()
+    }
+
+    pub fn total(&self) -> u64 {
+        self.lock_published().values().sum()
+    }
+
+    /// Records the memory of the running node if the plugin is enabled, replacing the record
+    /// of its previous run.
+    pub(crate) fn record_in<T: render_graph::Node>(world: &World, node: &T, bytes: u64) {
+        if let Some(usage) = world.get_resource::<Self>() {
+            usage
+                .nodes
+                .lock()
+                .expect("Node memory usage mutex is poisoned")
+                .insert(node_address(node), bytes);
+        }
+    }
+
+    /// Labels the records with the names of the nodes and drops the records of removed nodes.
+    fn publish_system(usage: Res<Self>, render_graph: Res<RenderGraph>) {
+        let mut nodes = usage
+            .nodes
+            .lock()
+            .expect("Node memory usage mutex is poisoned");
+        let mut published = HashMap::with_capacity(nodes.len());
+        let mut found = HashMap::with_capacity(nodes.len());
+        for (sub_graph_name, sub_graph) in render_graph.iter_sub_graphs() {
+            collect_node_memory(
+                sub_graph_name,
+                sub_graph,
+                &nodes,
+                &mut published,
+                &mut found,
+            );
+        }
+        *nodes = found;
+        drop(nodes);
+        *usage.lock_published() = published;
+    }
+
+    fn measure_system(mut diagnostics: Diagnostics, usage: Res<Self>) {
+        diagnostics.add_measurement(Self::DIAGNOSTIC_ID, || {
+            usage.total() as f64 / (1024.0 * 1024.0)
+        });
+    }
+
+    fn lock_published(&self) -> std::sync::MutexGuard<'_, HashMap<Cow<'static, str>, u64>> {
+        self.published
+            .lock()
+            .expect("Node memory usage mutex is poisoned")
+    }
+}
+
+fn node_address<T: render_graph::Node + ?Sized>(node: &T) -> usize {
+    node as *const T as *const () as usize
+}
+
+fn collect_node_memory(
+    graph_name: &str,
+    graph: &RenderGraph,
+    nodes: &HashMap<usize, u64>,
+    published: &mut HashMap<Cow<'static, str>, u64>,
+    found: &mut HashMap<usize, u64>,
+) {
+    for node_state in graph.iter_nodes() {
+        let address = node_address(node_state.node.as_ref());
+        if let Some(bytes) = nodes.get(&address) {
+            let node_name = node_state.name.as_deref().unwrap_or(node_state.type_name);
+            published.insert(format!("{}/{}", graph_name, node_name).into(), *bytes);
+            found.insert(address, *bytes);
+        }
+    }
+    for (sub_graph_name, sub_graph) in graph.iter_sub_graphs() {
+        collect_node_memory(sub_graph_name, sub_graph, nodes, published, found);
+    }
+}
+
+/// Read-only description of the sub graph structure for debugging: the nodes with their slots,
+/// the edges and the states of the node providers.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct GraphDescription {
+    pub name: String,
+    pub deployed: bool,
+    pub nodes: Vec<NodeDescription>,
+    pub edges: Vec<EdgeDescription>,
+    pub providers: Vec<ProviderDescription>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeDescription {
+    pub name: String,
+    pub type_name: String,
+    pub inputs: Vec<SlotDescription>,
+    pub outputs: Vec<SlotDescription>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct SlotDescription {
+    pub name: String,
+    pub slot_type: String,
+}
+
+/// Slots are defined for the slot edges only.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct EdgeDescription {
+    pub output_node: String,
+    pub output_slot: Option<String>,
+    pub input_node: String,
+    pub input_slot: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProviderDescription {
+    pub node_name: String,
+    pub state: String,
+}
+
+impl GraphDescription {
+    pub fn from_render_graph(name: impl Into<String>, graph: &RenderGraph) -> Self {
+        let node_name = |state: &render_graph::NodeState| {
+            state
+                .name
+                .as_ref()
+                .map_or_else(|| format!("{:?}", state.id), |name| name.to_string())
+        };
+        let slots = |slots: &SlotInfos| {
+            slots
+                .iter()
+                .map(|slot| SlotDescription {
+                    name: slot.name.to_string(),
+                    slot_type: format!("{:?}", slot.slot_type),
+                })
+                .collect()
+        };
+
+        let mut nodes: Vec<NodeDescription> = graph
+            .iter_nodes()
+            .map(|state| NodeDescription {
+                name: node_name(state),
+                type_name: state.type_name.to_string(),
+                inputs: slots(&state.input_slots),
+                outputs: slots(&state.output_slots),
+            })
+            .collect();
+        nodes.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut edges: Vec<EdgeDescription> = graph
+            .iter_nodes()
+            .flat_map(|state| state.edges.output_edges())
+            .filter_map(|edge| {
+                let output = graph.get_node_state(edge.get_output_node()).ok()?;
+                let input = graph.get_node_state(edge.get_input_node()).ok()?;
+                let (output_slot, input_slot) = match edge {
+                    render_graph::Edge::SlotEdge {
+                        output_index,
+                        input_index,
+                        ..
+                    } => (
+                        output
+                            .output_slots
+                            .get_slot(*output_index)
+                            .map(|slot| slot.name.to_string()),
+                        input
+                            .input_slots
+                            .get_slot(*input_index)
+                            .map(|slot| slot.name.to_string()),
+                    ),
+                    render_graph::Edge::NodeEdge { .. } => (None, None),
+                };
+                Some(EdgeDescription {
+                    output_node: node_name(output),
+                    output_slot,
+                    input_node: node_name(input),
+                    input_slot,
+                })
+            })
+            .collect();
+        edges.sort_by(|a, b| {
+            (&a.output_node, &a.input_node, &a.output_slot).cmp(&(
+                &b.output_node,
+                &b.input_node,
+                &b.output_slot,
+            ))
+        });
+
+        Self {
+            name: name.into(),
+            nodes,
+            edges,
+            ..default()
+        }
+    }
+
+    /// Graphviz graph with the slots in the node labels and the slot names on the edges.
+    pub fn to_dot(&self) -> String {
+        let slots = |slots: &[SlotDescription]| {
+            slots
+                .iter()
+                .map(|slot| format!("{}: {}", slot.name, slot.slot_type))
+                .collect::<Vec<_>>()
+                .join("\\n")
+        };
+        let mut dot = format!("digraph \"{}\" {{\n", self.name);
+        for node in &self.nodes {
+            let state = self
+                .providers
+                .iter()
+                .find(|provider| provider.node_name == node.name)
+                .map(|provider| format!("\\n[{}]", provider.state))
+                .unwrap_or_default();
+            let _ = writeln!(
+                dot,
+                "    \"{}\" [shape=record, label=\"{{{{{}}}|{}{}|{{{}}}}}\"];",
+                node.name,
+                slots(&node.inputs),
+                node.name,
+                state,
+                slots(&node.outputs)
+            );
+        }
+        for edge in &self.edges {
+            let label = match (&edge.output_slot, &edge.input_slot) {
+                (Some(output_slot), Some(input_slot)) => {
+                    format!(" [label=\"{} -> {}\"]", output_slot, input_slot)
+                }
+                _ => " [style=dashed]".to_string(),
+            };
+            let _ = writeln!(
+                dot,
+                "    \"{}\" -> \"{}\"{};",
+                edge.output_node, edge.input_node, label
+            );
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
@@ -1,24 +1,36 @@
-use bevy::log::warn;
+use crate::diagnostic::{GraphDescription, ProviderDescription};
+use crate::node::output::validate_output_buffers;
+use crate::node::{fallback_slot_value, LogOnce, NodeSwitch};
 use bevy::prelude::*;
-use bevy::utils::HashMap;
+use bevy::utils::{HashMap, HashSet};
 use bevy_render::render_graph::{NodeRunError, RenderGraph, RenderGraphContext, SlotInfo};
-use bevy_render::renderer::RenderContext;
+use bevy_render::render_resource::{BufferUsages, TextureUsages};
+use bevy_render::renderer::{RenderContext, RenderDevice};
 use bevy_render::RenderSet::PrepareResources;
 use bevy_render::{render_graph, MainWorld, Render, RenderApp};
 use std::any::TypeId;
 use std::borrow::Cow;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+pub mod immediate;
 
 pub struct SubGraphPlugin;
 
 impl Plugin for SubGraphPlugin {
-    fn build(&self, _app: &mut App) {}
+    fn build(&self, app: &mut App) {
+        app.add_event::<SubGraphRun>();
+        app.init_resource::<SubGraphRuns>();
+        app.add_systems(PreUpdate, SubGraphRuns::send_events);
+    }
 
     fn finish(&self, app: &mut App) {
+        let runs = app.world.resource::<SubGraphRuns>().clone();
         let render_app = app
             .get_sub_app_mut(RenderApp)
             .expect("Cannot find Render Plugin");
+        render_app.insert_resource(runs);
         render_app.init_resource::<SubGraphCache>();
         render_app.add_systems(ExtractSchedule, SubGraph::extract_to_render_world);
         render_app.add_systems(
@@ -28,6 +40,33 @@ impl Plugin for SubGraphPlugin {
     }
 }
 
+/// Sent in the main world each time the runner node has run the nodes of the sub graph,
+/// unlike the deployment, that happens once, and the read back of the outputs.
+/// Runs of the frame are received on the following update of the main world.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct SubGraphRun {
+    pub entity: Entity,
+    pub name: Cow<'static, str>,
+}
+
+/// Runs recorded by the runner nodes in the Render World, drained into `SubGraphRun` events.
+#[derive(Resource, Debug, Clone, Default)]
+pub(crate) struct SubGraphRuns(Arc<Mutex<Vec<SubGraphRun>>>);
+
+impl SubGraphRuns {
+    fn push(&self, run: SubGraphRun) {
+        self.0
+            .lock()
+            .expect("Sub graph runs mutex is poisoned")
+            .push(run);
+    }
+
+    fn send_events(runs: Res<Self>, mut events: EventWriter<SubGraphRun>) {
+        let runs = std::mem::take(&mut *runs.0.lock().expect("Sub graph runs mutex is poisoned"));
+        events.send_batch(runs);
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ProviderDescriptor {
     pub(crate) name: Cow<'static, str>,
@@ -35,6 +74,16 @@ pub struct ProviderDescriptor {
     pub(crate) state: ProviderState,
 }
 
+impl ProviderDescriptor {
+    pub fn name(&self) -> &Cow<'static, str> {
+        &self.name
+    }
+
+    pub fn state(&self) -> &ProviderState {
+        &self.state
+    }
+}
+
 #[derive(Debug, Default, Clone, Eq, PartialEq)]
 pub enum ProviderState {
     #[default]
@@ -44,6 +93,14 @@ pub enum ProviderState {
     Err(String),
 }
 
+/// Status of the node provider mirrored to its main world entity by the sub graph,
+/// contains the provider error until it recovers.
+#[derive(Component, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProviderStatus {
+    pub node_name: Cow<'static, str>,
+    pub error: Option<String>,
+}
+
 #[derive(Debug, Clone)]
 pub enum Edge {
     InputSlotEdge {
@@ -71,17 +128,336 @@ pub enum SubGraphTrigger {
     #[default]
     Always,
     Manual(Arc<AtomicBool>),
+    /// Same as `Manual`, but `completed` is incremented each time the sub graph is run
+    /// by the runner node, so the main world can check that the requested work was submitted.
+    ManualWithAck {
+        request: Arc<AtomicBool>,
+        completed: Arc<AtomicUsize>,
+    },
+    /// Runs on the first frame and then once per `frames` frames.
+    EveryNFrames {
+        frames: u32,
+        counter: Arc<AtomicU32>,
+    },
+    /// Runs each time the accumulated render world time reaches the interval.
+    FixedInterval {
+        interval: Duration,
+        accumulator: Arc<Mutex<IntervalAccumulator>>,
+    },
+    /// Runs when the condition returns `true` for the Render World of the current frame.
+    When(WorldCondition),
+    /// Runs on each frame until the sub graph is run by the runner node once, e.g. for the
+    /// initialization passes. The flag is set after the run, so the frames skipped while the
+    /// sub graph is not deployed or cannot be encoded don't count.
+    Once(Arc<AtomicBool>),
+}
+
+/// Condition of the `When` trigger, evaluated by the runner node against the Render World.
+#[derive(Clone)]
+pub struct WorldCondition(pub Arc<dyn Fn(&World) -> bool + Send + Sync>);
+
+impl std::fmt::Debug for WorldCondition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("WorldCondition").finish_non_exhaustive()
+    }
+}
+
+/// Time accumulated by the `FixedInterval` trigger since its last run.
+#[derive(Debug, Default)]
+pub struct IntervalAccumulator {
+    last_check: Option<Instant>,
+    elapsed: Duration,
+}
+
+impl IntervalAccumulator {
+    fn elapsed_at(&self, now: Instant) -> Duration {
+        self.elapsed
+            + self
+                .last_check
+                .map_or(Duration::ZERO, |last_check| now - last_check)
+    }
+}
+
+impl SubGraphTrigger {
+    pub fn every_n_frames(frames: u32) -> Self {
+        SubGraphTrigger::EveryNFrames {
+            frames: frames.max(1),
+            counter: default(),
+        }
+    }
+
+    pub fn fixed_interval(interval: Duration) -> Self {
+        SubGraphTrigger::FixedInterval {
+            interval,
+            accumulator: default(),
+        }
+    }
+
+    pub fn once() -> Self {
+        SubGraphTrigger::Once(default())
+    }
+
+    pub fn when(condition: impl Fn(&World) -> bool + Send + Sync + 'static) -> Self {
+        SubGraphTrigger::When(WorldCondition(Arc::new(condition)))
+    }
+
+    /// Runs when the condition returns `true` for the resource of the Render World, the resource
+    /// must be extracted, e.g. by `ExtractResourcePlugin`. Doesn't run while the resource is missing.
+    pub fn when_resource<R: Resource>(condition: fn(&R) -> bool) -> Self {
+        Self::when(move |world| world.get_resource::<R>().is_some_and(condition))
+    }
+
+    /// Checks the trigger condition for the current run and consumes it if required.
+    /// `Manual` trigger fires exactly once for each `store(true)`.
+    /// `When` condition requires the Render World and is never met, see [`Self::should_run_in`].
+    pub fn should_run(&self) -> bool {
+        self.check(None)
+    }
+
+    /// Same as [`Self::should_run`], the `When` condition is evaluated against the world.
+    /// Used by the runner node with the Render World.
+    pub fn should_run_in(&self, world: &World) -> bool {
+        self.check(Some(world))
+    }
+
+    fn check(&self, world: Option<&World>) -> bool {
+        match self {
+            SubGraphTrigger::Always => true,
+            SubGraphTrigger::Once(completed) => !completed.load(Ordering::Acquire),
+            SubGraphTrigger::When(condition) => world.is_some_and(|world| (condition.0)(world)),
+            SubGraphTrigger::Manual(manual) => manual.swap(false, Ordering::Relaxed),
+            SubGraphTrigger::ManualWithAck { request, .. } => {
+                request.swap(false, Ordering::Relaxed)
+            }
+            SubGraphTrigger::EveryNFrames { frames, counter } => {
+                let previous = counter
+                    .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |counter| {
+                        Some((counter + 1) % frames.max(&1))
+                    })
+                    .expect("Update function always returns a value");
+                previous == 0
+            }
+            SubGraphTrigger::FixedInterval {
+                interval,
+                accumulator,
+            } => {
+                let mut accumulator = accumulator
+                    .lock()
+                    .expect("Interval accumulator mutex is poisoned");
+                let now = Instant::now();
+                let elapsed = accumulator.elapsed_at(now);
+                accumulator.last_check = Some(now);
+                if elapsed >= *interval {
+                    // Skipped intervals are dropped instead of running several times in a row
+                    accumulator.elapsed = Duration::from_nanos(
+                        (elapsed.as_nanos() % interval.as_nanos().max(1)) as u64,
+                    );
+                    true
+                } else {
+                    accumulator.elapsed = elapsed;
+                    false
+                }
+            }
+        }
+    }
+
+    /// Called by the runner node after the sub graph was run.
+    pub(crate) fn acknowledge(&self) {
+        match self {
+            SubGraphTrigger::ManualWithAck { completed, .. } => {
+                completed.fetch_add(1, Ordering::Release);
+            }
+            SubGraphTrigger::Once(completed) => completed.store(true, Ordering::Release),
+            _ => {}
+        }
+    }
+
+    /// Number of the acknowledged runs, `None` if the trigger doesn't track them.
+    pub fn completed_runs(&self) -> Option<usize> {
+        match self {
+            SubGraphTrigger::ManualWithAck { completed, .. } => {
+                Some(completed.load(Ordering::Acquire))
+            }
+            SubGraphTrigger::Once(completed) => Some(completed.load(Ordering::Acquire).into()),
+            _ => None,
+        }
+    }
+
+    /// Checks whether the sub graph is going to run on the next frame, without consuming the trigger.
+    /// `When` condition requires the Render World, so it's always considered pending.
+    pub fn is_pending(&self) -> bool {
+        match self {
+            SubGraphTrigger::Always | SubGraphTrigger::When(_) => true,
+            SubGraphTrigger::Manual(manual) => manual.load(Ordering::Relaxed),
+            SubGraphTrigger::ManualWithAck { request, .. } => request.load(Ordering::Relaxed),
+            SubGraphTrigger::EveryNFrames { counter, .. } => counter.load(Ordering::Relaxed) == 0,
+            SubGraphTrigger::Once(completed) => !completed.load(Ordering::Acquire),
+            SubGraphTrigger::FixedInterval {
+                interval,
+                accumulator,
+            } => {
+                accumulator
+                    .lock()
+                    .expect("Interval accumulator mutex is poisoned")
+                    .elapsed_at(Instant::now())
+                    >= *interval
+            }
+        }
+    }
+
+    /// Number of the pending runs, `None` if the runs are not limited.
+    pub fn remaining_runs(&self) -> Option<u32> {
+        match self {
+            SubGraphTrigger::Always
+            | SubGraphTrigger::When(_)
+            | SubGraphTrigger::EveryNFrames { .. }
+            | SubGraphTrigger::FixedInterval { .. } => None,
+            SubGraphTrigger::Manual(manual) => Some(manual.load(Ordering::Relaxed).into()),
+            SubGraphTrigger::ManualWithAck { request, .. } => {
+                Some(request.load(Ordering::Relaxed).into())
+            }
+            SubGraphTrigger::Once(completed) => Some((!completed.load(Ordering::Acquire)).into()),
+        }
+    }
 }
 
 #[derive(Component, Debug)]
 pub struct SubGraph {
     pub(crate) name: Cow<'static, str>,
+    pub(crate) runner_name: Cow<'static, str>,
     pub(crate) providers: HashMap<Entity, ProviderDescriptor>,
     pub(crate) graph: SubGraphDeployState,
     pub(crate) trigger: SubGraphTrigger,
+    pub(crate) first_run: Arc<AtomicBool>,
+    pub(crate) missing: Arc<AtomicBool>,
+    pub(crate) stuck: Arc<AtomicBool>,
+    pub(crate) outputs: Vec<SubGraphOutput>,
+    pub(crate) switch: NodeSwitch,
+    pub(crate) ready_for_extraction: bool,
+    pub(crate) required_output_usages: Vec<RequiredOutputUsages>,
+}
+
+/// Usages required by the node connected to the output slot of the provider.
+#[derive(Debug, Clone)]
+pub(crate) struct RequiredOutputUsages {
+    pub(crate) entity: Entity,
+    pub(crate) node_name: Cow<'static, str>,
+    pub(crate) slot: Cow<'static, str>,
+    pub(crate) usages: RequiredUsages,
+}
+
+/// Usages of the buffer or of the texture created for the output slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RequiredUsages {
+    Buffer(BufferUsages),
+    Texture(TextureUsages),
+}
+
+/// Output slot of the runner node, the value is captured inside the sub graph on each run.
+#[derive(Debug, Clone)]
+pub(crate) struct SubGraphOutput {
+    pub(crate) slot: SlotInfo,
+    pub(crate) value: Arc<Mutex<Option<render_graph::SlotValue>>>,
+    pub(crate) fallback: Arc<OnceLock<render_graph::SlotValue>>,
+}
+
+impl SubGraphOutput {
+    fn value(&self) -> Option<render_graph::SlotValue> {
+        self.value
+            .lock()
+            .expect("Sub graph output mutex is poisoned")
+            .clone()
+    }
+
+    /// Placeholder published before the first run, the Render Graph requires all outputs to be set.
+    /// Created once and reused until the value is captured.
+    fn fallback_value(
+        &self,
+        world: &World,
+        render_device: &RenderDevice,
+    ) -> render_graph::SlotValue {
+        self.fallback
+            .get_or_init(|| fallback_slot_value(self.slot.slot_type, world, render_device))
+            .clone()
+    }
+}
+
+/// Node added to the sub graph for each outer output slot, stores the received value.
+pub(crate) struct SubGraphOutputNode(pub(crate) SubGraphOutput);
+
+impl render_graph::Node for SubGraphOutputNode {
+    fn input(&self) -> Vec<SlotInfo> {
+        vec![self.0.slot.clone()]
+    }
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        _render_context: &mut RenderContext,
+        _world: &World,
+    ) -> Result<(), NodeRunError> {
+        let value = graph.get_input(0)?.clone();
+        *self
+            .0
+            .value
+            .lock()
+            .expect("Sub graph output mutex is poisoned") = Some(value);
+        Ok(())
+    }
 }
 
 impl SubGraph {
+    /// Returns true after the runner node has successfully submitted the sub graph at least once.
+    /// Unlike the deployment state, this confirms that the trigger fired.
+    /// The sub graph is only queued at this point, its nodes are run by the Render Graph after
+    /// the runner node.
+    pub fn has_run(&self) -> bool {
+        self.first_run.load(Ordering::Acquire)
+    }
+
+    /// Returns true after the sub graph is added to the Render Graph, synced from the Render World.
+    pub fn is_deployed(&self) -> bool {
+        matches!(self.graph, SubGraphDeployState::Deployed)
+    }
+
+    /// Returns true if the deployed sub graph was removed from the Render Graph, so the runner
+    /// node cannot run it anymore. The sub graph should be spawned again to be redeployed.
+    pub fn is_missing(&self) -> bool {
+        self.missing.load(Ordering::Acquire)
+    }
+
+    /// Returns true while the queued sub graph cannot be deployed because one of the node
+    /// providers failed, see [`ProviderStatus`] of the provider entities for the error,
+    /// or because one of the nodes requires a feature that the render device doesn't support.
+    pub fn is_stuck(&self) -> bool {
+        self.stuck.load(Ordering::Acquire)
+    }
+
+    pub fn trigger(&self) -> &SubGraphTrigger {
+        &self.trigger
+    }
+
+    /// Switch that pauses the sub graph without removing it from the Render Graph.
+    /// Paused runner node doesn't consume the trigger and publishes the last captured outputs.
+    pub fn switch(&self) -> &NodeSwitch {
+        &self.switch
+    }
+
+    /// Queued sub graph is moved to the Render World on the next extract only when it's ready,
+    /// so the deployment can wait for an external setup. True by default.
+    pub fn is_ready_for_extraction(&self) -> bool {
+        self.ready_for_extraction
+    }
+
+    pub fn set_ready_for_extraction(&mut self, ready: bool) {
+        self.ready_for_extraction = ready;
+    }
+
+    /// Shared flag that is set after the sub graph is encoded or queued for the first time.
+    pub fn first_run_flag(&self) -> Arc<AtomicBool> {
+        self.first_run.clone()
+    }
+
     pub fn providers_state_summary(&self) -> ProviderState {
         let mut has_created = false;
         let mut has_updating = false;
@@ -111,11 +487,18 @@ impl SubGraph {
     fn extract_to_render_world(
         mut main_world: ResMut<MainWorld>,
         mut sub_graph_cache: ResMut<SubGraphCache>,
+        mut render_graph: ResMut<RenderGraph>,
     ) {
         let mut query = main_world.query::<(&mut Self, Entity)>();
+        let mut statuses: Vec<(Entity, ProviderStatus)> = default();
+        let alive: HashSet<Entity> = query.iter(&main_world).map(|(_, entity)| entity).collect();
+        sub_graph_cache.remove_despawned(&alive, &mut render_graph);
 
         for (mut sub_graph, entity) in query.iter_mut(&mut main_world) {
             if matches!(sub_graph.graph, SubGraphDeployState::Queued(..)) {
+                if !sub_graph.ready_for_extraction {
+                    continue;
+                }
                 let graph = std::mem::replace(
                     &mut sub_graph.graph,
                     SubGraphDeployState::MovedToRenderWorld,
@@ -124,20 +507,97 @@ impl SubGraph {
                     entity,
                     SubGraph {
                         name: sub_graph.name.clone(),
+                        runner_name: sub_graph.runner_name.clone(),
                         providers: sub_graph.providers.clone(),
                         graph,
                         trigger: sub_graph.trigger.clone(),
+                        first_run: sub_graph.first_run.clone(),
+                        missing: sub_graph.missing.clone(),
+                        stuck: sub_graph.stuck.clone(),
+                        outputs: sub_graph.outputs.clone(),
+                        switch: sub_graph.switch.clone(),
+                        ready_for_extraction: true,
+                        required_output_usages: sub_graph.required_output_usages.clone(),
                     },
                 );
             } else if let Some(cached) = sub_graph_cache.0.get(&entity) {
                 // Sync providers state
                 sub_graph.providers = cached.providers.clone();
+                if matches!(cached.graph, SubGraphDeployState::Deployed) && !sub_graph.is_deployed()
+                {
+                    sub_graph.graph = SubGraphDeployState::Deployed;
+                }
+                statuses.extend(sub_graph.providers.iter().map(|(entity, descriptor)| {
+                    let error = match &descriptor.state {
+                        ProviderState::Err(err) => Some(err.clone()),
+                        _ => None,
+                    };
+                    let status = ProviderStatus {
+                        node_name: descriptor.name.clone(),
+                        error,
+                    };
+                    (*entity, status)
+                }));
+            }
+        }
+
+        for (entity, status) in statuses {
+            let Some(mut provider_entity) = main_world.get_entity_mut(entity) else {
+                continue;
+            };
+            match provider_entity.get_mut::<ProviderStatus>() {
+                Some(mut current) => {
+                    if *current != status {
+                        *current = status;
+                    }
+                }
+                None => {
+                    provider_entity.insert(status);
+                }
             }
         }
     }
     pub fn name(&self) -> &Cow<'static, str> {
         &self.name
     }
+
+    /// Failed provider blocks the deployment, logged once until the providers recover.
+    fn update_stuck(&self) {
+        let failed = self
+            .providers
+            .values()
+            .find_map(|descriptor| match &descriptor.state {
+                ProviderState::Err(err) => Some((&descriptor.name, err)),
+                _ => None,
+            });
+        match failed {
+            Some((node_name, err)) => {
+                if !self.stuck.swap(true, Ordering::AcqRel) {
+                    error!(
+                        "Sub graph {} cannot be deployed, node {} failed: {}",
+                        &self.name, node_name, err
+                    );
+                }
+            }
+            None => self.stuck.store(false, Ordering::Release),
+        }
+    }
+
+    fn mark_missing(&self) {
+        mark_missing(&self.missing, &self.name);
+    }
+
+    /// Node not supported by the render device blocks the deployment, logged once.
+    fn mark_unsupported(&self, err: &str) {
+        if !self.stuck.swap(true, Ordering::AcqRel) {
+            error!("Sub graph {} cannot be deployed: {}", &self.name, err);
+        }
+    }
+
+    /// Name of the node that runs the sub graph in the Render Graph.
+    pub fn runner_name(&self) -> &Cow<'static, str> {
+        &self.runner_name
+    }
     pub fn providers(&self) -> &HashMap<Entity, ProviderDescriptor> {
         &self.providers
     }
@@ -147,6 +607,72 @@ impl SubGraph {
 pub struct SubGraphCache(pub(crate) HashMap<Entity, SubGraph>);
 
 impl SubGraphCache {
+    /// Descriptions of the cached sub graphs, deployed to the Render Graph or still queued.
+    pub fn describe(&self, render_graph: &RenderGraph) -> Vec<GraphDescription> {
+        let mut descriptions: Vec<GraphDescription> = self
+            .0
+            .values()
+            .filter_map(|sub_graph| {
+                let (graph, deployed) = match &sub_graph.graph {
+                    SubGraphDeployState::Queued(_, graph) => (graph, false),
+                    SubGraphDeployState::Deployed => {
+                        (render_graph.get_sub_graph(&sub_graph.name)?, true)
+                    }
+                    SubGraphDeployState::MovedToRenderWorld => return None,
+                };
+                let mut description =
+                    GraphDescription::from_render_graph(sub_graph.name.clone(), graph);
+                description.deployed = deployed;
+                description.providers = sub_graph
+                    .providers
+                    .values()
+                    .map(|provider| ProviderDescription {
+                        node_name: provider.name.to_string(),
+                        state: format!("{:?}", provider.state),
+                    })
+                    .collect();
+                description
+                    .providers
+                    .sort_by(|a, b| a.node_name.cmp(&b.node_name));
+                Some(description)
+            })
+            .collect();
+        descriptions.sort_by(|a, b| a.name.cmp(&b.name));
+        descriptions
+    }
+
+    /// Removes the sub graphs whose main world entity was despawned or lost the `SubGraph`
+    /// component, deployed ones are removed from the Render Graph with the runner node.
+    fn remove_despawned(&mut self, alive: &HashSet<Entity>, render_graph: &mut RenderGraph) {
+        let removed: Vec<Entity> = self
+            .0
+            .keys()
+            .filter(|entity| !alive.contains(*entity))
+            .copied()
+            .collect();
+        for entity in removed {
+            let Some(sub_graph) = self.0.remove(&entity) else {
+                continue;
+            };
+            if !matches!(sub_graph.graph, SubGraphDeployState::Deployed) {
+                continue;
+            }
+            // Runner node is removed together with its outer edges, so no edge is left dangling
+            // when the sub graph is removed
+            if let Err(err) = render_graph.remove_node(sub_graph.runner_name.clone()) {
+                error!(
+                    "Cannot remove runner node {} of the sub graph {}: {}",
+                    &sub_graph.runner_name, &sub_graph.name, err
+                );
+            }
+            render_graph.remove_sub_graph(sub_graph.name.clone());
+            debug!(
+                "Sub graph {} is removed from the Render Graph",
+                &sub_graph.name
+            );
+        }
+    }
+
     fn update_system(world: &mut World) {
         world.resource_scope(|world, mut cache: Mut<Self>| {
             cache.update(world);
@@ -154,8 +680,20 @@ impl SubGraphCache {
     }
 
     fn update(&mut self, world: &mut World) {
+        // The features are not validated without a device, e.g. in a headless Render App.
+        let features = world
+            .get_resource::<RenderDevice>()
+            .map(|device| device.features());
         let mut render_graph = world.resource_mut::<RenderGraph>();
-        for sub_graph in self.0.values_mut() {
+        for (entity, sub_graph) in self.0.iter_mut() {
+            if let (SubGraphDeployState::Queued(_, graph), Some(features)) =
+                (&sub_graph.graph, features)
+            {
+                if let Err(err) = validate_output_buffers(graph, features) {
+                    sub_graph.mark_unsupported(&err);
+                    continue;
+                }
+            }
             if matches!(sub_graph.graph, SubGraphDeployState::Queued(..))
                 && matches!(
                     sub_graph.providers_state_summary(),
@@ -167,14 +705,24 @@ impl SubGraphCache {
                     unreachable!()
                 };
                 let name = sub_graph.name.clone();
-                let node_name = render_graph::NodeLabel::Name(name.clone());
+                let node_name = render_graph::NodeLabel::Name(sub_graph.runner_name.clone());
                 let runner = SubGraphRunnerNode {
+                    entity: *entity,
                     sub_graph_name: name.clone(),
-                    node_inputs: graph.input_node().input_slots.iter().cloned().collect(),
+                    // Generative sub graphs may have no inputs at all
+                    node_inputs: graph
+                        .get_input_node()
+                        .map(|input_node| input_node.input_slots.iter().cloned().collect())
+                        .unwrap_or_default(),
                     trigger: sub_graph.trigger.clone(),
+                    first_run: sub_graph.first_run.clone(),
+                    missing: sub_graph.missing.clone(),
+                    node_outputs: sub_graph.outputs.clone(),
+                    switch: sub_graph.switch.clone(),
+                    undeclared_inputs: default(),
                 };
                 render_graph.add_sub_graph(name.clone(), graph);
-                render_graph.add_node(name.clone(), runner);
+                render_graph.add_node(sub_graph.runner_name.clone(), runner);
                 for edge in edges {
                     match edge {
                         Edge::InputSlotEdge {
@@ -195,39 +743,132 @@ impl SubGraphCache {
                         }
                     }
                 }
+            } else if matches!(sub_graph.graph, SubGraphDeployState::Deployed)
+                && render_graph.get_sub_graph(&sub_graph.name).is_none()
+            {
+                sub_graph.mark_missing();
+            } else if matches!(sub_graph.graph, SubGraphDeployState::Queued(..)) {
+                sub_graph.update_stuck();
             }
         }
     }
 }
 
+/// Runs the nested sub graph with the values of its input slots, that mirror the input
+/// slots of the nested graph. The view entity of the current graph is passed through.
+/// See `SubGraphBuilder::add_sub_graph`.
+#[derive(Debug, Clone)]
+pub struct RunNestedGraphNode {
+    sub_graph_name: Cow<'static, str>,
+    inputs: Vec<SlotInfo>,
+}
+
+impl RunNestedGraphNode {
+    pub fn new(sub_graph_name: impl Into<Cow<'static, str>>, sub_graph: &RenderGraph) -> Self {
+        Self {
+            sub_graph_name: sub_graph_name.into(),
+            inputs: sub_graph
+                .get_input_node()
+                .map(|input_node| input_node.input_slots.iter().cloned().collect())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl render_graph::Node for RunNestedGraphNode {
+    fn input(&self) -> Vec<SlotInfo> {
+        self.inputs.clone()
+    }
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        _render_context: &mut RenderContext,
+        _world: &World,
+    ) -> Result<(), NodeRunError> {
+        let inputs = graph.inputs().to_vec();
+        let view_entity = graph.get_view_entity();
+        graph.run_sub_graph(self.sub_graph_name.clone(), inputs, view_entity)?;
+        Ok(())
+    }
+}
+
 #[derive(Component, Debug, Clone)]
 pub struct SubGraphRunnerNode {
+    entity: Entity,
     sub_graph_name: Cow<'static, str>,
     node_inputs: Vec<SlotInfo>,
     trigger: SubGraphTrigger,
+    first_run: Arc<AtomicBool>,
+    missing: Arc<AtomicBool>,
+    node_outputs: Vec<SubGraphOutput>,
+    switch: NodeSwitch,
+    undeclared_inputs: LogOnce,
 }
 
-impl render_graph::Node for SubGraphRunnerNode {
-    fn input(&self) -> Vec<SlotInfo> {
-        self.node_inputs.clone()
+/// Orders the inputs provided by name as the input slots of the sub graph, since the sub graph
+/// run accepts the values only by position. The inputs that the sub graph doesn't declare are
+/// reported once and ignored.
+fn positional_input_values<'a>(
+    graph_name: Cow<'static, str>,
+    input_slots: impl IntoIterator<Item = &'a SlotInfo>,
+    mut inputs: HashMap<Cow<'static, str>, render_graph::SlotValue>,
+    undeclared_inputs: &LogOnce,
+) -> Result<Vec<render_graph::SlotValue>, render_graph::RunSubGraphError> {
+    let mut input_values = Vec::with_capacity(inputs.len());
+    for (index, info) in input_slots.into_iter().enumerate() {
+        let value = inputs.remove(&info.name).ok_or_else(|| {
+            render_graph::RunSubGraphError::MissingInput {
+                slot_index: index,
+                slot_name: info.name.clone(),
+                graph_name: graph_name.clone(),
+            }
+        })?;
+        input_values.push(value);
+    }
+    if !inputs.is_empty() && undeclared_inputs.first() {
+        warn!(
+            "Inputs {:?} are not declared by sub graph {} and ignored",
+            inputs.keys().collect::<Vec<_>>(),
+            graph_name
+        );
     }
+    Ok(input_values)
+}
 
-    fn run(
+/// Deployed sub graph is removed only by a bug or by another plugin, logged once.
+fn mark_missing(missing: &AtomicBool, name: &str) {
+    if !missing.swap(true, Ordering::AcqRel) {
+        error!(
+            "Deployed sub graph {} is missing from the Render Graph and cannot be run",
+            name
+        );
+    }
+}
+
+impl SubGraphRunnerNode {
+    /// Outputs captured by the last run of the sub graph, the sub graph is run after the runner
+    /// node, so the values are from the previous run, with placeholders before the first run.
+    fn set_outputs(
         &self,
         graph: &mut RenderGraphContext,
-        _render_context: &mut RenderContext,
         world: &World,
+        render_device: &RenderDevice,
     ) -> Result<(), NodeRunError> {
-        match &self.trigger {
-            SubGraphTrigger::Manual(manual) => {
-                if !manual.swap(false, Ordering::Relaxed) {
-                    debug!("Manual subgraph trigger condition is not met, skipping");
-                    return Ok(());
-                }
-            }
-            SubGraphTrigger::Always => {}
+        for output in &self.node_outputs {
+            let value = output
+                .value()
+                .unwrap_or_else(|| output.fallback_value(world, render_device));
+            graph.set_output(output.slot.name.clone(), value)?;
         }
+        Ok(())
+    }
 
+    fn run_sub_graph(
+        &self,
+        graph: &mut RenderGraphContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
         let render_graph = world.resource::<RenderGraph>();
 
         let mut sub_graph_inputs =
@@ -243,27 +884,72 @@ impl render_graph::Node for SubGraphRunnerNode {
         }
 
         if let Some(sub_graph) = render_graph.get_sub_graph(&self.sub_graph_name) {
-            let mut input_values = Vec::with_capacity(sub_graph_inputs.len());
-            // Creating an input values vector with the same order as in sub-graph because
-            // mapping by input name is not supported there
-            for (index, info) in sub_graph.input_node().input_slots.iter().enumerate() {
-                if let Some(value) = sub_graph_inputs.remove(&info.name) {
-                    input_values.push(value);
-                } else {
-                    return Err(NodeRunError::RunSubGraphError(
-                        render_graph::RunSubGraphError::MissingInput {
-                            slot_index: index,
-                            slot_name: info.name.clone(),
-                            graph_name: self.sub_graph_name.clone(),
-                        },
-                    ));
-                }
-            }
+            let input_slots = sub_graph
+                .get_input_node()
+                .into_iter()
+                .flat_map(|input_node| input_node.input_slots.iter());
+            let input_values = positional_input_values(
+                self.sub_graph_name.clone(),
+                input_slots,
+                sub_graph_inputs,
+                &self.undeclared_inputs,
+            )?;
             graph.run_sub_graph(self.sub_graph_name.clone(), input_values, None)?;
+            // Sub graph is only queued here, its nodes are run after this node
+            self.complete_run(world);
         } else {
-            warn!("Sub graph with name {} not found!", &self.sub_graph_name);
+            mark_missing(&self.missing, &self.sub_graph_name);
         }
 
         Ok(())
     }
+
+    fn complete_run(&self, world: &World) {
+        self.first_run.store(true, Ordering::Release);
+        self.trigger.acknowledge();
+        if let Some(runs) = world.get_resource::<SubGraphRuns>() {
+            runs.push(SubGraphRun {
+                entity: self.entity,
+                name: self.sub_graph_name.clone(),
+            });
+        }
+    }
+
+    /// Evaluates the switch and the trigger like the node run, but only records the run
+    /// without running the sub graph, for the Render App without the render device.
+    #[cfg(any(test, feature = "mock"))]
+    pub(crate) fn run_trigger(&self, world: &World) {
+        if self.switch.is_enabled() && self.trigger.should_run_in(world) {
+            self.complete_run(world);
+        }
+    }
+}
+
+impl render_graph::Node for SubGraphRunnerNode {
+    fn input(&self) -> Vec<SlotInfo> {
+        self.node_inputs.clone()
+    }
+
+    fn output(&self) -> Vec<SlotInfo> {
+        self.node_outputs
+            .iter()
+            .map(|output| output.slot.clone())
+            .collect()
+    }
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        if !self.switch.is_enabled() {
+            debug!("Subgraph {} is paused, skipping", &self.sub_graph_name);
+        } else if self.trigger.should_run_in(world) {
+            self.run_sub_graph(graph, world)?;
+        } else {
+            debug!("Subgraph trigger condition is not met, skipping");
+        }
+        self.set_outputs(graph, world, render_context.render_device())
+    }
 }
@@ -0,0 +1,186 @@
+//! Execution of deployed sub graphs outside of the render schedule.
+//!
+//! The functions there use the Render World directly, so they must be called with exclusive
+//! access to it and never while the Render schedule is running (with pipelined rendering the
+//! Render World lives on another thread during the frame, it can be used only between frames,
+//! for example from a tool that drives `App::update` by itself).
+//! The encoded commands are submitted to the queue of the [`RenderDevice`] and the call
+//! blocks the current thread until the device finishes the submission.
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_render::render_graph;
+use bevy_render::render_graph::{
+    NodeId, NodeRunError, NodeState, RenderGraph, RenderGraphContext, SlotValue,
+};
+use bevy_render::renderer::{RenderContext, RenderDevice, RenderQueue};
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ImmediateRunError {
+    #[error("Sub graph `{0}` is not found in the Render Graph")]
+    SubGraphNotFound(Cow<'static, str>),
+    #[error("Sub graph `{graph_name}` input slot `{slot_name}` value is missing")]
+    MissingInput {
+        graph_name: Cow<'static, str>,
+        slot_name: Cow<'static, str>,
+    },
+    #[error(
+        "Node `{node_name:?}` has {slot_count} input slots, but was provided {value_count} values"
+    )]
+    MismatchedInputCount {
+        node_name: Option<Cow<'static, str>>,
+        slot_count: usize,
+        value_count: usize,
+    },
+    #[error("Node `{node_name:?}` output slot `{slot_name}` was not set")]
+    EmptyNodeOutputSlot {
+        node_name: Option<Cow<'static, str>>,
+        slot_name: Cow<'static, str>,
+    },
+    #[error("Node run error: `{0}`")]
+    NodeRunError(#[from] NodeRunError),
+}
+
+/// Runs the deployed sub graph with the given inputs in a separate command submission
+/// and waits until the GPU completes it.
+pub fn run_sub_graph(
+    render_world: &World,
+    sub_graph_name: impl Into<Cow<'static, str>>,
+    inputs: &[SlotValue],
+) -> Result<(), ImmediateRunError> {
+    let sub_graph_name = sub_graph_name.into();
+    let render_graph = render_world.resource::<RenderGraph>();
+    let Some(sub_graph) = render_graph.get_sub_graph(&sub_graph_name) else {
+        return Err(ImmediateRunError::SubGraphNotFound(sub_graph_name));
+    };
+    let render_device = render_world.resource::<RenderDevice>();
+    let render_queue = render_world.resource::<RenderQueue>();
+
+    let mut render_context = RenderContext::new(render_device.clone());
+    run_graph(
+        sub_graph,
+        sub_graph_name.clone(),
+        &mut render_context,
+        render_world,
+        inputs,
+    )?;
+    let submission_index = render_queue.submit(render_context.finish());
+    render_device.poll(wgpu::Maintain::WaitForSubmissionIndex(submission_index));
+    debug!("Sub graph `{}` executed immediately", &sub_graph_name);
+    Ok(())
+}
+
+fn run_graph(
+    graph: &RenderGraph,
+    graph_name: Cow<'static, str>,
+    render_context: &mut RenderContext,
+    world: &World,
+    inputs: &[SlotValue],
+) -> Result<(), ImmediateRunError> {
+    let mut node_outputs: HashMap<NodeId, Vec<SlotValue>> = default();
+    let mut node_queue: VecDeque<&NodeState> = graph
+        .iter_nodes()
+        .filter(|node| node.input_slots.is_empty())
+        .collect();
+
+    if let Some(input_node) = graph.get_input_node() {
+        let mut input_values = Vec::with_capacity(input_node.input_slots.len());
+        for (index, input_slot) in input_node.input_slots.iter().enumerate() {
+            let Some(value) = inputs.get(index) else {
+                return Err(ImmediateRunError::MissingInput {
+                    graph_name,
+                    slot_name: input_slot.name.clone(),
+                });
+            };
+            input_values.push(value.clone());
+        }
+        node_outputs.insert(input_node.id, input_values);
+        for (_, node_state) in graph
+            .iter_node_outputs(input_node.id)
+            .expect("Input node must be in graph")
+        {
+            node_queue.push_front(node_state);
+        }
+    }
+
+    'handle_node: while let Some(node_state) = node_queue.pop_back() {
+        if node_outputs.contains_key(&node_state.id) {
+            continue;
+        }
+
+        let mut indexed_inputs: Vec<(usize, SlotValue)> = default();
+        for (edge, input_node) in graph
+            .iter_node_inputs(node_state.id)
+            .expect("Node must be in graph")
+        {
+            let Some(outputs) = node_outputs.get(&input_node.id) else {
+                node_queue.push_front(node_state);
+                continue 'handle_node;
+            };
+            if let render_graph::Edge::SlotEdge {
+                output_index,
+                input_index,
+                ..
+            } = edge
+            {
+                indexed_inputs.push((*input_index, outputs[*output_index].clone()));
+            }
+        }
+        indexed_inputs.sort_by_key(|(index, _)| *index);
+        let node_inputs: Vec<SlotValue> = indexed_inputs.into_iter().map(|(_, v)| v).collect();
+        if node_inputs.len() != node_state.input_slots.len() {
+            return Err(ImmediateRunError::MismatchedInputCount {
+                node_name: node_state.name.clone(),
+                slot_count: node_state.input_slots.len(),
+                value_count: node_inputs.len(),
+            });
+        }
+
+        let mut outputs: Vec<Option<SlotValue>> = vec![None; node_state.output_slots.len()];
+        {
+            let mut context =
+                RenderGraphContext::new(graph, node_state, &node_inputs, &mut outputs);
+            node_state.node.run(&mut context, render_context, world)?;
+
+            for run_sub_graph in context.finish() {
+                let Some(sub_graph) = graph.get_sub_graph(&run_sub_graph.name) else {
+                    return Err(ImmediateRunError::SubGraphNotFound(run_sub_graph.name));
+                };
+                run_graph(
+                    sub_graph,
+                    run_sub_graph.name,
+                    render_context,
+                    world,
+                    &run_sub_graph.inputs,
+                )?;
+            }
+        }
+
+        let mut values = Vec::with_capacity(outputs.len());
+        for (index, output) in outputs.into_iter().enumerate() {
+            let Some(value) = output else {
+                return Err(ImmediateRunError::EmptyNodeOutputSlot {
+                    node_name: node_state.name.clone(),
+                    slot_name: node_state
+                        .output_slots
+                        .get_slot(index)
+                        .map_or_else(|| index.to_string().into(), |slot| slot.name.clone()),
+                });
+            };
+            values.push(value);
+        }
+        node_outputs.insert(node_state.id, values);
+
+        for (_, node_state) in graph
+            .iter_node_outputs(node_state.id)
+            .expect("Node must be in graph")
+        {
+            node_queue.push_front(node_state);
+        }
+    }
+
+    Ok(())
+}
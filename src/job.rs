@@ -0,0 +1,158 @@
+//! The simplest use case without the node and sub graph machinery: run one compute shader
+//! once over one input buffer and read back one output buffer.
+//!
+//! The shader declares the input as read-only storage buffer at `@group(0) @binding(0)`
+//! and the output as read-write storage buffer at `@group(0) @binding(1)`.
+
+use crate::builder::{BuilderError, ComputeNodeBuilder, SubGraphBuilder};
+use crate::graph::SubGraphTrigger;
+use crate::node::input::{BufferVecNode, SLOT_NAME as INPUT_SLOT_NAME};
+use crate::node::output::{OutputBuffer, OutputError, SLOT_NAME as OUTPUT_SLOT_NAME};
+use crate::node::DispatchWorkgroupsStrategy;
+use bevy::core::Pod;
+use bevy::prelude::*;
+use bevy_render::render_graph::RenderGraph;
+use bevy_render::render_resource::{BufferAddress, BufferUsages};
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const INPUT_NAME: &str = "input";
+const OUTPUT_NAME: &str = "output";
+const COMPUTE_NAME: &str = "compute";
+
+static NEXT_JOB_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// One-shot compute dispatch, see the [module documentation](self).
+#[derive(Debug, Clone)]
+pub struct ComputeJob {
+    shader: Handle<Shader>,
+    entry_point: Cow<'static, str>,
+    input: Vec<u32>,
+    output_size: Option<BufferAddress>,
+    dispatch: Option<DispatchWorkgroupsStrategy>,
+}
+
+impl ComputeJob {
+    pub fn new(shader: Handle<Shader>, entry_point: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            shader,
+            entry_point: entry_point.into(),
+            input: Vec::new(),
+            output_size: None,
+            dispatch: None,
+        }
+    }
+
+    /// Input data, padded with zeroes to the multiple of 4 bytes.
+    pub fn input<T: Pod>(mut self, data: &[T]) -> Self {
+        let mut bytes = bevy::core::cast_slice::<T, u8>(data).to_vec();
+        bytes.resize(bytes.len().next_multiple_of(4), 0);
+        self.input = bytemuck::pod_collect_to_vec(&bytes);
+        self
+    }
+
+    /// Size of the output buffer in bytes, the size of the input by default.
+    pub fn output_size(mut self, size: BufferAddress) -> Self {
+        self.output_size = Some(size);
+        self
+    }
+
+    pub fn dispatch(mut self, strategy: DispatchWorkgroupsStrategy) -> Self {
+        self.dispatch = Some(strategy);
+        self
+    }
+
+    /// Spawns the compute node, the output buffer and the sub graph that runs once.
+    /// The returned handle is ready after the output buffer is read back.
+    pub fn run(self, commands: &mut Commands) -> Result<ComputeJobHandle, BuilderError> {
+        let output_size = self
+            .output_size
+            .unwrap_or((self.input.len() * std::mem::size_of::<u32>()) as BufferAddress);
+        let compute_node = ComputeNodeBuilder::default()
+            .shader(self.shader)
+            .entry_point(self.entry_point)
+            .dispatch_workgroups_strategy(
+                self.dispatch
+                    .ok_or(BuilderError::ValueNotDefined("dispatch"))?,
+            )
+            .bind_resource()
+            .name(INPUT_NAME)
+            .binding(0)
+            .read_only()
+            .input()
+            .buffer()
+            .add()
+            .bind_resource()
+            .name(OUTPUT_NAME)
+            .binding(1)
+            .output()
+            .build_buffer(
+                "compute_job_output",
+                output_size,
+                BufferUsages::STORAGE | BufferUsages::COPY_SRC,
+                false,
+            )
+            .add()
+            .build()?;
+
+        let input = BufferVecNode::<u32>::default();
+        for value in self.input {
+            input.push(value);
+        }
+        let output = OutputBuffer::default();
+        let compute_entity = commands.spawn(compute_node.clone()).id();
+        let sub_graph = SubGraphBuilder::default()
+            .name(
+                format!(
+                    "compute_job_{}",
+                    NEXT_JOB_ID.fetch_add(1, Ordering::Relaxed)
+                )
+                .into(),
+            )
+            .add_node(INPUT_NAME, input)
+            .add_node(OUTPUT_NAME, output.clone())
+            .add_node_provider(COMPUTE_NAME.into(), compute_entity, &compute_node)
+            .add_node_edge(RenderGraph::INPUT_NODE_NAME, INPUT_NAME)
+            .add_slot_edge(INPUT_NAME, INPUT_SLOT_NAME, COMPUTE_NAME, INPUT_NAME)
+            .add_slot_edge(COMPUTE_NAME, OUTPUT_NAME, OUTPUT_NAME, OUTPUT_SLOT_NAME)
+            .trigger(SubGraphTrigger::once())
+            .build()?;
+        let sub_graph_entity = commands.spawn(sub_graph).id();
+        let output_entity = commands.spawn(output.clone()).id();
+
+        Ok(ComputeJobHandle {
+            output,
+            entities: vec![compute_entity, sub_graph_entity, output_entity],
+        })
+    }
+}
+
+/// Result of the [`ComputeJob`], polled from the main world systems.
+#[derive(Component, Debug, Clone)]
+pub struct ComputeJobHandle {
+    output: OutputBuffer,
+    entities: Vec<Entity>,
+}
+
+impl ComputeJobHandle {
+    pub fn is_ready(&self) -> bool {
+        self.output.buffer_ready()
+    }
+
+    /// Takes the output once it's ready, see [`OutputBuffer::take_pod_vec`].
+    pub fn take_result<T: Pod>(&self) -> Result<Vec<T>, OutputError> {
+        self.output.take_pod_vec()
+    }
+
+    /// Entities of the compute node, the sub graph and the output buffer.
+    pub fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+
+    /// Despawns the job entities, the sub graph is removed from the Render Graph.
+    pub fn despawn(&self, commands: &mut Commands) {
+        for entity in &self.entities {
+            commands.entity(*entity).despawn();
+        }
+    }
+}
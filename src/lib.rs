@@ -1,19 +1,32 @@
-use crate::graph::{ProviderState, SubGraphCache, SubGraphDeployState, SubGraphPlugin};
-use crate::node::compute::ComputeNode;
+use crate::diagnostic::NodeProviderTimings;
+use crate::graph::{
+    ProviderState, RequiredUsages, SubGraph, SubGraphCache, SubGraphDeployState, SubGraphPlugin,
+};
+use crate::node::compute::{ComputeNode, ShaderReflectionPlugin};
+use crate::node::input::BufferAssetPlugin;
+use crate::node::memo::WrittenResourcesPlugin;
 use crate::node::output::OutputBufferPlugin;
+use crate::node::render::RenderNode;
+use crate::resource::TextureViewSourcesPlugin;
 use bevy::prelude::*;
 use bevy::utils::HashMap;
 use bevy_render::extract_component::{ExtractComponent, ExtractComponentPlugin};
 use bevy_render::render_graph::RenderGraph;
+use bevy_render::render_resource::{BufferUsages, TextureUsages};
 use bevy_render::RenderSet::PrepareAssets;
-use bevy_render::{Render, RenderApp};
+use bevy_render::{Extract, ExtractSchedule, Render, RenderApp};
 use std::any::TypeId;
 use std::borrow::Cow;
 use std::fmt::Debug;
 use std::marker::PhantomData;
+use std::time::Instant;
 
 pub mod builder;
+pub mod diagnostic;
 pub mod graph;
+pub mod job;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
 pub mod node;
 pub mod resource;
 
@@ -24,13 +37,39 @@ pub mod prelude {
     pub use crate::resource::BindResourceCreationDescriptor;
     pub use crate::resource::BindResourceCreationInfo;
     pub use crate::resource::BindResourceDirection;
+    pub use crate::resource::BindResourceError;
+    pub use crate::resource::BindResourceSizes;
+    pub use crate::resource::DynamicOffset;
+    pub use crate::resource::DynamicOffsetStrategy;
+    pub use crate::resource::ExternalTextureView;
+    pub use crate::resource::GraphContextFn;
+    pub use crate::resource::IndirectArgs;
+    pub use crate::resource::MissingInputMode;
+    pub use crate::resource::ResourceLifetime;
+    pub use crate::resource::SharedBindGroupLayout;
+    pub use crate::resource::SharedBindings;
+    pub use crate::resource::TextureViewSources;
 
     pub use crate::graph;
+    pub use crate::job::ComputeJob;
+    pub use crate::job::ComputeJobHandle;
     pub use crate::node::compute;
+    pub use crate::node::copy;
+    pub use crate::node::histogram;
     pub use crate::node::input;
     pub use crate::node::input::InputBuffer;
+    pub use crate::node::memo;
+    pub use crate::node::ordering;
     pub use crate::node::output;
+    pub use crate::node::reflect::ShaderReflection;
+    pub use crate::node::render;
+    pub use crate::node::DispatchChunking;
     pub use crate::node::DispatchWorkgroupsStrategy;
+    pub use crate::node::DispatchedDynamicOffsets;
+    pub use crate::node::DispatchedWorkgroups;
+    pub use crate::node::NodeSwitch;
+    pub use crate::node::PushConstantsStrategy;
+    pub use crate::node::{ReflectedBindingKind, ReflectedLayoutEntry, ReflectedWorkgroupSizes};
 }
 
 pub struct NodePlumberPlugin;
@@ -38,8 +77,13 @@ pub struct NodePlumberPlugin;
 impl Plugin for NodePlumberPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(OutputBufferPlugin);
+        app.add_plugins(BufferAssetPlugin);
         app.add_plugins(SubGraphPlugin);
+        app.add_plugins(WrittenResourcesPlugin);
+        app.add_plugins(TextureViewSourcesPlugin);
+        app.add_plugins(ShaderReflectionPlugin);
         app.add_plugins(NodeProviderPlugin::<ComputeNode>::default());
+        app.add_plugins(NodeProviderPlugin::<RenderNode>::default());
     }
 }
 
@@ -59,7 +103,9 @@ impl<T: NodeProvider + Sized> NodeProviderPlugin<T> {
         providers_cache: Res<NodeProviderCache<T>>,
         mut sub_graph_cache: ResMut<SubGraphCache>,
         mut render_graph: ResMut<RenderGraph>,
+        timings: Res<NodeProviderTimings<T>>,
     ) {
+        let start = Instant::now();
         for graph_component in sub_graph_cache.0.values_mut() {
             let graph_component_entities: Vec<Entity> =
                 graph_component.providers.keys().copied().collect();
@@ -108,20 +154,33 @@ impl<T: NodeProvider + Sized> NodeProviderPlugin<T> {
                 }
             }
         }
+        timings.set_sub_graphs_update(start.elapsed());
     }
 }
 
 impl<T: NodeProvider + Sized> Plugin for NodeProviderPlugin<T> {
     fn build(&self, app: &mut App) {
         app.add_plugins(ExtractComponentPlugin::<T>::default());
-        app.add_systems(PostUpdate, on_node_provider_component_changed::<T>);
+        app.add_systems(
+            PostUpdate,
+            (
+                add_required_output_usages::<T>,
+                on_node_provider_component_changed::<T>,
+            )
+                .chain(),
+        );
+        app.init_resource::<NodeProviderTimings<T>>();
+        NodeProviderTimings::<T>::register(app);
     }
 
     fn finish(&self, app: &mut App) {
+        let timings = app.world.resource::<NodeProviderTimings<T>>().clone();
         let render_app = app
             .get_sub_app_mut(RenderApp)
             .expect("Cannot find Render Plugin");
+        render_app.insert_resource(timings);
         render_app.init_resource::<NodeProviderCache<T>>();
+        render_app.add_systems(ExtractSchedule, NodeProviderCache::<T>::extract_removed);
         render_app.add_systems(
             Render,
             NodeProviderCache::<T>::update_system.in_set(PrepareAssets),
@@ -136,11 +195,61 @@ fn on_node_provider_component_changed<T: NodeProvider>(mut query: Query<&mut T,
     }
 }
 
+/// Adds the usages required by the nodes connected to the provider outputs in the spawned
+/// sub graphs, e.g. `COPY_SRC` for the buffers read back by `OutputBuffer`.
+fn add_required_output_usages<T: NodeProvider>(
+    sub_graphs: Query<&SubGraph, Added<SubGraph>>,
+    mut providers: Query<&mut T>,
+) {
+    for sub_graph in sub_graphs.iter() {
+        for required in &sub_graph.required_output_usages {
+            let Ok(mut provider) = providers.get_mut(required.entity) else {
+                continue;
+            };
+            let provider_mut = provider.bypass_change_detection();
+            let result = match required.usages {
+                RequiredUsages::Buffer(usages) => {
+                    provider_mut.add_output_buffer_usages(&required.slot, usages)
+                }
+                RequiredUsages::Texture(usages) => {
+                    provider_mut.add_output_texture_usages(&required.slot, usages)
+                }
+            };
+            match result {
+                Ok(true) => provider.set_changed(),
+                Ok(false) => {}
+                Err(err) => error!(
+                    "Cannot add usages {:?} to output `{}` of node {:?} in sub graph {:?}: {}",
+                    required.usages, required.slot, required.node_name, sub_graph.name, err
+                ),
+            }
+        }
+    }
+}
+
 pub trait NodeProvider: Component + Clone + ExtractComponent {
     fn on_component_changed(&mut self) {}
     fn update(&mut self, _world: &mut World) {}
     fn state(&self) -> ProviderState;
     fn add_node_to_graph(&self, graph: &mut RenderGraph, node_name: Cow<'static, str>);
+    /// Adds the usages to the buffer created for the output slot. Returns `true` if the
+    /// provider is modified, or the error if the output buffer cannot have the usages.
+    fn add_output_buffer_usages(
+        &mut self,
+        _slot: &str,
+        _usages: BufferUsages,
+    ) -> Result<bool, String> {
+        Ok(false)
+    }
+    /// Adds the usages to the texture created for the output slot, see
+    /// [`Self::add_output_buffer_usages`].
+    fn add_output_texture_usages(
+        &mut self,
+        _slot: &str,
+        _usages: TextureUsages,
+    ) -> Result<bool, String> {
+        Ok(false)
+    }
 }
 
 #[derive(Resource)]
@@ -154,9 +263,23 @@ impl<T: NodeProvider> Default for NodeProviderCache<T> {
 
 impl<T: NodeProvider> NodeProviderCache<T> {
     fn update_system(world: &mut World) {
+        let start = Instant::now();
         world.resource_scope(|world, mut cache: Mut<Self>| {
             cache.update(world);
         });
+        world
+            .resource::<NodeProviderTimings<T>>()
+            .set_cache_update(start.elapsed());
+    }
+
+    /// Providers are extracted only when changed, so the cache keeps them between the frames
+    /// until the component is removed or its entity is despawned in the Main World.
+    fn extract_removed(mut cache: ResMut<Self>, mut removed: Extract<RemovedComponents<T>>) {
+        for entity in removed.read() {
+            if cache.0.remove(&entity).is_some() {
+                debug!("Node provider of removed entity {:?} is evicted", entity);
+            }
+        }
     }
 
     fn update(&mut self, world: &mut World) {
@@ -0,0 +1,221 @@
+//! Node provider and Render App that work without a render device, so the sub graph
+//! deployment and trigger logic can be tested in headless CI. Shaders are never executed.
+
+use crate::graph::{ProviderState, SubGraphRunnerNode};
+use crate::node::add_or_replace_graph_node;
+use crate::{MainWorldEntity, NodeProvider};
+use bevy::app::SubApp;
+use bevy::ecs::query::QueryItem;
+use bevy::ecs::schedule::ScheduleLabel;
+use bevy::prelude::*;
+use bevy_render::extract_component::ExtractComponent;
+use bevy_render::render_graph::{
+    Node, NodeRunError, RenderGraph, RenderGraphContext, SlotInfo, SlotType,
+};
+use bevy_render::render_resource::{BufferUsages, TextureUsages};
+use bevy_render::renderer::RenderContext;
+use bevy_render::{ExtractSchedule, MainWorld, Render, RenderApp, RenderSet};
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+/// Inserts the Render App with the render schedules and the Render Graph, but without
+/// the render device. Must be added before the plugins that configure the Render App.
+pub struct HeadlessRenderPlugin;
+
+impl Plugin for HeadlessRenderPlugin {
+    fn build(&self, app: &mut App) {
+        let mut render_app = App::empty();
+        render_app.main_schedule_label = Render.intern();
+        render_app.add_schedule(Schedule::new(ExtractSchedule));
+        render_app.add_schedule(Render::base_schedule());
+        render_app.add_systems(Render, run_sub_graph_triggers.in_set(RenderSet::Render));
+        // Extracted entities live for one frame like in the `RenderPlugin`
+        render_app.add_systems(Render, World::clear_entities.in_set(RenderSet::Cleanup));
+        render_app.init_resource::<RenderGraph>();
+        app.insert_sub_app(
+            RenderApp,
+            SubApp::new(render_app, |main_world, render_app| {
+                render_app.world.insert_resource(MainWorld::default());
+                let mut extracted = render_app.world.resource_mut::<MainWorld>();
+                std::mem::swap(main_world, &mut extracted);
+                render_app.world.run_schedule(ExtractSchedule);
+                let mut extracted = render_app
+                    .world
+                    .remove_resource::<MainWorld>()
+                    .expect("Main world must be returned after extraction");
+                std::mem::swap(main_world, &mut extracted);
+            }),
+        );
+    }
+}
+
+/// Replaces the Render Graph run: the triggers of the deployed sub graphs are evaluated and
+/// acknowledged, and the run events are sent, but the nodes are never run.
+fn run_sub_graph_triggers(world: &World) {
+    for node in world.resource::<RenderGraph>().iter_nodes() {
+        if let Ok(runner) = node.node::<SubGraphRunnerNode>() {
+            runner.run_trigger(world);
+        }
+    }
+}
+
+/// Node provider that becomes ready after the given number of updates or fails with the error.
+/// The added node only defines the slots and does nothing on run.
+#[derive(Component, Clone, Debug, Default)]
+pub struct MockNodeProvider {
+    updates_until_ready: u32,
+    error: Option<String>,
+    input: Vec<SlotInfo>,
+    output: Vec<SlotInfo>,
+    output_usages: Vec<(Cow<'static, str>, BufferUsages)>,
+    output_texture_usages: Vec<(Cow<'static, str>, TextureUsages)>,
+    updates: Arc<AtomicU32>,
+}
+
+impl MockNodeProvider {
+    pub fn new(updates_until_ready: u32) -> Self {
+        Self {
+            updates_until_ready,
+            ..default()
+        }
+    }
+
+    pub fn failing(error: impl Into<String>) -> Self {
+        Self {
+            error: Some(error.into()),
+            ..default()
+        }
+    }
+
+    pub fn with_input(mut self, name: impl Into<Cow<'static, str>>, slot_type: SlotType) -> Self {
+        self.input.push(SlotInfo::new(name, slot_type));
+        self
+    }
+
+    pub fn with_output(mut self, name: impl Into<Cow<'static, str>>, slot_type: SlotType) -> Self {
+        self.output.push(SlotInfo::new(name, slot_type));
+        self
+    }
+
+    /// Usages added to the buffer output slot by [`NodeProvider::add_output_buffer_usages`].
+    pub fn output_usages(&self, slot: &str) -> BufferUsages {
+        self.output_usages
+            .iter()
+            .filter(|(name, _)| name == slot)
+            .fold(BufferUsages::empty(), |all, (_, usages)| all | *usages)
+    }
+
+    /// Usages added to the texture output slot by [`NodeProvider::add_output_texture_usages`].
+    pub fn output_texture_usages(&self, slot: &str) -> TextureUsages {
+        self.output_texture_usages
+            .iter()
+            .filter(|(name, _)| name == slot)
+            .fold(TextureUsages::empty(), |all, (_, usages)| all | *usages)
+    }
+
+    /// Number of the provider updates in the Render World, shared with the main world component.
+    pub fn updates(&self) -> u32 {
+        self.updates.load(Ordering::Relaxed)
+    }
+}
+
+impl NodeProvider for MockNodeProvider {
+    fn update(&mut self, _world: &mut World) {
+        self.updates.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn state(&self) -> ProviderState {
+        if let Some(error) = &self.error {
+            ProviderState::Err(error.clone())
+        } else if self.updates() >= self.updates_until_ready {
+            ProviderState::CanCreateNode
+        } else {
+            ProviderState::Updating
+        }
+    }
+
+    fn add_node_to_graph(&self, graph: &mut RenderGraph, node_name: Cow<'static, str>) {
+        add_or_replace_graph_node(
+            graph,
+            node_name,
+            MockNode {
+                input: self.input.clone(),
+                output: self.output.clone(),
+            },
+        );
+    }
+
+    fn add_output_buffer_usages(
+        &mut self,
+        slot: &str,
+        usages: BufferUsages,
+    ) -> Result<bool, String> {
+        if !self
+            .output
+            .iter()
+            .any(|info| info.name == slot && info.slot_type == SlotType::Buffer)
+        {
+            return Err(format!("Buffer output `{}` is not found", slot));
+        }
+        if self.output_usages(slot).contains(usages) {
+            return Ok(false);
+        }
+        self.output_usages.push((slot.to_string().into(), usages));
+        Ok(true)
+    }
+
+    fn add_output_texture_usages(
+        &mut self,
+        slot: &str,
+        usages: TextureUsages,
+    ) -> Result<bool, String> {
+        if !self
+            .output
+            .iter()
+            .any(|info| info.name == slot && info.slot_type == SlotType::TextureView)
+        {
+            return Err(format!("Texture output `{}` is not found", slot));
+        }
+        if self.output_texture_usages(slot).contains(usages) {
+            return Ok(false);
+        }
+        self.output_texture_usages
+            .push((slot.to_string().into(), usages));
+        Ok(true)
+    }
+}
+
+impl ExtractComponent for MockNodeProvider {
+    type Query = (&'static Self, Entity);
+    type Filter = Changed<Self>;
+    type Out = (Self, MainWorldEntity);
+
+    fn extract_component(item: QueryItem<'_, Self::Query>) -> Option<Self::Out> {
+        Some((item.0.clone(), MainWorldEntity(item.1)))
+    }
+}
+
+struct MockNode {
+    input: Vec<SlotInfo>,
+    output: Vec<SlotInfo>,
+}
+
+impl Node for MockNode {
+    fn input(&self) -> Vec<SlotInfo> {
+        self.input.clone()
+    }
+
+    fn output(&self) -> Vec<SlotInfo> {
+        self.output.clone()
+    }
+
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        _render_context: &mut RenderContext,
+        _world: &World,
+    ) -> Result<(), NodeRunError> {
+        Ok(())
+    }
+}
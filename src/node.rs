@@ -1,13 +1,33 @@
-use crate::resource::{BindResourceCreationInfo, BindResourceDirection};
+use crate::node::reflect::ShaderReflection;
+use crate::resource::{BindResourceCreationInfo, BindResourceDirection, GraphContextFn};
 use bevy::prelude::*;
+use bevy::utils::HashMap;
 use bevy_render::render_graph;
-use bevy_render::render_graph::{NodeRunError, RenderGraph, RenderGraphContext, SlotInfo};
-use bevy_render::renderer::RenderContext;
+use bevy_render::render_graph::{
+    NodeRunError, RenderGraph, RenderGraphContext, SlotInfo, SlotType, SlotValue,
+};
+use bevy_render::render_resource::{
+    BindGroupLayout, BindGroupLayoutEntry, BindingType, Buffer, BufferAddress, BufferBindingType,
+    BufferDescriptor, BufferUsages, PushConstantRange, ShaderStages,
+};
+use bevy_render::renderer::{RenderContext, RenderDevice};
+use bevy_render::texture::FallbackImage;
 use std::borrow::Cow;
+use std::num::NonZeroU32;
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use wgpu::COPY_BUFFER_ALIGNMENT;
 
 pub mod compute;
+pub mod copy;
+pub mod histogram;
 pub mod input;
+pub mod memo;
+pub mod ordering;
 pub mod output;
+pub mod reflect;
+pub mod render;
 
 #[derive(Default, Debug)]
 struct DummyNode {
@@ -74,10 +94,186 @@ pub(crate) fn add_or_replace_graph_node<T: render_graph::Node>(
     }
 }
 
+/// Layouts of the bind groups used by the resources, taken from the explicit pipeline layout
+/// if it is defined, otherwise from the layout derived by the pipeline. `None` until the bind
+/// group count of the derived layout is reflected.
+pub(crate) fn pipeline_bind_group_layouts(
+    binding_resource_info: &[BindResourceCreationInfo],
+    explicit_layouts: &[BindGroupLayout],
+    derived_layout: impl Fn(u32) -> BindGroupLayout,
+    derived_group_count: &ReflectedBindGroupCount,
+) -> Option<Result<Vec<(u32, BindGroupLayout)>, String>> {
+    let groups = BindResourceCreationInfo::groups(binding_resource_info);
+    if !explicit_layouts.is_empty() {
+        return Some(
+            groups
+                .into_iter()
+                .map(|index| {
+                    let layout = explicit_layouts.get(index as usize).cloned().ok_or_else(|| {
+                        format!(
+                            "Bind group index {} is out of range, pipeline layout has {} bind groups",
+                            index,
+                            explicit_layouts.len()
+                        )
+                    })?;
+                    Ok((index, layout))
+                })
+                .collect(),
+        );
+    }
+    if groups.is_empty() {
+        return Some(Ok(Vec::new()));
+    }
+    // wgpu panics when the derived pipeline layout has no bind group with the index
+    let count = match derived_group_count.get()? {
+        Ok(count) => count,
+        Err(err) => {
+            return Some(Err(format!(
+                "Cannot reflect the bind groups of the derived pipeline layout: {}",
+                err
+            )))
+        }
+    };
+    Some(
+        groups
+            .into_iter()
+            .map(|index| {
+                if index < count {
+                    Ok((index, derived_layout(index)))
+                } else {
+                    Err(format!(
+                        "Bind group index {} is not used by the shader, derived pipeline layout has {} bind groups",
+                        index, count
+                    ))
+                }
+            })
+            .collect(),
+    )
+}
+
+/// Number of the bind groups in the pipeline layout derived from the shader, reflected in the
+/// Main World by [`compute::ShaderReflectionPlugin`] and shared with the Render World.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ReflectedBindGroupCount(Arc<Mutex<Option<Result<u32, String>>>>);
+
+impl ReflectedBindGroupCount {
+    pub(crate) fn get(&self) -> Option<Result<u32, String>> {
+        self.lock().clone()
+    }
+
+    pub(crate) fn set(&self, count: Result<u32, String>) {
+        *self.lock() = Some(count);
+    }
+
+    pub(crate) fn clear(&self) {
+        *self.lock() = None;
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<Result<u32, String>>> {
+        self.0
+            .lock()
+            .expect("Reflected bind group count mutex is poisoned")
+    }
+}
+
+/// Placeholder for the output slot that has no value yet, the Render Graph requires all outputs
+/// of the node to be set. Textures and samplers are taken from the `FallbackImage`, the buffer
+/// is a small zero-initialized buffer created on each call, so the callers should keep it.
+pub(crate) fn fallback_slot_value(
+    slot_type: SlotType,
+    world: &World,
+    render_device: &RenderDevice,
+) -> SlotValue {
+    match slot_type {
+        SlotType::Buffer => SlotValue::Buffer(fallback_buffer(render_device)),
+        SlotType::TextureView => {
+            SlotValue::TextureView(world.resource::<FallbackImage>().d2.texture_view.clone())
+        }
+        SlotType::Sampler => {
+            SlotValue::Sampler(world.resource::<FallbackImage>().d2.sampler.clone())
+        }
+        SlotType::Entity => SlotValue::Entity(Entity::PLACEHOLDER),
+    }
+}
+
+/// Small zero-initialized buffer used as the placeholder of the buffer output.
+pub(crate) fn fallback_buffer(render_device: &RenderDevice) -> Buffer {
+    render_device.create_buffer(&BufferDescriptor {
+        label: Some("fallback"),
+        size: 16,
+        usage: BufferUsages::STORAGE
+            | BufferUsages::UNIFORM
+            | BufferUsages::COPY_SRC
+            | BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+/// Panics if the bounds of the copied buffer range are not multiples of `COPY_BUFFER_ALIGNMENT`,
+/// the copy commands would fail validation on every run otherwise.
+pub(crate) fn assert_copy_range_aligned(range: &Range<BufferAddress>) {
+    assert!(
+        range.start.is_multiple_of(COPY_BUFFER_ALIGNMENT)
+            && range.end.is_multiple_of(COPY_BUFFER_ALIGNMENT),
+        "Copied buffer range {:?} must be aligned to {} bytes",
+        range,
+        COPY_BUFFER_ALIGNMENT
+    );
+}
+
+/// Shared flag to log a repeated issue of the node run only once.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LogOnce(Arc<AtomicBool>);
+
+impl LogOnce {
+    /// Returns true only for the first call.
+    pub(crate) fn first(&self) -> bool {
+        !self.0.swap(true, Ordering::Relaxed)
+    }
+}
+
+/// Shared switch that allows to skip the node runs without changing the component,
+/// so the node is not recreated. Skipped node publishes the outputs of its previous run.
+#[derive(Debug, Clone)]
+pub struct NodeSwitch(Arc<AtomicBool>);
+
+impl Default for NodeSwitch {
+    fn default() -> Self {
+        Self(Arc::new(AtomicBool::new(true)))
+    }
+}
+
+impl NodeSwitch {
+    pub fn set_enabled(&self, enabled: bool) {
+        self.0.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum DispatchWorkgroupsStrategy {
     Static(u32, u32, u32),
-    FromGraphContext(fn(&render_graph::RenderGraphContext) -> (u32, u32, u32)),
+    FromGraphContext(GraphContextFn<(u32, u32, u32)>),
+    /// One invocation per element of the input buffer in the given slot.
+    PerBufferElement {
+        slot: Cow<'static, str>,
+        element_size: u64,
+        workgroup_size: u32,
+    },
+    /// Workgroups are read on GPU from the input buffer in the given slot.
+    Indirect {
+        buffer_slot: Cow<'static, str>,
+        offset: BufferAddress,
+    },
+    /// Fractions of the device `max_compute_workgroups_per_dimension` limit per dimension,
+    /// e.g. for benchmarks or adaptive quality.
+    FractionOfMaxWorkgroups(f32, f32, f32),
+    /// One invocation per element of the extent, e.g. per texel of a texture. The extent is
+    /// divided by the `@workgroup_size` of the WGSL entry point, see [`ReflectedWorkgroupSizes`].
+    CoverExtent(u32, u32, u32),
 }
 
 impl Default for DispatchWorkgroupsStrategy {
@@ -87,13 +283,395 @@ impl Default for DispatchWorkgroupsStrategy {
 }
 
 impl DispatchWorkgroupsStrategy {
+    pub fn from_graph_context(
+        f: impl Fn(&render_graph::RenderGraphContext) -> (u32, u32, u32) + Send + Sync + 'static,
+    ) -> Self {
+        Self::FromGraphContext(GraphContextFn::new(f))
+    }
+
+    /// Workgroups computed on CPU, `None` for the indirect dispatch and for
+    /// [`Self::CoverExtent`] until the workgroup size is reflected.
+    /// The `workgroup_size` is the reflected size of the dispatched entry point.
     pub(crate) fn workgroups_to_dispatch(
         &self,
         graph: &render_graph::RenderGraphContext,
-    ) -> (u32, u32, u32) {
-        match self {
+        render_device: &RenderDevice,
+        workgroup_size: Option<Workgroups>,
+    ) -> Option<(u32, u32, u32)> {
+        Some(match self {
             DispatchWorkgroupsStrategy::Static(x, y, z) => (*x, *y, *z),
-            DispatchWorkgroupsStrategy::FromGraphContext(from_graph) => from_graph(graph),
+            DispatchWorkgroupsStrategy::FromGraphContext(from_graph) => from_graph.call(graph),
+            DispatchWorkgroupsStrategy::PerBufferElement {
+                slot,
+                element_size,
+                workgroup_size,
+            } => {
+                let x = graph.get_input_buffer(slot.clone()).map_or(1, |buffer| {
+                    let elements = buffer.size() / element_size.max(&1);
+                    elements.div_ceil(u64::from(*workgroup_size.max(&1)))
+                });
+                (x as u32, 1, 1)
+            }
+            DispatchWorkgroupsStrategy::Indirect { .. } => return None,
+            DispatchWorkgroupsStrategy::FractionOfMaxWorkgroups(x, y, z) => {
+                Self::fraction_of_max_workgroups(
+                    (*x, *y, *z),
+                    render_device.limits().max_compute_workgroups_per_dimension,
+                )
+            }
+            DispatchWorkgroupsStrategy::CoverExtent(x, y, z) => {
+                Self::cover_extent((*x, *y, *z), workgroup_size?)
+            }
+        })
+    }
+
+    /// Ceil division of the extent by the workgroup size per dimension.
+    pub fn cover_extent(extent: Workgroups, workgroup_size: Workgroups) -> Workgroups {
+        (
+            extent.0.div_ceil(workgroup_size.0.max(1)),
+            extent.1.div_ceil(workgroup_size.1.max(1)),
+            extent.2.div_ceil(workgroup_size.2.max(1)),
+        )
+    }
+
+    /// Fractions are clamped to `[0, 1]`, at least one workgroup is dispatched per dimension.
+    pub fn fraction_of_max_workgroups(
+        fractions: (f32, f32, f32),
+        max_workgroups_per_dimension: u32,
+    ) -> Workgroups {
+        let count = |fraction: f32| {
+            let fraction = if fraction.is_nan() {
+                0.0
+            } else {
+                fraction.clamp(0.0, 1.0)
+            };
+            ((max_workgroups_per_dimension as f64 * fraction as f64) as u32).max(1)
+        };
+        (count(fractions.0), count(fractions.1), count(fractions.2))
+    }
+
+    /// Describes the dimensions where the invocations of the dispatched workgroups don't cover
+    /// the element count, or where at least one whole workgroup is dispatched over it.
+    /// `None` if the dispatch is exactly the ceil division of the count by the workgroup size.
+    pub fn coverage_issue(
+        workgroups: Workgroups,
+        workgroup_size: Workgroups,
+        element_count: Workgroups,
+    ) -> Option<String> {
+        let dimensions = [
+            ("x", workgroups.0, workgroup_size.0, element_count.0),
+            ("y", workgroups.1, workgroup_size.1, element_count.1),
+            ("z", workgroups.2, workgroup_size.2, element_count.2),
+        ];
+        let issues: Vec<String> = dimensions
+            .into_iter()
+            .filter_map(|(axis, workgroups, size, count)| {
+                let invocations = u64::from(workgroups) * u64::from(size);
+                let required = u64::from(count).div_ceil(u64::from(size.max(1)));
+                if invocations < u64::from(count) {
+                    Some(format!(
+                        "{}: {} workgroups of size {} cover {} of {} elements, {} workgroups required",
+                        axis, workgroups, size, invocations, count, required
+                    ))
+                } else if u64::from(workgroups) > required {
+                    Some(format!(
+                        "{}: {} workgroups of size {} dispatch {} invocations for {} elements, {} workgroups are enough",
+                        axis, workgroups, size, invocations, count, required
+                    ))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        (!issues.is_empty()).then(|| issues.join("; "))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum PushConstantsStrategy {
+    Static(Vec<u8>),
+    FromGraphContext(GraphContextFn<Vec<u8>>),
+}
+
+impl PushConstantsStrategy {
+    pub fn from_graph_context(
+        f: impl Fn(&render_graph::RenderGraphContext) -> Vec<u8> + Send + Sync + 'static,
+    ) -> Self {
+        Self::FromGraphContext(GraphContextFn::new(f))
+    }
+
+    pub(crate) fn push_constants(&self, graph: &render_graph::RenderGraphContext) -> Vec<u8> {
+        match self {
+            PushConstantsStrategy::Static(bytes) => bytes.clone(),
+            PushConstantsStrategy::FromGraphContext(from_graph) => from_graph.call(graph),
+        }
+    }
+
+    /// Checks that the bytes set at offset 0 fit within a declared compute push constant range.
+    pub(crate) fn validate(bytes: &[u8], ranges: &[PushConstantRange]) -> Result<(), String> {
+        if !bytes.len().is_multiple_of(4) {
+            return Err(format!(
+                "Push constants size {} is not a multiple of 4",
+                bytes.len()
+            ));
+        }
+        let fits = ranges.iter().any(|range| {
+            range.stages.contains(ShaderStages::COMPUTE)
+                && range.range.start == 0
+                && range.range.end as usize >= bytes.len()
+        });
+        if fits {
+            Ok(())
+        } else {
+            Err(format!(
+                "Push constants of {} bytes don't fit within the declared compute ranges {:?}",
+                bytes.len(),
+                ranges
+            ))
+        }
+    }
+}
+
+pub type Workgroups = (u32, u32, u32);
+
+/// Workgroup counts dispatched by the last run of the node, one entry per pass starting with
+/// the main entry point, `None` for the indirect dispatch. Shared between the main and the render
+/// world, so the counts computed at run time can be read from the main world component.
+#[derive(Debug, Clone, Default)]
+pub struct DispatchedWorkgroups(Arc<Mutex<Vec<Option<Workgroups>>>>);
+
+impl DispatchedWorkgroups {
+    /// Empty until the first dispatch of the node.
+    pub fn snapshot(&self) -> Vec<Option<Workgroups>> {
+        self.0
+            .lock()
+            .expect("Dispatched workgroups mutex is poisoned")
+            .clone()
+    }
+
+    /// Workgroups of the main entry point.
+    pub fn main(&self) -> Option<Workgroups> {
+        self.snapshot().first().copied().flatten()
+    }
+
+    pub(crate) fn update(&self, workgroups: Vec<Option<Workgroups>>) {
+        *self
+            .0
+            .lock()
+            .expect("Dispatched workgroups mutex is poisoned") = workgroups;
+    }
+}
+
+/// Bind group index with the dynamic offsets of the group.
+pub type GroupDynamicOffsets = (u32, Vec<u32>);
+
+/// Dynamic offsets of the bind groups passed to `set_bind_group` by the last dispatch of the node,
+/// ordered by the group index. Shared with the Render World.
+#[derive(Debug, Clone, Default)]
+pub struct DispatchedDynamicOffsets(Arc<Mutex<Vec<GroupDynamicOffsets>>>);
+
+impl DispatchedDynamicOffsets {
+    /// Empty until the first dispatch of the node.
+    pub fn snapshot(&self) -> Vec<GroupDynamicOffsets> {
+        self.0
+            .lock()
+            .expect("Dispatched dynamic offsets mutex is poisoned")
+            .clone()
+    }
+
+    /// Offsets of the bind group, `None` if the group was not bound by the last dispatch.
+    pub fn group(&self, group: u32) -> Option<Vec<u32>> {
+        self.snapshot()
+            .into_iter()
+            .find(|(index, _)| *index == group)
+            .map(|(_, offsets)| offsets)
+    }
+
+    pub(crate) fn update(&self, offsets: Vec<GroupDynamicOffsets>) {
+        *self
+            .0
+            .lock()
+            .expect("Dispatched dynamic offsets mutex is poisoned") = offsets;
+    }
+}
+
+/// Workgroup sizes of the compute node entry points, reflected from the shader module composed
+/// with the imports and the shader defs of the pipeline in the Main World, see [`ShaderReflection`].
+/// Shared with the Render World.
+#[derive(Debug, Clone, Default)]
+pub struct ReflectedWorkgroupSizes(Arc<Mutex<HashMap<Cow<'static, str>, Workgroups>>>);
+
+impl ReflectedWorkgroupSizes {
+    pub fn get(&self, entry_point: &str) -> Option<Workgroups> {
+        self.lock().get(entry_point).copied()
+    }
+
+    pub(crate) fn insert(&self, entry_point: Cow<'static, str>, workgroup_size: Workgroups) {
+        self.lock().insert(entry_point, workgroup_size);
+    }
+
+    pub(crate) fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<Cow<'static, str>, Workgroups>> {
+        self.0
+            .lock()
+            .expect("Reflected workgroup sizes mutex is poisoned")
+    }
+}
+
+/// Reflects the `@workgroup_size` of the compute entry point of the standalone WGSL module,
+/// see [`ShaderReflection`] for the shaders with imports and shader defs.
+pub fn reflect_workgroup_size(wgsl: &str, entry_point: &str) -> Option<Workgroups> {
+    ShaderReflection::from_wgsl(wgsl)
+        .ok()?
+        .workgroup_size(entry_point)
+}
+
+/// Kind of the binding of the bind group layout entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReflectedBindingKind {
+    StorageBuffer { read_only: bool },
+    UniformBuffer,
+    Texture,
+    StorageTexture,
+    Sampler,
+}
+
+impl ReflectedBindingKind {
+    fn from_binding_type(ty: &BindingType) -> Self {
+        match ty {
+            BindingType::Buffer {
+                ty: BufferBindingType::Storage { read_only },
+                ..
+            } => Self::StorageBuffer {
+                read_only: *read_only,
+            },
+            BindingType::Buffer { .. } => Self::UniformBuffer,
+            BindingType::Texture { .. } => Self::Texture,
+            BindingType::StorageTexture { .. } => Self::StorageTexture,
+            BindingType::Sampler(_) => Self::Sampler,
+        }
+    }
+
+    /// Slot type of the graph value bound to the binding.
+    pub fn slot_type(&self) -> render_graph::SlotType {
+        match self {
+            Self::StorageBuffer { .. } | Self::UniformBuffer => render_graph::SlotType::Buffer,
+            Self::Texture | Self::StorageTexture => render_graph::SlotType::TextureView,
+            Self::Sampler => render_graph::SlotType::Sampler,
+        }
+    }
+}
+
+/// Bind group layout entry of the pipeline, for comparison with the declared
+/// [`BindResourceCreationInfo`]. The name is known only for the entries reflected from the shader.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReflectedLayoutEntry {
+    pub group: u32,
+    pub binding: u32,
+    pub name: Option<String>,
+    pub kind: ReflectedBindingKind,
+    pub visibility: ShaderStages,
+    /// Number of the elements of the binding array, `None` for the single bindings and the
+    /// binding arrays without a fixed size.
+    pub count: Option<u32>,
+}
+
+impl ReflectedLayoutEntry {
+    pub fn from_layout_entries(entries: &[Vec<BindGroupLayoutEntry>]) -> Vec<Self> {
+        entries
+            .iter()
+            .zip(0u32..)
+            .flat_map(|(entries, group)| {
+                entries.iter().map(move |entry| Self {
+                    group,
+                    binding: entry.binding,
+                    name: None,
+                    kind: ReflectedBindingKind::from_binding_type(&entry.ty),
+                    visibility: entry.visibility,
+                    count: entry.count.map(NonZeroU32::get),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Reflects the bindings of the standalone WGSL module, see [`ShaderReflection::layout_entries`].
+pub fn reflect_layout_entries(
+    wgsl: &str,
+) -> Result<Vec<ReflectedLayoutEntry>, reflect::ShaderReflectionError> {
+    Ok(ShaderReflection::from_wgsl(wgsl)?.layout_entries())
+}
+
+/// Splits one large dispatch into several smaller ones to avoid GPU watchdog timeouts.
+///
+/// Before each chunk is dispatched, the workgroup offset of the chunk is written as
+/// `vec3<u32>` push constant at `push_constant_offset`. The shader should add it to
+/// `workgroup_id` (or `offset * workgroup_size` to `global_invocation_id`) to get the
+/// position in the whole dispatch. The offset must be placed after the node push constants,
+/// overlapping ranges are rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DispatchChunking {
+    pub max_workgroups_per_dispatch: Workgroups,
+    pub push_constant_offset: u32,
+}
+
+impl DispatchChunking {
+    pub const PUSH_CONSTANT_SIZE: u32 = 3 * std::mem::size_of::<u32>() as u32;
+
+    pub fn new(max_workgroups_per_dispatch: Workgroups) -> Self {
+        Self {
+            max_workgroups_per_dispatch,
+            push_constant_offset: 0,
+        }
+    }
+
+    pub fn with_push_constant_offset(mut self, push_constant_offset: u32) -> Self {
+        self.push_constant_offset = push_constant_offset;
+        self
+    }
+
+    pub fn push_constant_range(&self) -> std::ops::Range<u32> {
+        self.push_constant_offset..self.push_constant_offset + Self::PUSH_CONSTANT_SIZE
+    }
+
+    /// Checks that the chunk offset doesn't overwrite the node push constants set at offset 0.
+    pub(crate) fn validate_push_constants(&self, push_constants: &[u8]) -> Result<(), String> {
+        if push_constants.len() > self.push_constant_offset as usize {
+            Err(format!(
+                "Dispatch chunking offset at {} overlaps {} bytes of push constants",
+                self.push_constant_offset,
+                push_constants.len()
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns `(offset, count)` workgroup pairs covering the whole `workgroups` range.
+    pub fn chunks(&self, workgroups: Workgroups) -> Vec<(Workgroups, Workgroups)> {
+        let (max_x, max_y, max_z) = self.max_workgroups_per_dispatch;
+        let axis = |total: u32, max: u32| -> Vec<(u32, u32)> {
+            let max = max.max(1);
+            (0..total)
+                .step_by(max as usize)
+                .map(|offset| (offset, max.min(total - offset)))
+                .collect()
+        };
+        let xs = axis(workgroups.0, max_x);
+        let ys = axis(workgroups.1, max_y);
+        let zs = axis(workgroups.2, max_z);
+
+        let mut chunks = Vec::with_capacity(xs.len() * ys.len() * zs.len());
+        for (z, count_z) in &zs {
+            for (y, count_y) in &ys {
+                for (x, count_x) in &xs {
+                    chunks.push(((*x, *y, *z), (*count_x, *count_y, *count_z)));
+                }
+            }
         }
+        chunks
     }
 }
@@ -1,54 +1,438 @@
+use crate::diagnostic::{NodeMemoryUsage, TimestampQuerySet};
 use crate::graph::ProviderState;
-use crate::node::{add_or_replace_graph_node, DispatchWorkgroupsStrategy, DummyNode};
-use crate::resource::{BindResourceCreationInfo, NodeResources};
+use crate::node::memo::{MemoKey, NodeMemo};
+use crate::node::reflect::{ShaderReflection, ShaderReflectionError};
+use crate::node::render::RenderNode;
+use crate::node::{
+    add_or_replace_graph_node, pipeline_bind_group_layouts, DispatchChunking,
+    DispatchWorkgroupsStrategy, DispatchedDynamicOffsets, DispatchedWorkgroups, DummyNode,
+    GroupDynamicOffsets, LogOnce, NodeSwitch, PushConstantsStrategy, ReflectedBindGroupCount,
+    ReflectedBindingKind, ReflectedLayoutEntry, ReflectedWorkgroupSizes, Workgroups,
+};
+use crate::resource::{
+    BindResourceCreationDescriptor, BindResourceCreationInfo, BindResourceDirection,
+    BindResourceError, BindResourceSizes, MissingInputMode, NodeResources, SharedBindGroupLayout,
+};
 use crate::{MainWorldEntity, NodeProvider};
 use bevy::ecs::query::QueryItem;
 use bevy::log::debug;
 use bevy::prelude::*;
 use bevy_render::extract_component::ExtractComponent;
 use bevy_render::render_resource::PipelineCache;
-use bevy_render::renderer::RenderContext;
+use bevy_render::renderer::{RenderContext, RenderDevice};
 use bevy_render::{render_graph, render_resource};
 use std::any::type_name;
 use std::borrow::Cow;
 
+/// Opt-in plugin that marks `ComputeNode`s and `RenderNode`s as changed when their shader asset
+/// or one of the `shader_imports` is modified, so the pipeline is recompiled without manual
+/// `set_changed` calls. The changed node is extracted again in the `Creating` state, so a node
+/// that failed to compile recovers after the shader is fixed.
+pub struct AutoReloadShadersPlugin;
+
+impl Plugin for AutoReloadShadersPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreUpdate, mark_compute_nodes_on_shader_modified);
+    }
+}
+
+/// Reflects the shader modules of `ComputeNode`s and `RenderNode`s: the workgroup sizes of the
+/// entry points, required by [`DispatchWorkgroupsStrategy::CoverExtent`], and the bind group count
+/// of the pipeline layout derived from the shader. The shaders are reflected again when the node
+/// is changed.
+pub struct ShaderReflectionPlugin;
+
+impl Plugin for ShaderReflectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            (reflect_compute_node_shaders, reflect_render_node_shaders),
+        );
+    }
+}
+
+fn reflect_compute_node_shaders(
+    query: Query<Ref<ComputeNode>>,
+    shaders: Option<Res<Assets<Shader>>>,
+) {
+    let Some(shaders) = shaders else {
+        return;
+    };
+    for compute_node in query.iter() {
+        let sizes = &compute_node.workgroup_sizes;
+        if compute_node.is_changed() {
+            sizes.clear();
+            compute_node.bind_group_count.clear();
+        } else if compute_node.bind_group_count.get().is_some() {
+            continue;
+        }
+        let descriptor = compute_node.specialized_pipeline_descriptor();
+        let reflection = match ShaderReflection::from_shader(
+            &shaders,
+            &descriptor.shader,
+            &descriptor.shader_defs,
+        ) {
+            Ok(reflection) => reflection,
+            Err(ShaderReflectionError::NotLoaded(_)) => continue,
+            Err(err) => {
+                warn!(
+                    "Cannot reflect shader of compute node {:?}: {}",
+                    compute_node.debug_name(),
+                    err
+                );
+                compute_node.bind_group_count.set(Err(err.to_string()));
+                continue;
+            }
+        };
+        for entry_point in compute_node.entry_points() {
+            match reflection.workgroup_size(entry_point) {
+                Some(size) => sizes.insert(entry_point.clone(), size),
+                None => warn!(
+                    "Compute entry point {:?} of compute node {:?} is not found in the shader",
+                    entry_point,
+                    compute_node.debug_name()
+                ),
+            }
+        }
+        compute_node.bind_group_count.set(
+            reflection
+                .bind_group_count(&descriptor.entry_point)
+                .ok_or_else(|| format!("Entry point {:?} is not found", descriptor.entry_point)),
+        );
+    }
+}
+
+fn reflect_render_node_shaders(
+    query: Query<Ref<RenderNode>>,
+    shaders: Option<Res<Assets<Shader>>>,
+) {
+    let Some(shaders) = shaders else {
+        return;
+    };
+    for render_node in query.iter() {
+        if render_node.is_changed() {
+            render_node.bind_group_count.clear();
+        } else if render_node.bind_group_count.get().is_some() {
+            continue;
+        }
+        let descriptor = &render_node.pipeline_descriptor;
+        let stages = std::iter::once((
+            &descriptor.vertex.shader,
+            &descriptor.vertex.shader_defs,
+            &descriptor.vertex.entry_point,
+        ))
+        .chain(descriptor.fragment.as_ref().map(|fragment| {
+            (
+                &fragment.shader,
+                &fragment.shader_defs,
+                &fragment.entry_point,
+            )
+        }));
+        let mut count = Ok(0);
+        for (shader, shader_defs, entry_point) in stages {
+            let stage_count = match ShaderReflection::from_shader(&shaders, shader, shader_defs) {
+                Ok(reflection) => reflection
+                    .bind_group_count(entry_point)
+                    .ok_or_else(|| format!("Entry point {:?} is not found", entry_point)),
+                Err(ShaderReflectionError::NotLoaded(_)) => return,
+                Err(err) => Err(err.to_string()),
+            };
+            count = count.and_then(|count: u32| Ok(count.max(stage_count?)));
+        }
+        if let Err(err) = &count {
+            warn!(
+                "Cannot reflect shaders of render node {:?}: {}",
+                render_node.debug_name(),
+                err
+            );
+        }
+        render_node.bind_group_count.set(count);
+    }
+}
+
+fn mark_compute_nodes_on_shader_modified(
+    mut events: EventReader<AssetEvent<Shader>>,
+    mut query: Query<&mut ComputeNode>,
+    mut render_nodes: Query<&mut RenderNode>,
+) {
+    let ids: Vec<AssetId<Shader>> = events
+        .read()
+        .filter_map(|event| {
+            if let AssetEvent::Modified { id } = event {
+                Some(*id)
+            } else {
+                None
+            }
+        })
+        .collect();
+    if ids.is_empty() {
+        return;
+    }
+
+    for mut compute_node in query.iter_mut() {
+        let modified = std::iter::once(&compute_node.pipeline_descriptor.shader)
+            .chain(&compute_node.shader_imports)
+            .any(|shader| ids.contains(&shader.id()));
+        if modified {
+            debug!(
+                "Shader of compute node {:?} is modified, recompiling",
+                compute_node.debug_name()
+            );
+            compute_node.set_changed();
+        }
+    }
+    for mut render_node in render_nodes.iter_mut() {
+        let descriptor = &render_node.pipeline_descriptor;
+        let modified = std::iter::once(&descriptor.vertex.shader)
+            .chain(
+                descriptor
+                    .fragment
+                    .as_ref()
+                    .map(|fragment| &fragment.shader),
+            )
+            .any(|shader| ids.contains(&shader.id()));
+        if modified {
+            debug!(
+                "Shader of render node {:?} is modified, recompiling",
+                render_node.debug_name()
+            );
+            render_node.set_changed();
+        }
+    }
+}
+
+/// Configuration of the compute pipeline. Any change of the component, including a mutable
+/// access through `Mut<ComputeNode>`, re-extracts it to the Render World and recompiles the
+/// pipeline. Per-frame data should be provided by the input nodes instead.
 #[derive(Component, Clone, Debug)]
 pub struct ComputeNode {
     pub label: Option<Cow<'static, str>>,
+    /// Human-readable name used for the compute pass, bind group and debug group labels.
+    pub debug_name: Option<Cow<'static, str>>,
+    /// Bind group of the resources declared without an explicit group.
     pub bind_group_index: u32,
     pub pipeline_descriptor: render_resource::ComputePipelineDescriptor,
+    /// Shader modules imported by name with `#import`, the handles keep them loaded until the
+    /// pipeline is compiled. Modules imported by asset path are loaded as shader dependencies.
+    pub shader_imports: Vec<Handle<Shader>>,
     pub binding_resource_info: Vec<BindResourceCreationInfo>,
     pub dispatch_workgroups_strategy: DispatchWorkgroupsStrategy,
+    pub dispatch_chunking: Option<DispatchChunking>,
+    pub push_constants_strategy: Option<PushConstantsStrategy>,
+    /// Name of the shader def that is set to the number of declared bind resources.
+    pub bind_resource_count_shader_def: Option<String>,
+    /// Skips the dispatch when the inputs and the dispatch parameters are the same as in the
+    /// last run and none of the inputs was written in the current frame, see [`crate::node::memo`].
+    pub memoize: bool,
+    /// Handling of the inputs without a value in the graph context, see [`MissingInputMode`].
+    pub missing_input_mode: MissingInputMode,
+    /// Entry points of the same shader dispatched in order after the main entry point, with the
+    /// same bind groups. Requires the explicit bind group layout in the pipeline descriptor.
+    pub additional_passes: Vec<ComputeNodePass>,
+    /// Entries of the bind group layouts created in the Render World, when the pipeline
+    /// descriptor has no explicit layout.
+    pub layout_entries: Vec<Vec<render_resource::BindGroupLayoutEntry>>,
+    /// Layouts shared with other nodes, used instead of the pipeline descriptor layout.
+    pub shared_layout: Option<SharedBindGroupLayout>,
 
+    pub(crate) bind_resource_sizes: BindResourceSizes,
+    pub(crate) dispatched_workgroups: DispatchedWorkgroups,
+    pub(crate) dispatched_dynamic_offsets: DispatchedDynamicOffsets,
+    pub(crate) workgroup_sizes: ReflectedWorkgroupSizes,
+    pub(crate) bind_group_count: ReflectedBindGroupCount,
+    pub(crate) switch: NodeSwitch,
     pub(crate) state: ComputeNodeState,
 }
 
+/// Entry point of the compute node shader with its own pipeline and dispatch.
+#[derive(Clone, Debug)]
+pub struct ComputeNodePass {
+    pub entry_point: Cow<'static, str>,
+    pub dispatch_workgroups_strategy: DispatchWorkgroupsStrategy,
+}
+
+impl ComputeNode {
+    /// Sizes of the buffers bound during the last run of the node.
+    pub fn bind_resource_sizes(&self) -> &BindResourceSizes {
+        &self.bind_resource_sizes
+    }
+
+    /// Workgroup counts of the last dispatch, including the ones computed from the graph context.
+    pub fn dispatched_workgroups(&self) -> &DispatchedWorkgroups {
+        &self.dispatched_workgroups
+    }
+
+    /// Dynamic offsets of the bind groups of the last dispatch, resolved from the graph context.
+    pub fn dispatched_dynamic_offsets(&self) -> &DispatchedDynamicOffsets {
+        &self.dispatched_dynamic_offsets
+    }
+
+    /// Workgroup sizes of the entry points, reflected by [`ShaderReflectionPlugin`].
+    pub fn workgroup_sizes(&self) -> &ReflectedWorkgroupSizes {
+        &self.workgroup_sizes
+    }
+
+    /// Bind group layout entries of the pipeline: the explicit layout entries, or the
+    /// bindings reflected from the shader module when the layout is derived by the pipeline.
+    /// `None` until the shader and its imports are loaded, or if the shader cannot be reflected.
+    /// Intended for debugging bind group creation errors.
+    pub fn reflected_layout_entries(
+        &self,
+        shaders: &Assets<Shader>,
+    ) -> Option<Vec<ReflectedLayoutEntry>> {
+        let explicit_entries = match &self.shared_layout {
+            Some(shared_layout) => shared_layout.entries(),
+            None => &self.layout_entries,
+        };
+        if !explicit_entries.is_empty() {
+            return Some(ReflectedLayoutEntry::from_layout_entries(explicit_entries));
+        }
+        let descriptor = self.specialized_pipeline_descriptor();
+        ShaderReflection::from_shader(shaders, &descriptor.shader, &descriptor.shader_defs)
+            .ok()
+            .map(|reflection| reflection.layout_entries())
+    }
+
+    /// Differences between [`Self::reflected_layout_entries`] and the declared bind resources:
+    /// missing bindings, slot types and read-only access of the storage buffers.
+    pub fn layout_mismatches(&self, shaders: &Assets<Shader>) -> Option<Vec<String>> {
+        let entries = self.reflected_layout_entries(shaders)?;
+        let find_entry = |group: u32, binding: u32| {
+            entries
+                .iter()
+                .find(|entry| entry.group == group && entry.binding == binding)
+        };
+        let mut mismatches = Vec::new();
+        for info in &self.binding_resource_info {
+            let Some(entry) = find_entry(info.group, info.binding) else {
+                mismatches.push(format!(
+                    "Bind resource {:?} at group {} binding {} is not in the layout",
+                    info.name, info.group, info.binding
+                ));
+                continue;
+            };
+            let slot_type = match &info.direction {
+                BindResourceDirection::Input(slot_type)
+                | BindResourceDirection::InputOutput(slot_type) => *slot_type,
+                BindResourceDirection::Output(descriptor) => descriptor.to_slot_type(),
+            };
+            if slot_type != entry.kind.slot_type() {
+                mismatches.push(format!(
+                    "Bind resource {:?} is {:?}, but the layout entry {:?} is {:?}",
+                    info.name, slot_type, entry.name, entry.kind
+                ));
+            } else if let ReflectedBindingKind::StorageBuffer { read_only } = entry.kind {
+                if read_only != info.read_only {
+                    mismatches.push(format!(
+                        "Bind resource {:?} has read_only {}, but the layout entry {:?} has {}",
+                        info.name, info.read_only, entry.name, read_only
+                    ));
+                }
+            }
+            let declared_count = match &info.direction {
+                BindResourceDirection::Output(BindResourceCreationDescriptor::TextureArray(
+                    textures,
+                )) => Some(textures.len() as u32),
+                _ => None,
+            };
+            if entry.count.is_some() && declared_count != entry.count {
+                mismatches.push(format!(
+                    "Bind resource {:?} has {:?} elements, but the layout entry {:?} has {:?}",
+                    info.name, declared_count, entry.name, entry.count
+                ));
+            }
+        }
+        for entry in &entries {
+            if !self
+                .binding_resource_info
+                .iter()
+                .any(|info| info.group == entry.group && info.binding == entry.binding)
+            {
+                mismatches.push(format!(
+                    "Layout entry {:?} at group {} binding {} has no bind resource",
+                    entry.name, entry.group, entry.binding
+                ));
+            }
+        }
+        Some(mismatches)
+    }
+
+    fn entry_points(&self) -> impl Iterator<Item = &Cow<'static, str>> {
+        std::iter::once(&self.pipeline_descriptor.entry_point)
+            .chain(self.additional_passes.iter().map(|pass| &pass.entry_point))
+    }
+}
+
 #[derive(Clone, Debug)]
 pub(crate) enum ComputeNodeState {
     Creating,
     PipelineQueued {
         pipeline_id: render_resource::CachedComputePipelineId,
+        pass_pipeline_ids: Vec<render_resource::CachedComputePipelineId>,
     },
     PipelineCached {
-        layout: render_resource::BindGroupLayout,
+        layouts: Vec<(u32, render_resource::BindGroupLayout)>,
         pipeline: render_resource::ComputePipeline,
+        pass_pipelines: Vec<render_resource::ComputePipeline>,
     },
     ReadyToRun {
-        node: ComputeNodeImpl,
+        node: Box<ComputeNodeImpl>,
     },
     Err(String),
 }
 
 #[derive(Clone, Debug)]
 pub(crate) struct ComputeNodeImpl {
-    label: Option<Cow<'static, str>>,
-    bind_group_index: u32,
-    layout: render_resource::BindGroupLayout,
+    debug_name: Cow<'static, str>,
+    layouts: Vec<(u32, render_resource::BindGroupLayout)>,
+    entry_point: Cow<'static, str>,
     pipeline: render_resource::ComputePipeline,
+    passes: Vec<(
+        Cow<'static, str>,
+        render_resource::ComputePipeline,
+        DispatchWorkgroupsStrategy,
+    )>,
     bind_resources: NodeResources,
     input_slots: Vec<render_graph::SlotInfo>,
     output_slots: Vec<render_graph::SlotInfo>,
     dispatch_workgroups_strategy: DispatchWorkgroupsStrategy,
+    dispatch_chunking: Option<DispatchChunking>,
+    push_constants_strategy: Option<PushConstantsStrategy>,
+    push_constant_ranges: Vec<render_resource::PushConstantRange>,
+    switch: NodeSwitch,
+    memo: Option<NodeMemo>,
+    dispatched_workgroups: DispatchedWorkgroups,
+    dispatched_dynamic_offsets: DispatchedDynamicOffsets,
+    workgroup_sizes: ReflectedWorkgroupSizes,
+    unknown_workgroup_size: LogOnce,
+    invalid_push_constants: LogOnce,
+}
+
+/// Buffer with the indirect dispatch arguments and the offset of the arguments.
+type IndirectDispatch = (render_resource::Buffer, u64);
+
+impl ComputeNodeImpl {
+    fn dispatch(
+        strategy: &DispatchWorkgroupsStrategy,
+        graph: &render_graph::RenderGraphContext,
+        render_device: &RenderDevice,
+        workgroup_size: Option<Workgroups>,
+    ) -> Result<(Option<Workgroups>, Option<IndirectDispatch>), render_graph::NodeRunError> {
+        let indirect = match strategy {
+            DispatchWorkgroupsStrategy::Indirect {
+                buffer_slot,
+                offset,
+            } => Some((
+                graph.get_input_buffer(buffer_slot.clone())?.clone(),
+                *offset,
+            )),
+            _ => None,
+        };
+        Ok((
+            strategy.workgroups_to_dispatch(graph, render_device, workgroup_size),
+            indirect,
+        ))
+    }
 }
 
 impl render_graph::Node for ComputeNodeImpl {
@@ -64,47 +448,293 @@ impl render_graph::Node for ComputeNodeImpl {
         &self,
         graph: &mut render_graph::RenderGraphContext,
         render_context: &mut RenderContext,
-        _world: &World,
+        world: &World,
     ) -> Result<(), render_graph::NodeRunError> {
         let render_device = render_context.render_device().clone();
+        let node_error = |err: BindResourceError| err.into_node_run_error(&self.debug_name);
+        if !self.switch.is_enabled() {
+            debug!(
+                "Compute node {:?} is disabled, publishing previous outputs",
+                &self.debug_name
+            );
+            self.bind_resources
+                .set_previous_output_slots(graph, &render_device, world)
+                .map_err(node_error)?;
+            return Ok(());
+        }
+        let push_constants = self
+            .push_constants_strategy
+            .as_ref()
+            .map(|strategy| strategy.push_constants(graph));
+        if let Some(push_constants) = &push_constants {
+            let validation =
+                PushConstantsStrategy::validate(push_constants, &self.push_constant_ranges)
+                    .and_then(|_| {
+                        self.dispatch_chunking.as_ref().map_or(Ok(()), |chunking| {
+                            chunking.validate_push_constants(push_constants)
+                        })
+                    });
+            if let Err(err) = validation {
+                if self.invalid_push_constants.first() {
+                    error!(
+                        "Compute node {:?} is not dispatched, publishing previous outputs: {}",
+                        &self.debug_name, err
+                    );
+                }
+                self.bind_resources
+                    .set_previous_output_slots(graph, &render_device, world)
+                    .map_err(node_error)?;
+                return Ok(());
+            }
+        }
         let command_encoder = render_context.command_encoder();
-        let bind_group = self
+        self.bind_resources
+            .release_transient_resources()
+            .map_err(node_error)?;
+        let bind_groups = self
             .bind_resources
-            .set_bind_group(&render_device, graph, &self.layout)?;
-        let workgroups = self
-            .dispatch_workgroups_strategy
-            .workgroups_to_dispatch(graph);
+            .set_bind_groups(&render_device, graph, &self.layouts, Some(&self.debug_name))
+            .map_err(node_error)?;
+        let allocated_bytes = self.bind_resources.allocated_bytes().map_err(node_error)?;
+        NodeMemoryUsage::record_in(world, self, allocated_bytes);
+        let dispatches = std::iter::once((
+            &self.entry_point,
+            &self.pipeline,
+            &self.dispatch_workgroups_strategy,
+        ))
+        .chain(
+            self.passes
+                .iter()
+                .map(|(entry_point, pipeline, strategy)| (entry_point, pipeline, strategy)),
+        )
+        .map(|(entry_point, pipeline, strategy)| {
+            let workgroup_size = self.workgroup_sizes.get(entry_point);
+            if workgroup_size.is_none()
+                && matches!(strategy, DispatchWorkgroupsStrategy::CoverExtent(..))
+                && self.unknown_workgroup_size.first()
+            {
+                warn!(
+                    "Workgroup size of entry point {:?} of compute node {:?} is not reflected yet, the pass is not dispatched",
+                    entry_point, &self.debug_name
+                );
+            }
+            let (workgroups, indirect) =
+                Self::dispatch(strategy, graph, &render_device, workgroup_size)?;
+            Ok((pipeline, workgroups, indirect))
+        })
+        .collect::<Result<Vec<_>, render_graph::NodeRunError>>()?;
         self.bind_resources
-            .set_output_slots(graph, &render_device)?;
+            .set_output_slots(graph, &render_device, world)
+            .map_err(node_error)?;
+        let dynamic_offsets: Vec<GroupDynamicOffsets> = bind_groups
+            .iter()
+            .map(|(group, _)| (*group, self.bind_resources.dynamic_offsets(graph, *group)))
+            .collect();
+        let memo_key = self
+            .memo
+            .as_ref()
+            .map(|memo| {
+                let mut parameters: Vec<u32> = dispatches
+                    .iter()
+                    .filter_map(|(_, workgroups, _)| *workgroups)
+                    .flat_map(|w| [w.0, w.1, w.2])
+                    .collect();
+                for (_, offsets) in &dynamic_offsets {
+                    parameters.extend(offsets);
+                }
+                let key = MemoKey {
+                    resources: self.bind_resources.resource_ids(graph)?,
+                    parameters,
+                    push_constants: push_constants.clone(),
+                };
+                Ok((memo, key))
+            })
+            .transpose()
+            .map_err(node_error)?;
+        if let Some((memo, key)) = &memo_key {
+            if memo.is_clean(key, world) {
+                debug!(
+                    "Compute node {:?} inputs are not changed, skipping the dispatch",
+                    &self.debug_name
+                );
+                return Ok(());
+            }
+        }
+
+        let buffers_to_clear = self
+            .bind_resources
+            .buffers_to_clear(graph, &render_device)
+            .map_err(node_error)?;
 
+        command_encoder.push_debug_group(&self.debug_name);
+        let timestamps = world.get_resource::<TimestampQuerySet>();
+        let end_timestamp = timestamps.and_then(|timestamps| {
+            timestamps.write_begin(command_encoder, self.debug_name.clone())
+        });
+        for buffer in &buffers_to_clear {
+            command_encoder.clear_buffer(buffer, 0, None);
+        }
         {
             let mut pass =
                 command_encoder.begin_compute_pass(&render_resource::ComputePassDescriptor {
-                    label: Some(type_name::<Self>()),
+                    label: Some(&self.debug_name),
                 });
 
-            pass.set_bind_group(self.bind_group_index, &bind_group, &[]);
-            pass.set_pipeline(&self.pipeline);
-            pass.dispatch_workgroups(workgroups.0, workgroups.1, workgroups.2);
+            for ((group, bind_group), (_, offsets)) in bind_groups.iter().zip(&dynamic_offsets) {
+                pass.set_bind_group(*group, bind_group, offsets);
+            }
+            for (pipeline, workgroups, indirect) in &dispatches {
+                pass.set_pipeline(pipeline);
+                if let Some(push_constants) = &push_constants {
+                    pass.set_push_constants(0, push_constants);
+                }
+                match (workgroups, &self.dispatch_chunking) {
+                    (Some(workgroups), Some(chunking)) => {
+                        for (offset, count) in chunking.chunks(*workgroups) {
+                            let offset = [offset.0, offset.1, offset.2];
+                            pass.set_push_constants(
+                                chunking.push_constant_offset,
+                                bevy::core::cast_slice(&offset),
+                            );
+                            pass.dispatch_workgroups(count.0, count.1, count.2);
+                        }
+                    }
+                    (Some(workgroups), None) => {
+                        pass.dispatch_workgroups(workgroups.0, workgroups.1, workgroups.2);
+                    }
+                    (None, _) => {
+                        if let Some((buffer, offset)) = indirect {
+                            pass.dispatch_workgroups_indirect(buffer, *offset);
+                        }
+                    }
+                }
 
-            debug!(
-                "Dispatched Compute pass {:?} with {:?} workgroups",
-                &self.label, &workgroups
-            );
+                debug!(
+                    "Dispatched Compute pass {:?} with {:?} workgroups",
+                    &self.debug_name,
+                    workgroups.map_or("indirect".to_string(), |w| format!("{:?}", w))
+                );
+            }
+        }
+        if let (Some(timestamps), Some(index)) = (timestamps, end_timestamp) {
+            timestamps.write_end(command_encoder, index);
+        }
+        command_encoder.pop_debug_group();
+        self.dispatched_workgroups.update(
+            dispatches
+                .iter()
+                .map(|(_, workgroups, _)| *workgroups)
+                .collect(),
+        );
+        self.dispatched_dynamic_offsets.update(dynamic_offsets);
+        self.bind_resources
+            .mark_written(graph, world)
+            .map_err(node_error)?;
+        if let Some((memo, key)) = memo_key {
+            memo.update(key);
         }
         Ok(())
     }
 }
 
+impl ComputeNode {
+    /// Switch that allows to skip the node runs without recreating the pipeline.
+    pub fn switch(&self) -> &NodeSwitch {
+        &self.switch
+    }
+
+    /// Debug name, or the label, or the type name of the node if neither is defined.
+    pub fn debug_name(&self) -> Cow<'static, str> {
+        self.debug_name
+            .clone()
+            .or_else(|| self.label.clone())
+            .unwrap_or(Cow::Borrowed(type_name::<ComputeNodeImpl>()))
+    }
+
+    fn specialized_pipeline_descriptor(&self) -> render_resource::ComputePipelineDescriptor {
+        let mut descriptor = self.pipeline_descriptor.clone();
+        if let Some(def_name) = &self.bind_resource_count_shader_def {
+            descriptor
+                .shader_defs
+                .push(render_resource::ShaderDefVal::UInt(
+                    def_name.clone(),
+                    self.binding_resource_info.len() as u32,
+                ));
+        }
+        descriptor
+    }
+
+    fn bind_group_layouts(
+        &self,
+        pipeline: &render_resource::ComputePipeline,
+    ) -> Option<Result<Vec<(u32, render_resource::BindGroupLayout)>, String>> {
+        pipeline_bind_group_layouts(
+            &self.binding_resource_info,
+            &self.pipeline_descriptor.layout,
+            |index| pipeline.get_bind_group_layout(index).into(),
+            &self.bind_group_count,
+        )
+    }
+}
+
 impl NodeProvider for ComputeNode {
-    fn update(&mut self, _world: &mut World) {
-        let pipeline_cache = _world.resource::<PipelineCache>();
+    fn update(&mut self, world: &mut World) {
+        if let (ComputeNodeState::Creating, Some(shared_layout)) =
+            (&self.state, &self.shared_layout)
+        {
+            self.pipeline_descriptor.layout =
+                shared_layout.get_or_create(world.resource::<RenderDevice>());
+        }
+        if matches!(self.state, ComputeNodeState::Creating)
+            && self.pipeline_descriptor.layout.is_empty()
+            && !self.layout_entries.is_empty()
+        {
+            let render_device = world.resource::<RenderDevice>();
+            self.pipeline_descriptor.layout = self
+                .layout_entries
+                .iter()
+                .map(|entries| {
+                    render_device.create_bind_group_layout(
+                        &render_resource::BindGroupLayoutDescriptor {
+                            label: self.label.as_deref(),
+                            entries,
+                        },
+                    )
+                })
+                .collect();
+        }
+        let pipeline_cache = world.resource::<PipelineCache>();
         let new_state = match &self.state {
             ComputeNodeState::Creating => ComputeNodeState::PipelineQueued {
                 pipeline_id: pipeline_cache
-                    .queue_compute_pipeline(self.pipeline_descriptor.clone()),
+                    .queue_compute_pipeline(self.specialized_pipeline_descriptor()),
+                pass_pipeline_ids: self
+                    .additional_passes
+                    .iter()
+                    .map(|pass| {
+                        let mut descriptor = self.specialized_pipeline_descriptor();
+                        descriptor.entry_point = pass.entry_point.clone();
+                        pipeline_cache.queue_compute_pipeline(descriptor)
+                    })
+                    .collect(),
             },
-            ComputeNodeState::PipelineQueued { pipeline_id } => {
+            ComputeNodeState::PipelineQueued {
+                pipeline_id,
+                pass_pipeline_ids,
+            } => {
+                let mut pass_pipelines = Vec::with_capacity(pass_pipeline_ids.len());
+                for pass_pipeline_id in pass_pipeline_ids {
+                    match pipeline_cache.get_compute_pipeline_state(*pass_pipeline_id) {
+                        render_resource::CachedPipelineState::Ok(
+                            render_resource::Pipeline::ComputePipeline(pipeline),
+                        ) => pass_pipelines.push(pipeline.clone()),
+                        render_resource::CachedPipelineState::Err(err) => {
+                            self.state = ComputeNodeState::Err(err.to_string());
+                            return;
+                        }
+                        _ => return,
+                    }
+                }
                 match pipeline_cache.get_compute_pipeline_state(*pipeline_id) {
                     render_resource::CachedPipelineState::Ok(
                         render_resource::Pipeline::ComputePipeline(pipeline),
@@ -112,18 +742,20 @@ impl NodeProvider for ComputeNode {
                         let cached_pipeline = pipeline_cache
                             .get_compute_pipeline(*pipeline_id)
                             .expect("Cannot find Compute pipeline with status Ok in cache");
-                        let layout = self
-                            .pipeline_descriptor
-                            .layout
-                            .get(self.bind_group_index as usize)
-                            .cloned()
-                            .unwrap_or(
-                                cached_pipeline
-                                    .get_bind_group_layout(self.bind_group_index)
-                                    .into(),
-                            );
-                        let pipeline = pipeline.clone();
-                        ComputeNodeState::PipelineCached { layout, pipeline }
+                        let Some(layouts) = self.bind_group_layouts(cached_pipeline) else {
+                            return;
+                        };
+                        match layouts {
+                            Ok(layouts) => {
+                                let pipeline = pipeline.clone();
+                                ComputeNodeState::PipelineCached {
+                                    layouts,
+                                    pipeline,
+                                    pass_pipelines,
+                                }
+                            }
+                            Err(err) => ComputeNodeState::Err(err),
+                        }
                     }
                     render_resource::CachedPipelineState::Err(err) => {
                         ComputeNodeState::Err(err.to_string())
@@ -133,23 +765,51 @@ impl NodeProvider for ComputeNode {
                     }
                 }
             }
-            ComputeNodeState::PipelineCached { layout, pipeline } => {
+            ComputeNodeState::PipelineCached {
+                layouts,
+                pipeline,
+                pass_pipelines,
+            } => {
                 let (input_slots, output_slots) =
                     BindResourceCreationInfo::input_output_slot_info(&self.binding_resource_info);
 
                 ComputeNodeState::ReadyToRun {
-                    node: ComputeNodeImpl {
-                        label: self.label.clone(),
-                        bind_group_index: self.bind_group_index,
-                        layout: layout.clone(),
+                    node: Box::new(ComputeNodeImpl {
+                        debug_name: self.debug_name(),
+                        layouts: layouts.clone(),
+                        entry_point: self.pipeline_descriptor.entry_point.clone(),
                         pipeline: pipeline.clone(),
+                        passes: self
+                            .additional_passes
+                            .iter()
+                            .zip(pass_pipelines.iter().cloned())
+                            .map(|(pass, pipeline)| {
+                                (
+                                    pass.entry_point.clone(),
+                                    pipeline,
+                                    pass.dispatch_workgroups_strategy.clone(),
+                                )
+                            })
+                            .collect(),
                         bind_resources: NodeResources::from_bind_resource_info(
                             self.binding_resource_info.clone(),
-                        ),
+                            self.bind_resource_sizes.clone(),
+                        )
+                        .with_missing_input_mode(self.missing_input_mode),
                         input_slots,
                         output_slots,
                         dispatch_workgroups_strategy: self.dispatch_workgroups_strategy.clone(),
-                    },
+                        dispatch_chunking: self.dispatch_chunking,
+                        push_constants_strategy: self.push_constants_strategy.clone(),
+                        push_constant_ranges: self.pipeline_descriptor.push_constant_ranges.clone(),
+                        switch: self.switch.clone(),
+                        memo: self.memoize.then(NodeMemo::default),
+                        dispatched_workgroups: self.dispatched_workgroups.clone(),
+                        dispatched_dynamic_offsets: self.dispatched_dynamic_offsets.clone(),
+                        workgroup_sizes: self.workgroup_sizes.clone(),
+                        unknown_workgroup_size: default(),
+                        invalid_push_constants: default(),
+                    }),
                 }
             }
             _ => {
@@ -175,7 +835,7 @@ impl NodeProvider for ComputeNode {
     ) {
         match &self.state {
             ComputeNodeState::ReadyToRun { node } => {
-                let node = node.clone();
+                let node = node.as_ref().clone();
                 debug!("Added node impl: {:?} {:?}", &node_name, &node);
                 add_or_replace_graph_node(graph, node_name, node);
             }
@@ -186,6 +846,71 @@ impl NodeProvider for ComputeNode {
             }
         };
     }
+
+    fn add_output_buffer_usages(
+        &mut self,
+        slot: &str,
+        usages: render_resource::BufferUsages,
+    ) -> Result<bool, String> {
+        let Some(info) = self
+            .binding_resource_info
+            .iter_mut()
+            .find(|info| info.name == slot)
+        else {
+            return Err(format!("Bind resource `{}` is not found", slot));
+        };
+        match &mut info.direction {
+            BindResourceDirection::Output(BindResourceCreationDescriptor::Buffer(strategy)) => {
+                Ok(strategy.add_usages(usages))
+            }
+            BindResourceDirection::Output(_) => {
+                Err(format!("Bind resource `{}` is not a buffer", slot))
+            }
+            // The buffer is created by the source of the input
+            BindResourceDirection::Input(_) | BindResourceDirection::InputOutput(_) => Ok(false),
+        }
+    }
+
+    fn add_output_texture_usages(
+        &mut self,
+        slot: &str,
+        usages: render_resource::TextureUsages,
+    ) -> Result<bool, String> {
+        let Some(info) = self
+            .binding_resource_info
+            .iter_mut()
+            .find(|info| info.name == slot)
+        else {
+            return Err(format!("Bind resource `{}` is not found", slot));
+        };
+        match &mut info.direction {
+            BindResourceDirection::Output(BindResourceCreationDescriptor::Texture(strategy))
+            | BindResourceDirection::Output(BindResourceCreationDescriptor::StorageTexture {
+                texture: strategy,
+                ..
+            }) => Ok(strategy.add_usages(usages)),
+            BindResourceDirection::Output(BindResourceCreationDescriptor::TextureArray(
+                strategies,
+            )) => {
+                let mut changed = false;
+                for strategy in strategies {
+                    changed |= strategy.add_usages(usages);
+                }
+                Ok(changed)
+            }
+            BindResourceDirection::Output(BindResourceCreationDescriptor::ExternalTextureView(
+                _,
+            )) => Err(format!(
+                "Texture of bind resource `{}` is created externally",
+                slot
+            )),
+            BindResourceDirection::Output(_) => {
+                Err(format!("Bind resource `{}` is not a texture", slot))
+            }
+            // The texture is created by the source of the input
+            BindResourceDirection::Input(_) | BindResourceDirection::InputOutput(_) => Ok(false),
+        }
+    }
 }
 
 impl ExtractComponent for ComputeNode {
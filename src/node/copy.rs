@@ -0,0 +1,264 @@
+use crate::node::memo::WrittenResources;
+use crate::node::{assert_copy_range_aligned, fallback_slot_value, LogOnce};
+use crate::resource::TextureViewSources;
+use bevy::log::{debug, warn};
+use bevy::prelude::*;
+use bevy_render::render_asset::RenderAssets;
+use bevy_render::render_graph;
+use bevy_render::render_graph::{NodeRunError, RenderGraphContext, SlotInfo, SlotType, SlotValue};
+use bevy_render::render_resource::{
+    Buffer, BufferAddress, BufferDescriptor, BufferUsages, Extent3d, ImageCopyTexture, Origin3d,
+    TextureAspect,
+};
+use bevy_render::renderer::{RenderContext, RenderDevice};
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+
+pub const SLOT_NAME: &str = "in";
+pub const OUT_SLOT_NAME: &str = "out";
+
+#[derive(Clone, Debug)]
+enum CopySource {
+    Buffer {
+        range: Option<Range<BufferAddress>>,
+        usages: BufferUsages,
+        destination: Arc<Mutex<Option<Buffer>>>,
+    },
+    Texture {
+        destination: Handle<Image>,
+        region: Option<(Origin3d, Extent3d)>,
+        unknown_texture: LogOnce,
+    },
+}
+
+/// Copies the input resource without a pipeline.
+/// Buffers are copied from the input slot into a buffer created by the node and published to
+/// the output slot. Textures are copied from the texture of the input view, resolved by
+/// [`TextureViewSources`], into the destination image, the output slot receives the view of
+/// the destination image. Source must have `COPY_SRC` and destination `COPY_DST` usage.
+/// The copy is skipped while the source texture or the destination image is not found, the
+/// output slot receives the destination view as is, or the `FallbackImage` view if the
+/// destination is not prepared yet.
+#[derive(Clone, Debug)]
+pub struct CopyNode {
+    source: CopySource,
+}
+
+impl CopyNode {
+    pub fn buffer() -> Self {
+        Self {
+            source: CopySource::Buffer {
+                range: None,
+                usages: BufferUsages::STORAGE | BufferUsages::COPY_SRC,
+                destination: default(),
+            },
+        }
+    }
+
+    pub fn texture(destination: Handle<Image>) -> Self {
+        Self {
+            source: CopySource::Texture {
+                destination,
+                region: None,
+                unknown_texture: default(),
+            },
+        }
+    }
+
+    /// Copies only the range of the input buffer, the destination buffer starts with the range.
+    /// Panics if the range bounds are not multiples of `COPY_BUFFER_ALIGNMENT`.
+    pub fn with_range(mut self, range: Range<BufferAddress>) -> Self {
+        assert_copy_range_aligned(&range);
+        if let CopySource::Buffer { range: r, .. } = &mut self.source {
+            *r = Some(range);
+        }
+        self
+    }
+
+    /// Usages of the destination buffer in addition to `COPY_DST`.
+    pub fn with_usages(mut self, usages: BufferUsages) -> Self {
+        if let CopySource::Buffer { usages: u, .. } = &mut self.source {
+            *u = usages;
+        }
+        self
+    }
+
+    /// Copies only the region of the source texture to the same origin of the destination.
+    pub fn with_region(mut self, origin: Origin3d, extent: Extent3d) -> Self {
+        if let CopySource::Texture { region, .. } = &mut self.source {
+            *region = Some((origin, extent));
+        }
+        self
+    }
+
+    fn slot_type(&self) -> SlotType {
+        match self.source {
+            CopySource::Buffer { .. } => SlotType::Buffer,
+            CopySource::Texture { .. } => SlotType::TextureView,
+        }
+    }
+
+    fn copy_buffer(
+        graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+        range: &Option<Range<BufferAddress>>,
+        usages: BufferUsages,
+        destination: &Mutex<Option<Buffer>>,
+    ) -> Result<(), NodeRunError> {
+        let input = graph.get_input_buffer(SLOT_NAME)?.clone();
+        let (offset, size) = match range {
+            Some(range) => {
+                let start = range.start.min(input.size());
+                (start, range.end.clamp(start, input.size()) - start)
+            }
+            None => (0, input.size()),
+        };
+        let mut destination = destination
+            .lock()
+            .expect("Copy destination mutex is poisoned");
+        let buffer = match destination.as_ref() {
+            Some(buffer) if buffer.size() == size => buffer.clone(),
+            _ => {
+                let buffer = Self::create_buffer(render_context.render_device(), size, usages);
+                *destination = Some(buffer.clone());
+                buffer
+            }
+        };
+
+        debug!(
+            "Copy buffer to buffer command added to the queue from `{:?}` to `{:?}`",
+            &input, &buffer
+        );
+        render_context
+            .command_encoder()
+            .copy_buffer_to_buffer(&input, offset, &buffer, 0, size);
+        let value = SlotValue::Buffer(buffer);
+        WrittenResources::mark_in(world, &value);
+        graph.set_output(OUT_SLOT_NAME, value)?;
+        Ok(())
+    }
+
+    fn create_buffer(
+        render_device: &RenderDevice,
+        size: BufferAddress,
+        usages: BufferUsages,
+    ) -> Buffer {
+        render_device.create_buffer(&BufferDescriptor {
+            label: "copy_node_buffer".into(),
+            size,
+            usage: usages | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    fn copy_texture(
+        graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+        destination: &Handle<Image>,
+        region: &Option<(Origin3d, Extent3d)>,
+        unknown_texture: &LogOnce,
+    ) -> Result<(), NodeRunError> {
+        let view = graph.get_input_texture(SLOT_NAME)?;
+        let source_texture = TextureViewSources::find(world, view);
+        let destination_image = world.resource::<RenderAssets<Image>>().get(destination);
+        let (Some(source_texture), Some(destination_image)) = (source_texture, destination_image)
+        else {
+            if unknown_texture.first() {
+                warn!(
+                    "Texture of the input view `{:?}` or prepared destination image `{:?}` not found, skipping the copy",
+                    view.id(),
+                    destination
+                );
+            }
+            let value = match destination_image {
+                Some(destination_image) => {
+                    SlotValue::TextureView(destination_image.texture_view.clone())
+                }
+                None => fallback_slot_value(
+                    SlotType::TextureView,
+                    world,
+                    render_context.render_device(),
+                ),
+            };
+            graph.set_output(OUT_SLOT_NAME, value)?;
+            return Ok(());
+        };
+        let (origin, extent) = region.unwrap_or_else(|| {
+            let source_size = source_texture.size();
+            let destination_size = destination_image.texture.size();
+            (
+                Origin3d::ZERO,
+                Extent3d {
+                    width: source_size.width.min(destination_size.width),
+                    height: source_size.height.min(destination_size.height),
+                    depth_or_array_layers: source_size
+                        .depth_or_array_layers
+                        .min(destination_size.depth_or_array_layers),
+                },
+            )
+        });
+
+        debug!(
+            "Copy texture to texture command added to the queue from `{:?}` to `{:?}`",
+            &source_texture, &destination_image.texture
+        );
+        render_context.command_encoder().copy_texture_to_texture(
+            ImageCopyTexture {
+                texture: &source_texture,
+                mip_level: 0,
+                origin,
+                aspect: TextureAspect::All,
+            },
+            ImageCopyTexture {
+                texture: &destination_image.texture,
+                mip_level: 0,
+                origin,
+                aspect: TextureAspect::All,
+            },
+            extent,
+        );
+        let value = SlotValue::TextureView(destination_image.texture_view.clone());
+        WrittenResources::mark_in(world, &value);
+        graph.set_output(OUT_SLOT_NAME, value)?;
+        Ok(())
+    }
+}
+
+impl render_graph::Node for CopyNode {
+    fn input(&self) -> Vec<SlotInfo> {
+        vec![SlotInfo::new(SLOT_NAME, self.slot_type())]
+    }
+
+    fn output(&self) -> Vec<SlotInfo> {
+        vec![SlotInfo::new(OUT_SLOT_NAME, self.slot_type())]
+    }
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        match &self.source {
+            CopySource::Buffer {
+                range,
+                usages,
+                destination,
+            } => Self::copy_buffer(graph, render_context, world, range, *usages, destination),
+            CopySource::Texture {
+                destination,
+                region,
+                unknown_texture,
+            } => Self::copy_texture(
+                graph,
+                render_context,
+                world,
+                destination,
+                region,
+                unknown_texture,
+            ),
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use crate::builder::SubGraphBuilder;
+use crate::node::memo::WrittenResources;
+use crate::node::output::{OutputBuffer, OutputError};
+use crate::node::{input, output};
+use bevy::log::debug;
+use bevy::prelude::*;
+use bevy_render::render_graph;
+use bevy_render::render_graph::{
+    NodeLabel, NodeRunError, RenderGraphContext, SlotInfo, SlotType, SlotValue,
+};
+use bevy_render::render_resource::{Buffer, BufferAddress, BufferDescriptor, BufferUsages};
+use bevy_render::renderer::RenderContext;
+use std::borrow::Cow;
+use std::mem::size_of;
+use std::sync::{Arc, Mutex};
+
+/// Histogram of `u32` bins that is cleared before every run of the sub graph.
+///
+/// The node outputs the bins buffer on the `input::SLOT_NAME` slot, so it should be bound
+/// as `input_output` buffer by the compute node that increments the bins with `atomicAdd`.
+/// The bin counts are read back by the inner [`OutputBuffer`], that must be spawned as a
+/// component to be mapped.
+#[derive(Component, Clone, Debug)]
+pub struct HistogramNode {
+    bins: u32,
+    buffer: Arc<Mutex<Option<Buffer>>>,
+    output: OutputBuffer,
+}
+
+impl HistogramNode {
+    pub fn new(bins: u32) -> Self {
+        Self {
+            bins,
+            buffer: default(),
+            output: default(),
+        }
+    }
+
+    pub fn bins(&self) -> u32 {
+        self.bins
+    }
+
+    pub fn size(&self) -> BufferAddress {
+        self.bins as BufferAddress * size_of::<u32>() as BufferAddress
+    }
+
+    pub fn output_buffer(&self) -> OutputBuffer {
+        self.output.clone()
+    }
+
+    pub fn take_bin_counts(&self) -> Result<Vec<u32>, OutputError> {
+        self.output.take_buffer_as::<Vec<u32>>()
+    }
+
+    /// Adds the histogram and its readback nodes to the sub graph and connects them to
+    /// the `input_output` buffer slot of the node that fills the histogram.
+    pub fn add_to_sub_graph(
+        &self,
+        builder: SubGraphBuilder,
+        node_name: impl Into<Cow<'static, str>>,
+        target_node: impl Into<Cow<'static, str>>,
+        target_slot: impl Into<Cow<'static, str>>,
+    ) -> SubGraphBuilder {
+        let node_name = node_name.into();
+        let output_node_name: Cow<'static, str> = format!("{}_output", node_name).into();
+        let target_node = target_node.into();
+        let target_slot = target_slot.into();
+        builder
+            .add_node(node_name.clone(), self.clone())
+            .add_node(output_node_name.clone(), self.output.clone())
+            .add_slot_edge(
+                NodeLabel::Name(node_name),
+                input::SLOT_NAME,
+                NodeLabel::Name(target_node.clone()),
+                target_slot.clone(),
+            )
+            .add_slot_edge(
+                NodeLabel::Name(target_node),
+                target_slot,
+                NodeLabel::Name(output_node_name),
+                output::SLOT_NAME,
+            )
+    }
+}
+
+impl render_graph::Node for HistogramNode {
+    fn output(&self) -> Vec<SlotInfo> {
+        vec![SlotInfo::new(input::SLOT_NAME, SlotType::Buffer)]
+    }
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let size = self.size();
+        let mut lock = self
+            .buffer
+            .lock()
+            .expect("Histogram buffer mutex is poisoned");
+        let buffer = match lock.as_ref() {
+            Some(buffer) if buffer.size() == size => buffer.clone(),
+            _ => {
+                let buffer = render_context
+                    .render_device()
+                    .create_buffer(&BufferDescriptor {
+                        label: "histogram_buffer".into(),
+                        size,
+                        usage: BufferUsages::STORAGE
+                            | BufferUsages::COPY_DST
+                            | BufferUsages::COPY_SRC,
+                        mapped_at_creation: false,
+                    });
+                debug!("Created histogram buffer `{:?}`", &buffer);
+                *lock = Some(buffer.clone());
+                buffer
+            }
+        };
+        render_context
+            .command_encoder()
+            .clear_buffer(&buffer, 0, None);
+        let value = SlotValue::Buffer(buffer);
+        WrittenResources::mark_in(world, &value);
+        graph.set_output(input::SLOT_NAME, value)?;
+        Ok(())
+    }
+}
@@ -1,22 +1,74 @@
-use bevy::core::Pod;
-use bevy::log::{debug, error};
+//! Input nodes upload their data on the first run of the sub graph after it's changed by the
+//! setters, the other runs output the uploaded buffer as is. The data is shared with the
+//! Render World through `Arc<Mutex<_>>`, so setting it doesn't trigger change detection and the
+//! node doesn't need to be re-extracted: keep the node component on an entity, or a clone of it,
+//! and call the setters from any system. The next run of the sub graph uploads the new value.
+//!
+//! Node providers like `ComputeNode` are configuration: a changed component is extracted again
+//! and its pipeline is recompiled. Query them by `&` reference when only reading or toggling the
+//! `NodeSwitch`, and never store per-frame data in them.
+
+use crate::diagnostic::NodeMemoryUsage;
+use crate::node::memo::WrittenResources;
+use crate::node::{fallback_buffer, fallback_slot_value, LogOnce};
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext};
+use bevy::core::{bytes_of, Pod};
+use bevy::core_pipeline::core_3d::CORE_3D_DEPTH_FORMAT;
+use bevy::ecs::system::lifetimeless::SRes;
+use bevy::ecs::system::SystemParamItem;
+use bevy::log::{debug, error, warn};
 use bevy::prelude::*;
+use bevy::utils::thiserror::Error;
+use bevy::utils::BoxedFuture;
 use bevy_render::prelude::Image;
-use bevy_render::render_asset::RenderAssets;
+use bevy_render::render_asset::{PrepareAssetError, RenderAsset, RenderAssetPlugin, RenderAssets};
 use bevy_render::render_graph::{NodeRunError, RenderGraphContext, SlotInfo, SlotType, SlotValue};
 use bevy_render::render_resource::encase::internal::WriteInto;
 use bevy_render::render_resource::{
-    Buffer, BufferAddress, BufferUsages, BufferVec, DynamicStorageBuffer, ShaderType, StorageBuffer,
+    Buffer, BufferAddress, BufferDescriptor, BufferId, BufferInitDescriptor, BufferUsages,
+    BufferVec, DynamicStorageBuffer, Extent3d, Sampler, SamplerDescriptor, ShaderType,
+    StorageBuffer, TextureDescriptor, TextureDimension, TextureUsages, TextureView, UniformBuffer,
 };
 use bevy_render::renderer::{RenderContext, RenderDevice, RenderQueue};
+use bevy_render::view::ViewDepthTexture;
 use bevy_render::{render_graph, render_resource};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex, MutexGuard};
+use wgpu::COPY_BUFFER_ALIGNMENT;
 
 pub const SLOT_NAME: &str = "out";
 
 pub trait InputBuffer<T> {
     fn size(&self) -> BufferAddress;
     fn write_buffer(&self, device: &RenderDevice, queue: &RenderQueue) -> Option<Buffer>;
+
+    /// Buffer uploaded by the last `write_buffer`, if the data is not changed since then.
+    /// The node outputs it without the upload, so it's not marked as written.
+    /// `None` by default, the data is uploaded on every run.
+    fn uploaded_buffer(&self) -> Option<Buffer> {
+        None
+    }
+}
+
+/// Set by the setters of the input node and reset by the upload.
+#[derive(Clone, Debug, Default)]
+struct DataChanged(Arc<AtomicBool>);
+
+impl DataChanged {
+    fn set(&self) {
+        self.0.store(true, Ordering::Release);
+    }
+
+    /// Resets the flag before the upload, so the changes made during the upload are uploaded
+    /// by the next run.
+    fn take(&self) -> bool {
+        self.0.swap(false, Ordering::AcqRel)
+    }
+
+    fn get(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
 }
 
 macro_rules! impl_node_for_input_buffer {
@@ -36,12 +88,21 @@ macro_rules! impl_node_for_input_buffer {
                 world: &World,
             ) -> Result<(), NodeRunError> {
                 let queue = world.resource::<RenderQueue>();
-                if let Some(buffer) = self.write_buffer(render_context.render_device(), queue) {
+                let (buffer, uploaded) = match self.uploaded_buffer() {
+                    Some(buffer) => (Some(buffer), false),
+                    None => (self.write_buffer(render_context.render_device(), queue), true),
+                };
+                if let Some(buffer) = buffer {
                     debug!(
                         "Setting value for input buffer output slot `{}` to `{:?}`",
                         SLOT_NAME, buffer
                     );
-                    graph.set_output(SLOT_NAME, SlotValue::Buffer(buffer))?;
+                    NodeMemoryUsage::record_in(world, self, buffer.size());
+                    let value = SlotValue::Buffer(buffer);
+                    if uploaded {
+                        WrittenResources::mark_in(world, &value);
+                    }
+                    graph.set_output(SLOT_NAME, value)?;
                 } else {
                     error!("Buffer is not created on device!");
                 }
@@ -54,18 +115,22 @@ macro_rules! impl_node_for_input_buffer {
 #[derive(Clone, Component, Default)]
 pub struct DynamicStorageBufferNode<T: render_resource::ShaderType> {
     inner: Arc<Mutex<DynamicStorageBuffer<T>>>,
+    changed: DataChanged,
 }
 
 impl<T: render_resource::ShaderType + WriteInto> DynamicStorageBufferNode<T> {
     pub fn push(&self, val: T) -> u32 {
+        self.changed.set();
         self.inner.lock().unwrap().push(val)
     }
 
     pub fn clear(&self) {
+        self.changed.set();
         self.inner.lock().unwrap().clear()
     }
 
     pub fn add_usages(&self, usage: BufferUsages) {
+        self.changed.set();
         self.inner.lock().unwrap().add_usages(usage);
     }
 }
@@ -76,20 +141,30 @@ impl<T: render_resource::ShaderType + WriteInto> InputBuffer<T> for DynamicStora
     }
 
     fn write_buffer(&self, device: &RenderDevice, queue: &RenderQueue) -> Option<Buffer> {
+        self.changed.take();
         let mut lock = self.inner.lock().unwrap();
         lock.write_buffer(device, queue);
         lock.buffer().cloned()
     }
+
+    fn uploaded_buffer(&self) -> Option<Buffer> {
+        if self.changed.get() {
+            return None;
+        }
+        self.inner.lock().unwrap().buffer().cloned()
+    }
 }
 impl_node_for_input_buffer!(DynamicStorageBufferNode<T: ShaderType + WriteInto + 'static>);
 
 #[derive(Clone, Component, Default)]
 pub struct StorageBufferNode<T: render_resource::ShaderType> {
     inner: Arc<Mutex<StorageBuffer<T>>>,
+    changed: DataChanged,
 }
 
 impl<T: render_resource::ShaderType + WriteInto + Clone> StorageBufferNode<T> {
     pub fn set(&self, val: T) {
+        self.changed.set();
         self.inner.lock().unwrap().set(val);
     }
 
@@ -97,11 +172,14 @@ impl<T: render_resource::ShaderType + WriteInto + Clone> StorageBufferNode<T> {
         self.inner.lock().unwrap().get().clone()
     }
 
+    /// The data is uploaded on the next run, since it can be changed through the guard.
     pub fn lock(&self) -> MutexGuard<StorageBuffer<T>> {
+        self.changed.set();
         self.inner.lock().unwrap()
     }
 
     pub fn add_usages(&self, usage: BufferUsages) {
+        self.changed.set();
         self.inner.lock().unwrap().add_usages(usage);
     }
 }
@@ -112,30 +190,92 @@ impl<T: render_resource::ShaderType + WriteInto> InputBuffer<T> for StorageBuffe
     }
 
     fn write_buffer(&self, device: &RenderDevice, queue: &RenderQueue) -> Option<Buffer> {
+        self.changed.take();
         let mut lock = self.inner.lock().unwrap();
         lock.write_buffer(device, queue);
         lock.buffer().cloned()
     }
+
+    fn uploaded_buffer(&self) -> Option<Buffer> {
+        if self.changed.get() {
+            return None;
+        }
+        self.inner.lock().unwrap().buffer().cloned()
+    }
 }
 impl_node_for_input_buffer!(StorageBufferNode<T: ShaderType + WriteInto + Sync + Send + 'static>);
 
+#[derive(Clone, Component, Default)]
+pub struct UniformBufferNode<T: render_resource::ShaderType> {
+    inner: Arc<Mutex<UniformBuffer<T>>>,
+    changed: DataChanged,
+}
+
+impl<T: render_resource::ShaderType + WriteInto + Clone> UniformBufferNode<T> {
+    pub fn new(val: T) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(UniformBuffer::from(val))),
+            changed: default(),
+        }
+    }
+
+    pub fn set(&self, val: T) {
+        self.changed.set();
+        self.inner.lock().unwrap().set(val);
+    }
+
+    pub fn get(&self) -> T {
+        self.inner.lock().unwrap().get().clone()
+    }
+
+    pub fn add_usages(&self, usage: BufferUsages) {
+        self.changed.set();
+        self.inner.lock().unwrap().add_usages(usage);
+    }
+}
+
+impl<T: render_resource::ShaderType + WriteInto> InputBuffer<T> for UniformBufferNode<T> {
+    fn size(&self) -> BufferAddress {
+        self.inner.lock().unwrap().buffer().map_or(0, |b| b.size())
+    }
+
+    fn write_buffer(&self, device: &RenderDevice, queue: &RenderQueue) -> Option<Buffer> {
+        self.changed.take();
+        let mut lock = self.inner.lock().unwrap();
+        lock.write_buffer(device, queue);
+        lock.buffer().cloned()
+    }
+
+    fn uploaded_buffer(&self) -> Option<Buffer> {
+        if self.changed.get() {
+            return None;
+        }
+        self.inner.lock().unwrap().buffer().cloned()
+    }
+}
+impl_node_for_input_buffer!(UniformBufferNode<T: ShaderType + WriteInto + Sync + Send + 'static>);
+
 #[derive(Clone, Component)]
 pub struct BufferVecNode<T: Pod> {
     inner: Arc<Mutex<BufferVec<T>>>,
+    changed: DataChanged,
 }
 
 impl<T: Pod> BufferVecNode<T> {
     pub fn new(usages: BufferUsages) -> Self {
         Self {
             inner: Arc::new(Mutex::new(BufferVec::new(usages))),
+            changed: default(),
         }
     }
 
     pub fn push(&self, val: T) -> usize {
+        self.changed.set();
         self.inner.lock().unwrap().push(val)
     }
 
     pub fn clear(&self) {
+        self.changed.set();
         self.inner.lock().unwrap().clear()
     }
 }
@@ -152,29 +292,632 @@ impl<T: Pod> InputBuffer<T> for BufferVecNode<T> {
     }
 
     fn write_buffer(&self, device: &RenderDevice, queue: &RenderQueue) -> Option<Buffer> {
+        self.changed.take();
         let mut lock = self.inner.lock().unwrap();
         lock.write_buffer(device, queue);
         lock.buffer().cloned()
     }
+
+    fn uploaded_buffer(&self) -> Option<Buffer> {
+        if self.changed.get() {
+            return None;
+        }
+        self.inner.lock().unwrap().buffer().cloned()
+    }
 }
 impl_node_for_input_buffer!(BufferVecNode<T: Pod + Send + Sync + 'static>);
 
+/// Input buffer with the content generated lazily at upload time.
+/// Elements are written directly into the buffer mapped at creation, so the content
+/// is never materialized in an intermediate CPU `Vec`.
+#[derive(Clone, Component)]
+pub struct GeneratedBufferNode<T: Pod> {
+    len: usize,
+    usages: BufferUsages,
+    generator: Arc<dyn Fn(usize) -> T + Send + Sync>,
+    buffer: Arc<Mutex<Option<Buffer>>>,
+}
+
+impl<T: Pod> GeneratedBufferNode<T> {
+    /// Panics if `T` is zero-sized, there is no content to generate for it.
+    pub fn new(len: usize, generator: impl Fn(usize) -> T + Send + Sync + 'static) -> Self {
+        assert!(
+            std::mem::size_of::<T>() > 0,
+            "Generated buffer element must not be zero-sized"
+        );
+        Self {
+            len,
+            usages: BufferUsages::STORAGE,
+            generator: Arc::new(generator),
+            buffer: default(),
+        }
+    }
+
+    pub fn with_usages(mut self, usages: BufferUsages) -> Self {
+        self.usages = usages;
+        self
+    }
+
+    /// Drops the uploaded buffer, so the content is generated again on the next run.
+    pub fn invalidate(&self) {
+        self.buffer.lock().unwrap().take();
+    }
+}
+
+impl<T: Pod> InputBuffer<T> for GeneratedBufferNode<T> {
+    fn size(&self) -> BufferAddress {
+        (self.len * std::mem::size_of::<T>()) as BufferAddress
+    }
+
+    fn write_buffer(&self, device: &RenderDevice, _queue: &RenderQueue) -> Option<Buffer> {
+        let mut lock = self.buffer.lock().unwrap();
+        if lock.is_none() {
+            let element_size = std::mem::size_of::<T>();
+            let size = self.size().max(1);
+            let size = size.div_ceil(COPY_BUFFER_ALIGNMENT) * COPY_BUFFER_ALIGNMENT;
+            let buffer = device.create_buffer(&BufferDescriptor {
+                label: Some("generated_buffer"),
+                size,
+                usage: self.usages,
+                mapped_at_creation: true,
+            });
+            {
+                let mut mapped = buffer.slice(..).get_mapped_range_mut();
+                for (index, chunk) in mapped
+                    .chunks_exact_mut(element_size)
+                    .take(self.len)
+                    .enumerate()
+                {
+                    chunk.copy_from_slice(bytes_of(&(self.generator)(index)));
+                }
+            }
+            buffer.unmap();
+            *lock = Some(buffer);
+        }
+        lock.clone()
+    }
+
+    fn uploaded_buffer(&self) -> Option<Buffer> {
+        self.buffer.lock().unwrap().clone()
+    }
+}
+impl_node_for_input_buffer!(GeneratedBufferNode<T: Pod + Send + Sync + 'static>);
+
+/// GPU-only buffer without the CPU copy, allocated once and output as is on every run,
+/// e.g. for the state of ping-pong simulations that is written by the compute nodes.
+/// The buffer is zero-initialized on allocation.
+#[derive(Clone, Component)]
+pub struct PersistentBufferNode {
+    inner: Arc<Mutex<PersistentBuffer>>,
+}
+
+struct PersistentBuffer {
+    size: BufferAddress,
+    usages: BufferUsages,
+    buffer: Option<Buffer>,
+}
+
+impl PersistentBufferNode {
+    pub fn new(size: BufferAddress, usages: BufferUsages) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(PersistentBuffer {
+                size,
+                usages,
+                buffer: None,
+            })),
+        }
+    }
+
+    /// Drops the buffer, a new zeroed buffer of the size is allocated on the next run.
+    pub fn resize(&self, size: BufferAddress) {
+        let mut lock = self.inner.lock().unwrap();
+        lock.size = size;
+        lock.buffer = None;
+    }
+
+    /// Drops the buffer with its content, so it's allocated again on the next run.
+    pub fn reallocate(&self) {
+        self.inner.lock().unwrap().buffer = None;
+    }
+
+    pub fn is_allocated(&self) -> bool {
+        self.inner.lock().unwrap().buffer.is_some()
+    }
+}
+
+impl InputBuffer<u8> for PersistentBufferNode {
+    fn size(&self) -> BufferAddress {
+        let lock = self.inner.lock().unwrap();
+        lock.buffer
+            .as_ref()
+            .map_or(lock.size, |buffer| buffer.size())
+    }
+
+    fn write_buffer(&self, device: &RenderDevice, _queue: &RenderQueue) -> Option<Buffer> {
+        let mut lock = self.inner.lock().unwrap();
+        if lock.buffer.is_none() {
+            let size = lock.size.max(1);
+            let size = size.div_ceil(COPY_BUFFER_ALIGNMENT) * COPY_BUFFER_ALIGNMENT;
+            lock.buffer = Some(device.create_buffer(&BufferDescriptor {
+                label: Some("persistent_buffer"),
+                size,
+                usage: lock.usages,
+                mapped_at_creation: false,
+            }));
+        }
+        lock.buffer.clone()
+    }
+
+    fn uploaded_buffer(&self) -> Option<Buffer> {
+        self.inner.lock().unwrap().buffer.clone()
+    }
+}
+impl_node_for_input_buffer!(PersistentBufferNode);
+
+pub const READ_SLOT_NAME: &str = "read";
+pub const WRITE_SLOT_NAME: &str = "write";
+
+/// Two GPU buffers of the same size for the iterative compute, swapped on every run.
+/// The `read` slot outputs the buffer written by the previous run and the `write` slot
+/// outputs the other one, so the nodes read the last state and write the next one.
+#[derive(Clone, Component)]
+pub struct PingPongBufferNode {
+    inner: Arc<Mutex<PingPongBuffers>>,
+}
+
+struct PingPongBuffers {
+    size: BufferAddress,
+    usages: BufferUsages,
+    buffers: Option<[Buffer; 2]>,
+    front: usize,
+    seed: Option<Vec<u8>>,
+}
+
+impl PingPongBufferNode {
+    /// `COPY_DST` is added to the usages to write the seed.
+    pub fn new(size: BufferAddress, usages: BufferUsages) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(PingPongBuffers {
+                size,
+                usages: usages | BufferUsages::COPY_DST,
+                buffers: None,
+                front: 0,
+                seed: None,
+            })),
+        }
+    }
+
+    /// Initial contents of the `read` buffer, written on the next run.
+    pub fn seed<T: Pod>(&self, data: &[T]) {
+        self.inner.lock().unwrap().seed = Some(bevy::core::cast_slice(data).to_vec());
+    }
+
+    /// Buffer written by the last run, it's output to the `read` slot on the next run.
+    pub fn front_buffer(&self) -> Option<Buffer> {
+        let lock = self.inner.lock().unwrap();
+        let back = 1 - lock.front;
+        lock.buffers.as_ref().map(|buffers| buffers[back].clone())
+    }
+
+    pub fn size(&self) -> BufferAddress {
+        self.inner.lock().unwrap().size
+    }
+}
+
+impl render_graph::Node for PingPongBufferNode {
+    fn output(&self) -> Vec<SlotInfo> {
+        vec![
+            SlotInfo::new(READ_SLOT_NAME, SlotType::Buffer),
+            SlotInfo::new(WRITE_SLOT_NAME, SlotType::Buffer),
+        ]
+    }
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let mut lock = self.inner.lock().unwrap();
+        let lock = &mut *lock;
+        match &lock.buffers {
+            // The buffer written by the previous run becomes the front one
+            Some(_) => lock.front = 1 - lock.front,
+            None => {
+                let size = lock.size.max(1);
+                let size = size.div_ceil(COPY_BUFFER_ALIGNMENT) * COPY_BUFFER_ALIGNMENT;
+                let create = || {
+                    render_context
+                        .render_device()
+                        .create_buffer(&BufferDescriptor {
+                            label: Some("ping_pong_buffer"),
+                            size,
+                            usage: lock.usages,
+                            mapped_at_creation: false,
+                        })
+                };
+                lock.buffers = Some([create(), create()]);
+            }
+        }
+        let buffers = lock.buffers.as_ref().expect("Buffers are created");
+        let (read, write) = (&buffers[lock.front], &buffers[1 - lock.front]);
+        if let Some(seed) = lock.seed.take() {
+            let mut seed = seed;
+            seed.truncate(read.size() as usize);
+            seed.resize(
+                seed.len().next_multiple_of(COPY_BUFFER_ALIGNMENT as usize),
+                0,
+            );
+            world.resource::<RenderQueue>().write_buffer(read, 0, &seed);
+            WrittenResources::mark_in(world, &SlotValue::Buffer(read.clone()));
+        }
+        for (slot, buffer) in [(READ_SLOT_NAME, read), (WRITE_SLOT_NAME, write)] {
+            graph.set_output(slot, SlotValue::Buffer(buffer.clone()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Registers `BufferAsset` with the loader of the raw `.bin` files and uploads the loaded
+/// assets to the GPU, so they can be used by `AssetBufferNode`.
+pub struct BufferAssetPlugin;
+
+impl Plugin for BufferAssetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<BufferAsset>()
+            .init_asset_loader::<BufferAssetLoader>()
+            .add_plugins(RenderAssetPlugin::<BufferAsset>::default());
+    }
+}
+
+/// Raw bytes uploaded to a GPU buffer with the usages once the asset is loaded.
+/// Modified asset is uploaded again into a new buffer.
+#[derive(Asset, TypePath, Clone, Debug)]
+pub struct BufferAsset {
+    pub data: Vec<u8>,
+    pub usages: BufferUsages,
+}
+
+impl BufferAsset {
+    pub fn new(data: Vec<u8>) -> Self {
+        Self {
+            data,
+            usages: BufferUsages::STORAGE | BufferUsages::COPY_SRC,
+        }
+    }
+
+    pub fn from_pod<T: Pod>(data: &[T]) -> Self {
+        Self::new(bytemuck::cast_slice(data).to_vec())
+    }
+}
+
+impl RenderAsset for BufferAsset {
+    type ExtractedAsset = BufferAsset;
+    type PreparedAsset = Buffer;
+    type Param = SRes<RenderDevice>;
+
+    fn extract_asset(&self) -> Self::ExtractedAsset {
+        self.clone()
+    }
+
+    fn prepare_asset(
+        extracted_asset: Self::ExtractedAsset,
+        render_device: &mut SystemParamItem<Self::Param>,
+    ) -> Result<Self::PreparedAsset, PrepareAssetError<Self::ExtractedAsset>> {
+        Ok(
+            render_device.create_buffer_with_data(&BufferInitDescriptor {
+                label: Some("buffer_asset"),
+                contents: &extracted_asset.data,
+                usage: extracted_asset.usages,
+            }),
+        )
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum BufferAssetLoaderError {
+    #[error("Cannot read the buffer asset: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Loads the whole file as the buffer content, e.g. a precomputed table.
+#[derive(Default)]
+pub struct BufferAssetLoader;
+
+impl AssetLoader for BufferAssetLoader {
+    type Asset = BufferAsset;
+    type Settings = ();
+    type Error = BufferAssetLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a Self::Settings,
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut data = Vec::new();
+            reader.read_to_end(&mut data).await?;
+            Ok(BufferAsset::new(data))
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["bin"]
+    }
+}
+
+/// Outputs the GPU buffer of the `BufferAsset`, requires `BufferAssetPlugin`.
+/// Any other asset prepared as a `Buffer` by its `RenderAssetPlugin` can be used as well with
+/// `from_asset`, e.g. the initial conditions of a simulation in a custom asset format.
+/// A small zero-initialized placeholder buffer is output until the asset is loaded and prepared,
+/// so the sub graph should be triggered with `is_prepared` condition. The buffer is marked as
+/// written only when the asset is uploaded again, e.g. after the hot reload.
+#[derive(Component)]
+pub struct AssetBufferNode<A: RenderAsset<PreparedAsset = Buffer> = BufferAsset> {
+    asset: Handle<A>,
+    uploaded: Arc<Mutex<Option<BufferId>>>,
+    placeholder: Arc<Mutex<Option<Buffer>>>,
+    not_prepared: LogOnce,
+}
+
+impl<A: RenderAsset<PreparedAsset = Buffer>> Clone for AssetBufferNode<A> {
+    fn clone(&self) -> Self {
+        Self {
+            asset: self.asset.clone(),
+            uploaded: self.uploaded.clone(),
+            placeholder: self.placeholder.clone(),
+            not_prepared: self.not_prepared.clone(),
+        }
+    }
+}
+
+impl<A: RenderAsset<PreparedAsset = Buffer>> std::fmt::Debug for AssetBufferNode<A> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AssetBufferNode")
+            .field("asset", &self.asset)
+            .field("uploaded", &self.uploaded)
+            .finish()
+    }
+}
+
+impl AssetBufferNode {
+    pub fn new(asset: Handle<BufferAsset>) -> Self {
+        Self::from_asset(asset)
+    }
+}
+
+impl<A: RenderAsset<PreparedAsset = Buffer>> AssetBufferNode<A> {
+    pub fn from_asset(asset: Handle<A>) -> Self {
+        Self {
+            asset,
+            uploaded: default(),
+            placeholder: default(),
+            not_prepared: default(),
+        }
+    }
+
+    pub fn asset(&self) -> &Handle<A> {
+        &self.asset
+    }
+
+    /// Returns true if the asset buffer is available in the Render World.
+    pub fn is_prepared(&self, world: &World) -> bool {
+        world
+            .get_resource::<RenderAssets<A>>()
+            .is_some_and(|assets| assets.get(&self.asset).is_some())
+    }
+}
+
+impl<A: RenderAsset<PreparedAsset = Buffer>> render_graph::Node for AssetBufferNode<A> {
+    fn output(&self) -> Vec<SlotInfo> {
+        vec![SlotInfo::new(SLOT_NAME, SlotType::Buffer)]
+    }
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let render_assets = world.resource::<RenderAssets<A>>();
+        let Some(buffer) = render_assets.get(&self.asset) else {
+            if self.not_prepared.first() {
+                warn!(
+                    "Buffer asset is not prepared, the placeholder is output: `{:?}`",
+                    &self.asset
+                );
+            }
+            let mut placeholder = self.placeholder.lock().unwrap();
+            let value = placeholder
+                .get_or_insert_with(|| fallback_buffer(render_context.render_device()))
+                .clone();
+            graph.set_output(SLOT_NAME, value)?;
+            return Ok(());
+        };
+        let value = SlotValue::Buffer(buffer.clone());
+        let mut uploaded = self.uploaded.lock().unwrap();
+        if *uploaded != Some(buffer.id()) {
+            debug!(
+                "Buffer asset `{:?}` is uploaded: `{:?}`",
+                &self.asset, buffer
+            );
+            *uploaded = Some(buffer.id());
+            WrittenResources::mark_in(world, &value);
+        }
+        graph.set_output(SLOT_NAME, value)?;
+        Ok(())
+    }
+}
+
+/// Outputs the depth texture view of the camera view.
+///
+/// The depth texture is resolved from the `ViewDepthTexture` of the view in the render world,
+/// so the camera must include `TextureUsages::TEXTURE_BINDING` in the depth texture usages.
+/// The view is selected by the camera entity, or the first found view is used if not defined.
+/// The sub graph should run after the camera driver node to get the depth of the current frame.
+/// A 1x1 depth texture placeholder is output while the view is not found, e.g. before the camera
+/// is extracted.
+pub struct ViewDepthTextureNode {
+    camera: Option<Entity>,
+    views: Option<QueryState<(Entity, &'static ViewDepthTexture)>>,
+    placeholder: Mutex<Option<TextureView>>,
+    not_found: LogOnce,
+}
+
+impl ViewDepthTextureNode {
+    pub fn new(camera: Option<Entity>) -> Self {
+        Self {
+            camera,
+            views: None,
+            placeholder: default(),
+            not_found: default(),
+        }
+    }
+
+    fn placeholder(&self, render_device: &RenderDevice) -> TextureView {
+        self.placeholder
+            .lock()
+            .unwrap()
+            .get_or_insert_with(|| {
+                render_device
+                    .create_texture(&TextureDescriptor {
+                        label: Some("view_depth_placeholder"),
+                        size: Extent3d::default(),
+                        mip_level_count: 1,
+                        sample_count: 1,
+                        dimension: TextureDimension::D2,
+                        format: CORE_3D_DEPTH_FORMAT,
+                        usage: TextureUsages::TEXTURE_BINDING | TextureUsages::RENDER_ATTACHMENT,
+                        view_formats: &[],
+                    })
+                    .create_view(&default())
+            })
+            .clone()
+    }
+}
+
+impl render_graph::Node for ViewDepthTextureNode {
+    fn output(&self) -> Vec<SlotInfo> {
+        vec![SlotInfo::new(SLOT_NAME, SlotType::TextureView)]
+    }
+
+    fn update(&mut self, world: &mut World) {
+        match &mut self.views {
+            Some(views) => views.update_archetypes(world),
+            None => self.views = Some(world.query()),
+        }
+    }
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let depth = self.views.as_ref().and_then(|views| {
+            views
+                .iter_manual(world)
+                .find(|(entity, _)| self.camera.is_none_or(|camera| camera == *entity))
+                .map(|(_, depth)| depth)
+        });
+        if let Some(depth) = depth {
+            debug!(
+                "Output `{}` set to view depth texture `{:?}`",
+                SLOT_NAME, &depth.view
+            );
+            graph.set_output(SLOT_NAME, SlotValue::TextureView(depth.view.clone()))?;
+        } else {
+            if self.not_found.first() {
+                warn!(
+                    "View depth texture not found for camera `{:?}`, the placeholder is output",
+                    self.camera
+                );
+            }
+            let placeholder = self.placeholder(render_context.render_device());
+            graph.set_output(SLOT_NAME, SlotValue::TextureView(placeholder))?;
+        }
+        Ok(())
+    }
+}
+
+/// Outputs the sampler created from the descriptor on the first run.
+#[derive(Component, Clone, Debug)]
+pub struct SamplerNode {
+    descriptor: SamplerDescriptor<'static>,
+    sampler: Arc<Mutex<Option<Sampler>>>,
+}
+
+impl SamplerNode {
+    pub fn new(descriptor: SamplerDescriptor<'static>) -> Self {
+        Self {
+            descriptor,
+            sampler: default(),
+        }
+    }
+}
+
+impl render_graph::Node for SamplerNode {
+    fn output(&self) -> Vec<SlotInfo> {
+        vec![SlotInfo::new(SLOT_NAME, SlotType::Sampler)]
+    }
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        _world: &World,
+    ) -> Result<(), NodeRunError> {
+        let sampler = self
+            .sampler
+            .lock()
+            .unwrap()
+            .get_or_insert_with(|| {
+                render_context
+                    .render_device()
+                    .create_sampler(&self.descriptor)
+            })
+            .clone();
+        graph.set_output(SLOT_NAME, SlotValue::Sampler(sampler))?;
+        Ok(())
+    }
+}
+
 #[derive(Clone, Debug)]
 enum InputTextureSource {
     Image(Handle<Image>),
 }
 
+/// Outputs the texture view of the image. Until the image is loaded and prepared, the view of
+/// the `FallbackImage` is published, use `is_prepared` to trigger the sub graph when
+/// the placeholder must not be processed.
 #[derive(Component, Clone, Debug)]
 pub struct InputTextureNode {
-    source: InputTextureSource,
+    source: Arc<Mutex<InputTextureSource>>,
+    not_prepared: LogOnce,
 }
 
 impl InputTextureNode {
     pub fn from_image(image: Handle<Image>) -> Self {
         Self {
-            source: InputTextureSource::Image(image),
+            source: Arc::new(Mutex::new(InputTextureSource::Image(image))),
+            not_prepared: default(),
         }
     }
+
+    /// Returns true if the source image is available in the Render World.
+    pub fn is_prepared(&self, world: &World) -> bool {
+        match &*self.source.lock().unwrap() {
+            InputTextureSource::Image(image) => world
+                .get_resource::<RenderAssets<Image>>()
+                .is_some_and(|assets| assets.get(image).is_some()),
+        }
+    }
+
+    /// Replaces the source image, the node instances in the render graph share the same source.
+    pub fn set_image(&self, image: Handle<Image>) {
+        *self.source.lock().unwrap() = InputTextureSource::Image(image);
+    }
 }
 
 impl render_graph::Node for InputTextureNode {
@@ -185,10 +928,10 @@ impl render_graph::Node for InputTextureNode {
     fn run(
         &self,
         graph: &mut RenderGraphContext,
-        _render_context: &mut RenderContext,
+        render_context: &mut RenderContext,
         world: &World,
     ) -> Result<(), NodeRunError> {
-        match &self.source {
+        match &*self.source.lock().unwrap() {
             InputTextureSource::Image(image) => {
                 let render_assets = world.resource::<RenderAssets<Image>>();
                 if let Some(prepared_image) = render_assets.get(image) {
@@ -201,7 +944,20 @@ impl render_graph::Node for InputTextureNode {
                         SlotValue::TextureView(prepared_image.texture_view.clone()),
                     )?;
                 } else {
-                    error!("Prepared GPU image not found: `{:?}`", image);
+                    if self.not_prepared.first() {
+                        warn!(
+                            "Prepared GPU image not found, the fallback image is output: `{:?}`",
+                            image
+                        );
+                    }
+                    graph.set_output(
+                        SLOT_NAME,
+                        fallback_slot_value(
+                            SlotType::TextureView,
+                            world,
+                            render_context.render_device(),
+                        ),
+                    )?;
                 }
             }
         }
@@ -0,0 +1,114 @@
+//! Node level memoization: a memoized node skips its run when the resources it receives are the
+//! same as in the last run and none of them was written earlier in the frame.
+//!
+//! Nodes of the crate mark the resources they write in [`WrittenResources`], so a change is
+//! propagated to all descendants of the re-run node. Nodes outside of the crate that write into
+//! resources consumed by memoized nodes must mark them as well, otherwise the stale results of
+//! the previous run are published.
+
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use bevy_render::render_graph::SlotValue;
+use bevy_render::render_resource::{BufferId, SamplerId, TextureViewId};
+use bevy_render::{Render, RenderApp, RenderSet};
+use std::sync::{Arc, Mutex};
+
+pub struct WrittenResourcesPlugin;
+
+impl Plugin for WrittenResourcesPlugin {
+    fn build(&self, _app: &mut App) {}
+
+    fn finish(&self, app: &mut App) {
+        let render_app = app
+            .get_sub_app_mut(RenderApp)
+            .expect("Cannot find Render Plugin");
+        render_app.init_resource::<WrittenResources>();
+        render_app.add_systems(
+            Render,
+            WrittenResources::clear_system.in_set(RenderSet::Cleanup),
+        );
+    }
+}
+
+/// Identity of the resource passed through a slot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SlotValueId {
+    Buffer(BufferId),
+    TextureView(TextureViewId),
+    Sampler(SamplerId),
+    Entity(Entity),
+}
+
+impl From<&SlotValue> for SlotValueId {
+    fn from(value: &SlotValue) -> Self {
+        match value {
+            SlotValue::Buffer(buffer) => SlotValueId::Buffer(buffer.id()),
+            SlotValue::TextureView(view) => SlotValueId::TextureView(view.id()),
+            SlotValue::Sampler(sampler) => SlotValueId::Sampler(sampler.id()),
+            SlotValue::Entity(entity) => SlotValueId::Entity(*entity),
+        }
+    }
+}
+
+/// Resources written by the nodes during the current frame, cleared after the frame is rendered.
+#[derive(Resource, Debug, Default)]
+pub struct WrittenResources(Mutex<HashSet<SlotValueId>>);
+
+impl WrittenResources {
+    pub fn mark(&self, id: impl Into<SlotValueId>) {
+        self.0
+            .lock()
+            .expect("Written resources mutex is poisoned")
+            .insert(id.into());
+    }
+
+    pub fn is_written(&self, id: &SlotValueId) -> bool {
+        self.0
+            .lock()
+            .expect("Written resources mutex is poisoned")
+            .contains(id)
+    }
+
+    /// Marks the value if the resource is available in the Render World.
+    pub(crate) fn mark_in(world: &World, value: &SlotValue) {
+        if let Some(written) = world.get_resource::<Self>() {
+            written.mark(value);
+        }
+    }
+
+    fn clear_system(written: Res<Self>) {
+        written
+            .0
+            .lock()
+            .expect("Written resources mutex is poisoned")
+            .clear();
+    }
+}
+
+/// State of the memoized node, shared by the clones of the node in the Render Graph.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct NodeMemo(Arc<Mutex<Option<MemoKey>>>);
+
+/// Everything that affects the results of the run, besides the content of the resources.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct MemoKey {
+    pub(crate) resources: Vec<SlotValueId>,
+    pub(crate) parameters: Vec<u32>,
+    pub(crate) push_constants: Option<Vec<u8>>,
+}
+
+impl NodeMemo {
+    /// Returns true if the key is equal to the key of the last run and none of the resources
+    /// was written in the current frame.
+    pub(crate) fn is_clean(&self, key: &MemoKey, world: &World) -> bool {
+        let Some(written) = world.get_resource::<WrittenResources>() else {
+            return false;
+        };
+        let last = self.0.lock().expect("Node memo mutex is poisoned");
+        last.as_ref() == Some(key) && !key.resources.iter().any(|id| written.is_written(id))
+    }
+
+    pub(crate) fn update(&self, key: MemoKey) {
+        *self.0.lock().expect("Node memo mutex is poisoned") = Some(key);
+    }
+}
@@ -0,0 +1,67 @@
+//! Ordering of the nodes that share a resource without a slot edge between them.
+//!
+//! wgpu tracks the usages of the resources and inserts the memory barriers between the compute
+//! passes, and between the dispatches of one pass, so the dependent dispatches are synchronized
+//! once they are encoded in the right order. Each compute node encodes its own pass, so the order
+//! of the nodes is the order of the Render Graph, which is defined only by the edges.
+//!
+//! The order is already defined when the resource flows through the slot edges, e.g. an output
+//! of one node connected to the input of the next one, or a chain of `InputOutput` bindings.
+//! It's undefined when several nodes take the same resource from one source node, e.g. the
+//! `PersistentBufferNode` or the `read` slot of the `PingPongBufferNode` connected to the writer
+//! and to the reader. Connect the source to the `OrderingNode` input, add the node edge
+//! from the writer to the ordering node and connect its output to the reader, so the reader
+//! runs after the writer and sees the written content. The node doesn't encode anything,
+//! the synchronization is left to wgpu.
+
+use bevy::log::debug;
+use bevy::prelude::*;
+use bevy_render::render_graph;
+use bevy_render::render_graph::{NodeRunError, RenderGraphContext, SlotInfo, SlotType};
+use bevy_render::renderer::RenderContext;
+
+pub const SLOT_NAME: &str = "in";
+pub const OUT_SLOT_NAME: &str = "out";
+
+/// Passes the input resource to the output after the nodes it depends on have run, see
+/// the [module documentation](self).
+#[derive(Clone, Debug)]
+pub struct OrderingNode {
+    slot_type: SlotType,
+}
+
+impl OrderingNode {
+    pub fn new(slot_type: SlotType) -> Self {
+        Self { slot_type }
+    }
+
+    pub fn buffer() -> Self {
+        Self::new(SlotType::Buffer)
+    }
+
+    pub fn texture_view() -> Self {
+        Self::new(SlotType::TextureView)
+    }
+}
+
+impl render_graph::Node for OrderingNode {
+    fn input(&self) -> Vec<SlotInfo> {
+        vec![SlotInfo::new(SLOT_NAME, self.slot_type)]
+    }
+
+    fn output(&self) -> Vec<SlotInfo> {
+        vec![SlotInfo::new(OUT_SLOT_NAME, self.slot_type)]
+    }
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        _render_context: &mut RenderContext,
+        _world: &World,
+    ) -> Result<(), NodeRunError> {
+        let value = graph.get_input(SLOT_NAME)?.clone();
+        debug!("Ordering node passes `{:?}`", &value);
+        graph.set_output(OUT_SLOT_NAME, value)?;
+        Ok(())
+    }
+}
@@ -1,23 +1,43 @@
-use bevy::log::debug;
+use crate::node::{assert_copy_range_aligned, LogOnce};
+use crate::resource::TextureViewSources;
+use bevy::log::{debug, warn};
 use bevy::prelude::*;
+use bevy::tasks::AsyncComputeTaskPool;
 use bevy::utils::thiserror::Error;
 use bevy_render::render_graph;
-use bevy_render::render_graph::{NodeRunError, RenderGraphContext, SlotInfo, SlotType};
-use bevy_render::render_resource::encase::internal::{CreateFrom, Reader};
+use bevy_render::render_graph::{
+    NodeRunError, RenderGraph, RenderGraphContext, SlotInfo, SlotType, SlotValue,
+};
+use bevy_render::render_resource::encase::internal::{CreateFrom, ReadFrom, Reader};
 use bevy_render::render_resource::{
-    encase, Buffer, BufferAddress, BufferDescriptor, BufferUsages, MapMode, ShaderType,
+    encase, Buffer, BufferAddress, BufferDescriptor, BufferUsages, Extent3d, ImageCopyBuffer,
+    ImageCopyTexture, ImageDataLayout, MapMode, Origin3d, ShaderType, TextureAspect,
+    TextureDimension, TextureFormat,
 };
 use bevy_render::renderer::{RenderContext, RenderDevice};
-use std::ops::{Deref, DerefMut, RangeFull};
+use bevy_render::settings::WgpuFeatures;
+use bytemuck::Pod;
+use std::borrow::Cow;
+use std::ops::{Deref, DerefMut, Range, RangeFull};
 use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
 
 pub const SLOT_NAME: &str = "in";
+/// Output slot with the read back buffer, defined only if the buffer has additional usages.
+pub const OUT_SLOT_NAME: &str = "out";
 
 pub struct OutputBufferPlugin;
 
 impl Plugin for OutputBufferPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(PreUpdate, OutputBuffer::map_output_buffers);
+        app.add_systems(
+            PreUpdate,
+            (
+                OutputBuffer::map_output_buffers,
+                MultiOutputBuffer::map_output_buffers,
+                OutputTexture::map_output_textures,
+            ),
+        );
     }
 }
 
@@ -31,6 +51,57 @@ pub enum OutputError {
     AsyncMapError,
     #[error("Buffer read-write error: {0}")]
     BufferReadWriteError(#[from] encase::internal::Error),
+    #[error("Buffer size {size} is not a multiple of the element size {element_size}")]
+    SizeMismatch { size: usize, element_size: usize },
+    #[error("Output slot `{0}` is not declared")]
+    SlotNotFound(String),
+}
+
+/// CPU side conversion of the read back bytes, for buffers packed on GPU in a layout that
+/// differs from the one expected by the reader.
+#[derive(Clone, Debug)]
+pub enum ReadbackConversion {
+    /// Reverses the byte order of every word with the given size.
+    SwapBytes {
+        word_size: usize,
+    },
+    /// Unpacks IEEE 754 half-precision floats into `f32` values.
+    F16ToF32,
+    Custom(fn(&[u8]) -> Vec<u8>),
+}
+
+impl ReadbackConversion {
+    pub fn convert(&self, bytes: &[u8]) -> Vec<u8> {
+        match self {
+            ReadbackConversion::SwapBytes { word_size } => bytes
+                .chunks(*word_size.max(&1))
+                .flat_map(|word| word.iter().rev().copied())
+                .collect(),
+            ReadbackConversion::F16ToF32 => bytes
+                .chunks_exact(2)
+                .flat_map(|half| f16_to_f32(u16::from_le_bytes([half[0], half[1]])).to_le_bytes())
+                .collect(),
+            ReadbackConversion::Custom(convert) => convert(bytes),
+        }
+    }
+}
+
+fn f16_to_f32(half: u16) -> f32 {
+    let sign = ((half >> 15) as u32) << 31;
+    let exponent = ((half >> 10) & 0x1f) as u32;
+    let mantissa = (half & 0x3ff) as u32;
+    let bits = match (exponent, mantissa) {
+        (0, 0) => sign,
+        (0, _) => {
+            // Subnormal half is normal in f32
+            let shift = mantissa.leading_zeros() - 21;
+            let mantissa = (mantissa << shift) & 0x3ff;
+            sign | ((127 - 15 + 1 - shift) << 23) | (mantissa << 13)
+        }
+        (0x1f, _) => sign | 0x7f80_0000 | (mantissa << 13),
+        _ => sign | ((exponent + 127 - 15) << 23) | (mantissa << 13),
+    };
+    f32::from_bits(bits)
 }
 
 #[derive(Default, Debug)]
@@ -43,12 +114,55 @@ enum OutputBufferState {
     MappingError,
 }
 
+/// Where the `on_mapped` callback of the output buffer is executed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MapCallbackExecution {
+    /// On the thread that polls the device, should be used only for cheap callbacks.
+    #[default]
+    Poller,
+    /// Handed off to the `AsyncComputeTaskPool`, keeps the poller thread responsive.
+    AsyncComputeTaskPool,
+}
+
 #[derive(Component, Clone, Debug, Default)]
 pub struct OutputBuffer {
     state: Arc<Mutex<OutputBufferState>>,
+    on_mapped: Option<fn(&OutputBuffer)>,
+    map_callback_execution: MapCallbackExecution,
+    range: Option<Range<BufferAddress>>,
+    additional_usages: Option<BufferUsages>,
+    wakers: Arc<Mutex<Vec<Waker>>>,
+    missing_copy_src: LogOnce,
 }
 
 impl OutputBuffer {
+    /// Adds usages to the read back buffer, so it can be used by the next nodes from the
+    /// `OUT_SLOT_NAME` output. Requires `MAPPABLE_PRIMARY_BUFFERS` device feature,
+    /// otherwise the sub graph with the node is not deployed.
+    pub fn with_usages(mut self, usages: BufferUsages) -> Self {
+        self.additional_usages = Some(usages);
+        self
+    }
+
+    /// Reads back only the range of the input buffer, the mapped data starts with the range.
+    /// Panics if the range bounds are not multiples of `COPY_BUFFER_ALIGNMENT`.
+    pub fn with_range(mut self, range: Range<BufferAddress>) -> Self {
+        assert_copy_range_aligned(&range);
+        self.range = Some(range);
+        self
+    }
+
+    /// Callback that is called once the buffer is mapped, usually to decode and notify.
+    pub fn with_on_mapped(
+        mut self,
+        on_mapped: fn(&OutputBuffer),
+        execution: MapCallbackExecution,
+    ) -> Self {
+        self.on_mapped = Some(on_mapped);
+        self.map_callback_execution = execution;
+        self
+    }
+
     pub fn take_buffer(&self) -> Result<Buffer, OutputError> {
         if let Ok(state) = self.state.try_lock().as_deref_mut() {
             if matches!(state, OutputBufferState::Mapped(_)) {
@@ -73,6 +187,122 @@ impl OutputBuffer {
         Ok(T::create_from(&mut reader))
     }
 
+    /// Same as `take_buffer_as`, but reads into the caller-owned value, so the allocated
+    /// capacity is reused by the steady read back loops.
+    pub fn take_buffer_into<T: ShaderType + ReadFrom>(
+        &self,
+        dst: &mut T,
+    ) -> Result<(), OutputError> {
+        let buffer = self.take_buffer()?;
+        let mapped_range = buffer.slice(RangeFull).get_mapped_range();
+        let mut reader = Reader::new::<T>(mapped_range.deref(), 0)?;
+        dst.read_from(&mut reader);
+        Ok(())
+    }
+
+    /// Reads the mapped bytes as a vector of plain values, e.g. the data uploaded by `BufferVecNode`.
+    pub fn take_pod_vec<T: Pod>(&self) -> Result<Vec<T>, OutputError> {
+        let buffer = self.take_buffer()?;
+        let mapped_range = buffer.slice(RangeFull).get_mapped_range();
+        pod_vec_from_bytes(mapped_range.deref())
+    }
+
+    /// Copies the raw mapped bytes into the caller-owned vector, keeping its capacity.
+    pub fn read_bytes_into(&self, dst: &mut Vec<u8>) -> Result<(), OutputError> {
+        let buffer = self.take_buffer()?;
+        let mapped_range = buffer.slice(RangeFull).get_mapped_range();
+        dst.clear();
+        dst.extend_from_slice(mapped_range.deref());
+        Ok(())
+    }
+
+    /// Reads the mapped buffer without taking it, so the state stays `Mapped` and the buffer
+    /// is reused by the next run of the node as usual. Repeated peeks return the same data
+    /// until the node copies the new content.
+    pub fn peek_buffer_as<T: ShaderType + CreateFrom>(&self) -> Result<T, OutputError> {
+        self.peek_mapped_bytes(|bytes| {
+            let mut reader = Reader::new::<T>(bytes, 0)?;
+            Ok(T::create_from(&mut reader))
+        })
+    }
+
+    /// Same as `take_pod_vec`, but leaves the buffer mapped, see `peek_buffer_as`.
+    pub fn peek_pod_vec<T: Pod>(&self) -> Result<Vec<T>, OutputError> {
+        self.peek_mapped_bytes(pod_vec_from_bytes)
+    }
+
+    /// Same as `read_bytes_into`, but leaves the buffer mapped, see `peek_buffer_as`.
+    pub fn peek_bytes_into(&self, dst: &mut Vec<u8>) -> Result<(), OutputError> {
+        self.peek_mapped_bytes(|bytes| {
+            dst.clear();
+            dst.extend_from_slice(bytes);
+            Ok(())
+        })
+    }
+
+    /// The state lock is held while the mapped range is read, and the range is dropped before
+    /// the lock is released, so the node cannot unmap the buffer during the read.
+    fn peek_mapped_bytes<R>(
+        &self,
+        read: impl FnOnce(&[u8]) -> Result<R, OutputError>,
+    ) -> Result<R, OutputError> {
+        let Ok(state) = self.state.try_lock() else {
+            return Err(OutputError::CannotLock);
+        };
+        let OutputBufferState::Mapped(buffer) = state.deref() else {
+            return Err(OutputError::MappedBufferNotFound);
+        };
+        let mapped_range = buffer.slice(RangeFull).get_mapped_range();
+        read(mapped_range.deref())
+    }
+
+    /// Same as `take_buffer_as`, but the mapped bytes are converted on CPU before reading.
+    pub fn take_buffer_as_converted<T: ShaderType + CreateFrom>(
+        &self,
+        conversion: &ReadbackConversion,
+    ) -> Result<T, OutputError> {
+        let buffer = self.take_buffer()?;
+        let mapped_range = buffer.slice(RangeFull).get_mapped_range();
+        let bytes = conversion.convert(mapped_range.deref());
+        let mut reader = Reader::new::<T>(bytes.as_slice(), 0)?;
+        Ok(T::create_from(&mut reader))
+    }
+
+    /// Completes once the buffer is mapped, the buffer stays in the node until it's taken.
+    /// Can be awaited by a task of the `AsyncComputeTaskPool` instead of polling `buffer_ready`.
+    pub async fn wait_mapped(&self) -> Result<(), OutputError> {
+        std::future::poll_fn(|cx| self.poll_mapped(cx)).await
+    }
+
+    fn poll_mapped(&self, cx: &mut Context<'_>) -> Poll<Result<(), OutputError>> {
+        let state = self
+            .state
+            .lock()
+            .expect("Output buffer state mutex is poisoned");
+        match state.deref() {
+            OutputBufferState::Mapped(_) => Poll::Ready(Ok(())),
+            OutputBufferState::MappingError => Poll::Ready(Err(OutputError::AsyncMapError)),
+            _ => {
+                // Registered under the state lock, so the map callback cannot be missed
+                let mut wakers = self.wakers.lock().expect("Output wakers mutex is poisoned");
+                if !wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+                    wakers.push(cx.waker().clone());
+                }
+                Poll::Pending
+            }
+        }
+    }
+
+    fn wake(&self) {
+        let wakers = std::mem::take(
+            self.wakers
+                .lock()
+                .expect("Output wakers mutex is poisoned")
+                .deref_mut(),
+        );
+        wakers.into_iter().for_each(Waker::wake);
+    }
+
     pub fn buffer_ready(&self) -> bool {
         self.state
             .try_lock()
@@ -80,11 +310,31 @@ impl OutputBuffer {
     }
 }
 
+fn pod_vec_from_bytes<T: Pod>(bytes: &[u8]) -> Result<Vec<T>, OutputError> {
+    let element_size = std::mem::size_of::<T>();
+    if element_size == 0 || !bytes.len().is_multiple_of(element_size) {
+        return Err(OutputError::SizeMismatch {
+            size: bytes.len(),
+            element_size,
+        });
+    }
+    // The mapped range is not guaranteed to be aligned for `T`
+    Ok(bytemuck::pod_collect_to_vec(bytes))
+}
+
 impl render_graph::Node for OutputBuffer {
     fn input(&self) -> Vec<SlotInfo> {
         vec![SlotInfo::new(SLOT_NAME, SlotType::Buffer)]
     }
 
+    fn output(&self) -> Vec<SlotInfo> {
+        if self.additional_usages.is_some() {
+            vec![SlotInfo::new(OUT_SLOT_NAME, SlotType::Buffer)]
+        } else {
+            vec![]
+        }
+    }
+
     fn run(
         &self,
         graph: &mut RenderGraphContext,
@@ -92,7 +342,35 @@ impl render_graph::Node for OutputBuffer {
         _world: &World,
     ) -> Result<(), NodeRunError> {
         let input = graph.get_input_buffer(SLOT_NAME)?;
-        let size = input.size();
+        if let Some(buffer) = self.copy_input(input, render_context) {
+            if self.additional_usages.is_some() {
+                graph.set_output(OUT_SLOT_NAME, SlotValue::Buffer(buffer))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl OutputBuffer {
+    /// Copies the input into the read back buffer, that is mapped by the next main world update.
+    fn copy_input(&self, input: &Buffer, render_context: &mut RenderContext) -> Option<Buffer> {
+        if !input.usage().contains(BufferUsages::COPY_SRC) {
+            if self.missing_copy_src.first() {
+                error!(
+                    "Output buffer input `{:?}` must have COPY_SRC usage to be read back, got {:?}",
+                    input,
+                    input.usage()
+                );
+            }
+            return None;
+        }
+        let (offset, size) = match &self.range {
+            Some(range) => {
+                let start = range.start.min(input.size());
+                (start, range.end.clamp(start, input.size()) - start)
+            }
+            None => (0, input.size()),
+        };
         let mut state = self
             .state
             .lock()
@@ -120,7 +398,7 @@ impl render_graph::Node for OutputBuffer {
         let buffer = if buffer.as_ref().is_some_and(|b| b.size() == size) {
             buffer.expect("Buffer must be checked for Some").clone()
         } else {
-            OutputBuffer::create_output_buffer(render_context.render_device(), size)
+            self.create_output_buffer(render_context.render_device(), size)
         };
 
         debug!(
@@ -129,50 +407,419 @@ impl render_graph::Node for OutputBuffer {
         );
         render_context
             .command_encoder()
-            .copy_buffer_to_buffer(input, 0, &buffer, 0, size);
-        *state = OutputBufferState::ReadyToMap(buffer);
-        Ok(())
+            .copy_buffer_to_buffer(input, offset, &buffer, 0, size);
+        *state = OutputBufferState::ReadyToMap(buffer.clone());
+        Some(buffer)
     }
-}
 
-impl OutputBuffer {
-    fn create_output_buffer(render_device: &RenderDevice, size: BufferAddress) -> Buffer {
+    fn create_output_buffer(&self, render_device: &RenderDevice, size: BufferAddress) -> Buffer {
+        // Additional usages are checked against the device features before the deployment
+        let usage = BufferUsages::COPY_DST
+            | BufferUsages::MAP_READ
+            | self.additional_usages.unwrap_or(BufferUsages::empty());
         render_device.create_buffer(&BufferDescriptor {
             label: "output_buffer".into(),
             size,
-            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            usage,
             mapped_at_creation: false,
         })
     }
 
+    fn validate_features(&self, features: WgpuFeatures) -> Result<(), String> {
+        match self.additional_usages {
+            Some(usages) if !features.contains(WgpuFeatures::MAPPABLE_PRIMARY_BUFFERS) => {
+                Err(format!(
+                    "Output buffer usages {:?} require MAPPABLE_PRIMARY_BUFFERS feature",
+                    usages
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
     pub(crate) fn map_output_buffers(query: Query<&Self>, render_device: Res<RenderDevice>) {
+        for output in query.iter() {
+            output.map(&render_device);
+        }
+    }
+
+    fn map(&self, render_device: &RenderDevice) {
+        let mut state_lock = self
+            .state
+            .lock()
+            .expect("Output buffer state mutex is poisoned");
+        let OutputBufferState::ReadyToMap(buffer) = state_lock.deref() else {
+            return;
+        };
+        let buffer = buffer.clone();
+        *state_lock.deref_mut() = OutputBufferState::WaitingForMap(buffer.clone());
+        render_device.map_buffer(&buffer.slice(RangeFull), MapMode::Read, {
+            let output = self.clone();
+            debug!("Waiting for map of the buffer `{:?}`", &buffer);
+            move |result| {
+                {
+                    let mut state = output
+                        .state
+                        .lock()
+                        .expect("Output buffer state mutex is poisoned");
+                    let OutputBufferState::WaitingForMap(buffer) =
+                        std::mem::replace(state.deref_mut(), OutputBufferState::NotCreated)
+                    else {
+                        return;
+                    };
+                    debug!("Buffer `{:?}` mapped with result `{:?}`", &buffer, &result);
+                    let new_state = result.map_or(OutputBufferState::MappingError, |_| {
+                        OutputBufferState::Mapped(buffer)
+                    });
+                    let _ = std::mem::replace(state.deref_mut(), new_state);
+                }
+                output.wake();
+                let Some(on_mapped) = output.on_mapped else {
+                    return;
+                };
+                match output.map_callback_execution {
+                    MapCallbackExecution::Poller => on_mapped(&output),
+                    MapCallbackExecution::AsyncComputeTaskPool => {
+                        AsyncComputeTaskPool::get()
+                            .spawn(async move { on_mapped(&output) })
+                            .detach();
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// Checks the output buffers of the graph and of its sub graphs against the device features.
+pub(crate) fn validate_output_buffers(
+    graph: &RenderGraph,
+    features: WgpuFeatures,
+) -> Result<(), String> {
+    for node in graph.iter_nodes() {
+        if let Ok(output) = node.node::<OutputBuffer>() {
+            output
+                .validate_features(features)
+                .map_err(|err| format!("Node {:?}: {}", node.name, err))?;
+        }
+    }
+    graph
+        .iter_sub_graphs()
+        .try_for_each(|(_, sub_graph)| validate_output_buffers(sub_graph, features))
+}
+
+/// Reads back several buffers with one node, each named input slot is copied into its own
+/// read back buffer and mapped independently, like with a separate `OutputBuffer` per slot.
+#[derive(Component, Clone, Debug, Default)]
+pub struct MultiOutputBuffer {
+    outputs: Vec<(Cow<'static, str>, OutputBuffer)>,
+}
+
+impl MultiOutputBuffer {
+    pub fn new<N: Into<Cow<'static, str>>>(names: impl IntoIterator<Item = N>) -> Self {
+        Self {
+            outputs: names
+                .into_iter()
+                .map(|name| (name.into(), OutputBuffer::default()))
+                .collect(),
+        }
+    }
+
+    /// Read back state of the input slot, `None` if the slot is not declared.
+    pub fn get(&self, name: &str) -> Option<&OutputBuffer> {
+        self.outputs
+            .iter()
+            .find(|(output_name, _)| output_name == name)
+            .map(|(_, output)| output)
+    }
+
+    pub fn take_buffer_as<T: ShaderType + CreateFrom>(&self, name: &str) -> Result<T, OutputError> {
+        self.get(name)
+            .ok_or_else(|| OutputError::SlotNotFound(name.to_string()))?
+            .take_buffer_as()
+    }
+
+    pub fn buffer_ready(&self, name: &str) -> bool {
+        self.get(name).is_some_and(OutputBuffer::buffer_ready)
+    }
+
+    /// Returns true if the buffers of all the slots are mapped.
+    pub fn buffers_ready(&self) -> bool {
+        self.outputs.iter().all(|(_, output)| output.buffer_ready())
+    }
+
+    pub(crate) fn map_output_buffers(query: Query<&Self>, render_device: Res<RenderDevice>) {
+        for (_, output) in query.iter().flat_map(|multi| &multi.outputs) {
+            output.map(&render_device);
+        }
+    }
+}
+
+impl render_graph::Node for MultiOutputBuffer {
+    fn input(&self) -> Vec<SlotInfo> {
+        self.outputs
+            .iter()
+            .map(|(name, _)| SlotInfo::new(name.clone(), SlotType::Buffer))
+            .collect()
+    }
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        _world: &World,
+    ) -> Result<(), NodeRunError> {
+        for (name, output) in &self.outputs {
+            let input = graph.get_input_buffer(name.clone())?;
+            output.copy_input(input, render_context);
+        }
+        Ok(())
+    }
+}
+
+/// Publishes the input buffer to the main world without the read back, so it can be bound by
+/// the main world consumers, e.g. a material with `#[storage(0, read_only, buffer)]` field.
+/// The source node should reuse its output buffer, like the `Output` bind resources do.
+#[derive(Component, Clone, Debug, Default)]
+pub struct SharedBufferNode {
+    buffer: Arc<Mutex<Option<Buffer>>>,
+}
+
+impl SharedBufferNode {
+    /// Buffer published by the last run, `None` until the sub graph is run.
+    pub fn buffer(&self) -> Option<Buffer> {
+        self.buffer
+            .lock()
+            .expect("Shared buffer mutex is poisoned")
+            .clone()
+    }
+}
+
+impl render_graph::Node for SharedBufferNode {
+    fn input(&self) -> Vec<SlotInfo> {
+        vec![SlotInfo::new(SLOT_NAME, SlotType::Buffer)]
+    }
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        _render_context: &mut RenderContext,
+        _world: &World,
+    ) -> Result<(), NodeRunError> {
+        let input = graph.get_input_buffer(SLOT_NAME)?;
+        let mut buffer = self.buffer.lock().expect("Shared buffer mutex is poisoned");
+        if buffer.as_ref().map(|buffer| buffer.id()) != Some(input.id()) {
+            debug!("Shared buffer is replaced by `{:?}`", input);
+            *buffer = Some(input.clone());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+struct TextureReadback {
+    buffer: Buffer,
+    size: Extent3d,
+    dimension: TextureDimension,
+    format: TextureFormat,
+    unpadded_bytes_per_row: u32,
+    padded_bytes_per_row: u32,
+}
+
+impl TextureReadback {
+    /// Copies the mapped rows without the padding required by `COPY_BYTES_PER_ROW_ALIGNMENT`.
+    fn unpadded_data(&self) -> Vec<u8> {
+        let mapped_range = self.buffer.slice(RangeFull).get_mapped_range();
+        if self.unpadded_bytes_per_row == self.padded_bytes_per_row {
+            // Rows are already aligned, so there is no padding to strip
+            return mapped_range.to_vec();
+        }
+        mapped_range
+            .chunks_exact(self.padded_bytes_per_row as usize)
+            .flat_map(|row| &row[..self.unpadded_bytes_per_row as usize])
+            .copied()
+            .collect()
+    }
+}
+
+#[derive(Default, Debug)]
+enum OutputTextureState {
+    #[default]
+    NotCreated,
+    ReadyToMap(TextureReadback),
+    WaitingForMap(TextureReadback),
+    Mapped(TextureReadback),
+    MappingError,
+}
+
+/// Reads back the texture of the view on the input slot to CPU, the whole first mip level is
+/// copied. The texture must have `COPY_SRC` usage and be either created by a node of the crate
+/// or a prepared image, see [`TextureViewSources`].
+#[derive(Component, Clone, Debug, Default)]
+pub struct OutputTexture {
+    state: Arc<Mutex<OutputTextureState>>,
+    unknown_texture: LogOnce,
+}
+
+impl OutputTexture {
+    pub fn take_image(&self) -> Result<Image, OutputError> {
+        let Ok(mut state) = self.state.try_lock() else {
+            return Err(OutputError::CannotLock);
+        };
+        let OutputTextureState::Mapped(readback) =
+            std::mem::replace(state.deref_mut(), OutputTextureState::NotCreated)
+        else {
+            return Err(OutputError::MappedBufferNotFound);
+        };
+        let data = readback.unpadded_data();
+        readback.buffer.unmap();
+        Ok(Image::new(
+            readback.size,
+            readback.dimension,
+            data,
+            readback.format,
+        ))
+    }
+
+    pub fn image_ready(&self) -> bool {
+        self.state
+            .try_lock()
+            .is_ok_and(|lock| matches!(lock.deref(), OutputTextureState::Mapped(_)))
+    }
+
+    pub(crate) fn map_output_textures(query: Query<&Self>, render_device: Res<RenderDevice>) {
         for output in query.iter() {
             let mut state_lock = output
                 .state
                 .lock()
-                .expect("Output buffer state mutex is poisoned");
-            let OutputBufferState::ReadyToMap(buffer) = state_lock.deref() else {
+                .expect("Output texture state mutex is poisoned");
+            let OutputTextureState::ReadyToMap(readback) = state_lock.deref() else {
                 continue;
             };
-            let buffer = buffer.clone();
-            *state_lock.deref_mut() = OutputBufferState::WaitingForMap(buffer.clone());
+            let readback = readback.clone();
+            let buffer = readback.buffer.clone();
+            *state_lock.deref_mut() = OutputTextureState::WaitingForMap(readback);
             render_device.map_buffer(&buffer.slice(RangeFull), MapMode::Read, {
                 let state = output.state.clone();
-                debug!("Waiting for map of the buffer `{:?}`", &buffer);
+                debug!("Waiting for map of the texture buffer `{:?}`", &buffer);
                 move |result| {
-                    let mut state = state.lock().expect("Output buffer state mutex is poisoned");
-                    let OutputBufferState::WaitingForMap(buffer) =
-                        std::mem::replace(state.deref_mut(), OutputBufferState::NotCreated)
+                    let mut state = state
+                        .lock()
+                        .expect("Output texture state mutex is poisoned");
+                    let OutputTextureState::WaitingForMap(readback) =
+                        std::mem::replace(state.deref_mut(), OutputTextureState::NotCreated)
                     else {
                         return;
                     };
-                    debug!("Buffer `{:?}` mapped with result `{:?}`", &buffer, &result);
-                    let new_state = result.map_or(OutputBufferState::MappingError, |_| {
-                        OutputBufferState::Mapped(buffer)
+                    debug!(
+                        "Texture buffer `{:?}` mapped with result `{:?}`",
+                        &readback.buffer, &result
+                    );
+                    *state = result.map_or(OutputTextureState::MappingError, |_| {
+                        OutputTextureState::Mapped(readback)
                     });
-                    let _ = std::mem::replace(state.deref_mut(), new_state);
                 }
             });
         }
     }
 }
+
+impl render_graph::Node for OutputTexture {
+    fn input(&self) -> Vec<SlotInfo> {
+        vec![SlotInfo::new(SLOT_NAME, SlotType::TextureView)]
+    }
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let view = graph.get_input_texture(SLOT_NAME)?;
+        let Some(texture) = TextureViewSources::find(world, view) else {
+            if self.unknown_texture.first() {
+                warn!(
+                    "Texture of the view `{:?}` is not found, skipping the readback",
+                    view.id()
+                );
+            }
+            return Ok(());
+        };
+        let texture = &texture;
+        let format = texture.format();
+        let size = texture.size();
+        let Some(bytes_per_block) = format.block_size(None) else {
+            warn!("Texture format `{:?}` cannot be read back", format);
+            return Ok(());
+        };
+        if format.block_dimensions() != (1, 1) {
+            warn!(
+                "Compressed texture format `{:?}` cannot be read back",
+                format
+            );
+            return Ok(());
+        }
+
+        let unpadded_bytes_per_row = size.width * bytes_per_block;
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(align) * align;
+        let buffer_size = padded_bytes_per_row as BufferAddress
+            * size.height as BufferAddress
+            * size.depth_or_array_layers as BufferAddress;
+
+        let mut state = self
+            .state
+            .lock()
+            .expect("Output texture state mutex is poisoned");
+        let buffer = match state.deref() {
+            OutputTextureState::Mapped(readback) => {
+                readback.buffer.unmap();
+                Some(readback.buffer.clone())
+            }
+            OutputTextureState::ReadyToMap(readback) => Some(readback.buffer.clone()),
+            OutputTextureState::WaitingForMap(_) => return Ok(()),
+            _ => None,
+        };
+        let buffer = match buffer {
+            Some(buffer) if buffer.size() == buffer_size => buffer,
+            _ => render_context
+                .render_device()
+                .create_buffer(&BufferDescriptor {
+                    label: "output_texture_buffer".into(),
+                    size: buffer_size,
+                    usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+                    mapped_at_creation: false,
+                }),
+        };
+
+        debug!(
+            "Copy texture to buffer command added to the queue from `{:?}` to `{:?}`",
+            texture, &buffer
+        );
+        render_context.command_encoder().copy_texture_to_buffer(
+            ImageCopyTexture {
+                texture,
+                mip_level: 0,
+                origin: Origin3d::ZERO,
+                aspect: TextureAspect::All,
+            },
+            ImageCopyBuffer {
+                buffer: &buffer,
+                layout: ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(size.height),
+                },
+            },
+            size,
+        );
+        *state = OutputTextureState::ReadyToMap(TextureReadback {
+            buffer,
+            size,
+            dimension: texture.dimension(),
+            format,
+            unpadded_bytes_per_row,
+            padded_bytes_per_row,
+        });
+        Ok(())
+    }
+}
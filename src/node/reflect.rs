@@ -0,0 +1,220 @@
+//! Reflection of the shader modules with naga. The shader is composed with its imports and
+//! the shader defs the same way as by the [`PipelineCache`](bevy_render::render_resource::PipelineCache),
+//! so the reflected module matches the compiled pipeline.
+
+use crate::node::{ReflectedBindingKind, ReflectedLayoutEntry, Workgroups};
+use bevy::prelude::*;
+use bevy::utils::thiserror;
+use bevy_render::render_resource::{ShaderDefVal, ShaderImport, ShaderStages, Source};
+use naga_oil::compose::{Composer, ComposerError, NagaModuleDescriptor, ShaderDefValue};
+use std::collections::HashMap;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ShaderReflectionError {
+    #[error("Shader {0:?} or one of its imports is not loaded")]
+    NotLoaded(AssetId<Shader>),
+    #[error("Only WGSL and GLSL shaders can be reflected")]
+    UnsupportedSource,
+    #[error("Cannot compose shader: {0}")]
+    Compose(String),
+    #[error("Invalid shader module: {0}")]
+    Validation(String),
+}
+
+/// Validated naga module of the shader, used to reflect the entry points and the bindings.
+#[derive(Debug)]
+pub struct ShaderReflection {
+    module: naga::Module,
+    info: naga::valid::ModuleInfo,
+}
+
+impl ShaderReflection {
+    /// Reflects the standalone WGSL source without imports and shader defs.
+    pub fn from_wgsl(wgsl: &str) -> Result<Self, ShaderReflectionError> {
+        let mut composer = Self::composer();
+        let module = composer
+            .make_naga_module(NagaModuleDescriptor {
+                source: wgsl,
+                ..default()
+            })
+            .map_err(|err| ShaderReflectionError::Compose(err.emit_to_string(&composer)))?;
+        Self::from_module(module)
+    }
+
+    /// Reflects the loaded shader asset, the imports are resolved from the other loaded shaders.
+    pub fn from_shader(
+        shaders: &Assets<Shader>,
+        shader: &Handle<Shader>,
+        shader_defs: &[ShaderDefVal],
+    ) -> Result<Self, ShaderReflectionError> {
+        let not_loaded = || ShaderReflectionError::NotLoaded(shader.id());
+        let source = shaders.get(shader).ok_or_else(not_loaded)?;
+        if matches!(source.source, Source::SpirV(_)) {
+            return Err(ShaderReflectionError::UnsupportedSource);
+        }
+        let import_paths: HashMap<&ShaderImport, &Shader> = shaders
+            .iter()
+            .map(|(_, shader)| (shader.import_path(), shader))
+            .collect();
+        let mut composer = Self::composer();
+        for import in source.imports() {
+            Self::add_import(&mut composer, &import_paths, import)
+                .ok_or_else(not_loaded)?
+                .map_err(|err| ShaderReflectionError::Compose(err.emit_to_string(&composer)))?;
+        }
+        let shader_defs = shader_defs
+            .iter()
+            .chain(&source.shader_defs)
+            .map(|def| match def.clone() {
+                ShaderDefVal::Bool(name, value) => (name, ShaderDefValue::Bool(value)),
+                ShaderDefVal::Int(name, value) => (name, ShaderDefValue::Int(value)),
+                ShaderDefVal::UInt(name, value) => (name, ShaderDefValue::UInt(value)),
+            })
+            .collect();
+        let module = composer
+            .make_naga_module(NagaModuleDescriptor {
+                shader_defs,
+                ..source.into()
+            })
+            .map_err(|err| ShaderReflectionError::Compose(err.emit_to_string(&composer)))?;
+        Self::from_module(module)
+    }
+
+    /// The `@workgroup_size` of the compute entry point, constants are evaluated by naga.
+    pub fn workgroup_size(&self, entry_point: &str) -> Option<Workgroups> {
+        let entry_point = self.entry_point(entry_point)?.1;
+        (entry_point.stage == naga::ShaderStage::Compute).then_some((
+            entry_point.workgroup_size[0],
+            entry_point.workgroup_size[1],
+            entry_point.workgroup_size[2],
+        ))
+    }
+
+    /// Resource bindings of the module sorted by group and binding. The visibility contains
+    /// the stages of the entry points that use the binding, it's empty for the unused bindings
+    /// that are not included in the layout derived by the pipeline.
+    pub fn layout_entries(&self) -> Vec<ReflectedLayoutEntry> {
+        let mut entries: Vec<_> = self
+            .module
+            .global_variables
+            .iter()
+            .filter_map(|(handle, variable)| {
+                let binding = variable.binding.as_ref()?;
+                let (kind, count) = self.binding_kind(variable.space, variable.ty)?;
+                let visibility = self
+                    .module
+                    .entry_points
+                    .iter()
+                    .enumerate()
+                    .filter(|(index, _)| !self.info.get_entry_point(*index)[handle].is_empty())
+                    .fold(ShaderStages::NONE, |visibility, (_, entry_point)| {
+                        visibility
+                            | match entry_point.stage {
+                                naga::ShaderStage::Vertex => ShaderStages::VERTEX,
+                                naga::ShaderStage::Fragment => ShaderStages::FRAGMENT,
+                                naga::ShaderStage::Compute => ShaderStages::COMPUTE,
+                            }
+                    });
+                Some(ReflectedLayoutEntry {
+                    group: binding.group,
+                    binding: binding.binding,
+                    name: variable.name.clone(),
+                    kind,
+                    visibility,
+                    count,
+                })
+            })
+            .collect();
+        entries.sort_by_key(|entry| (entry.group, entry.binding));
+        entries
+    }
+
+    /// Number of the bind groups in the pipeline layout derived for the entry point,
+    /// one more than the highest group used by the entry point.
+    pub fn bind_group_count(&self, entry_point: &str) -> Option<u32> {
+        let (index, _) = self.entry_point(entry_point)?;
+        let info = self.info.get_entry_point(index);
+        Some(
+            self.module
+                .global_variables
+                .iter()
+                .filter(|(handle, _)| !info[*handle].is_empty())
+                .filter_map(|(_, variable)| variable.binding.as_ref())
+                .map(|binding| binding.group + 1)
+                .max()
+                .unwrap_or(0),
+        )
+    }
+
+    fn binding_kind(
+        &self,
+        space: naga::AddressSpace,
+        ty: naga::Handle<naga::Type>,
+    ) -> Option<(ReflectedBindingKind, Option<u32>)> {
+        let kind = match (space, &self.module.types[ty].inner) {
+            (naga::AddressSpace::Uniform, _) => ReflectedBindingKind::UniformBuffer,
+            (naga::AddressSpace::Storage { access }, _) => ReflectedBindingKind::StorageBuffer {
+                read_only: !access.contains(naga::StorageAccess::STORE),
+            },
+            (_, naga::TypeInner::BindingArray { base, size }) => {
+                let count = match size {
+                    naga::ArraySize::Constant(count) => Some(count.get()),
+                    naga::ArraySize::Dynamic => None,
+                };
+                return Some((self.binding_kind(space, *base)?.0, count));
+            }
+            (
+                _,
+                naga::TypeInner::Image {
+                    class: naga::ImageClass::Storage { .. },
+                    ..
+                },
+            ) => ReflectedBindingKind::StorageTexture,
+            (_, naga::TypeInner::Image { .. }) => ReflectedBindingKind::Texture,
+            (_, naga::TypeInner::Sampler { .. }) => ReflectedBindingKind::Sampler,
+            _ => return None,
+        };
+        Some((kind, None))
+    }
+
+    fn entry_point(&self, name: &str) -> Option<(usize, &naga::EntryPoint)> {
+        self.module
+            .entry_points
+            .iter()
+            .enumerate()
+            .find(|(_, entry_point)| entry_point.name == name)
+    }
+
+    fn from_module(module: naga::Module) -> Result<Self, ShaderReflectionError> {
+        let info = naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::all(),
+        )
+        .validate(&module)
+        .map_err(|err| ShaderReflectionError::Validation(err.into_inner().to_string()))?;
+        Ok(Self { module, info })
+    }
+
+    fn composer() -> Composer {
+        Composer::non_validating().with_capabilities(naga::valid::Capabilities::all())
+    }
+
+    /// Returns `None` if the import or one of its own imports is not loaded.
+    fn add_import(
+        composer: &mut Composer,
+        import_paths: &HashMap<&ShaderImport, &Shader>,
+        import: &ShaderImport,
+    ) -> Option<Result<(), ComposerError>> {
+        if composer.contains_module(&import.module_name()) {
+            return Some(Ok(()));
+        }
+        let shader = import_paths.get(import)?;
+        for import in shader.imports() {
+            if let Err(err) = Self::add_import(composer, import_paths, import)? {
+                return Some(Err(err));
+            }
+        }
+        Some(composer.add_composable_module((*shader).into()).map(|_| ()))
+    }
+}
@@ -0,0 +1,288 @@
+use crate::diagnostic::NodeMemoryUsage;
+use crate::graph::ProviderState;
+use crate::node::memo::WrittenResources;
+use crate::node::{
+    add_or_replace_graph_node, pipeline_bind_group_layouts, DummyNode, NodeSwitch,
+    ReflectedBindGroupCount,
+};
+use crate::resource::{
+    BindResourceCreationInfo, BindResourceError, BindResourceSizes, NodeResources,
+};
+use crate::{MainWorldEntity, NodeProvider};
+use bevy::ecs::query::QueryItem;
+use bevy::log::debug;
+use bevy::prelude::*;
+use bevy_render::extract_component::ExtractComponent;
+use bevy_render::render_graph::{SlotInfo, SlotType};
+use bevy_render::render_resource::PipelineCache;
+use bevy_render::renderer::RenderContext;
+use bevy_render::{render_graph, render_resource};
+use std::any::type_name;
+use std::borrow::Cow;
+
+/// Graphics pipeline node that draws `vertex_count` vertices without vertex buffers into the
+/// color target, e.g. a fullscreen triangle. The target texture view is received from the input
+/// slot with `target_slot` name and published to the output slot with the same name.
+/// Like `ComputeNode`, any change of the component recompiles the pipeline.
+#[derive(Component, Clone, Debug)]
+pub struct RenderNode {
+    pub label: Option<Cow<'static, str>>,
+    /// Human-readable name used for the render pass, bind group and debug group labels.
+    pub debug_name: Option<Cow<'static, str>>,
+    pub pipeline_descriptor: render_resource::RenderPipelineDescriptor,
+    pub binding_resource_info: Vec<BindResourceCreationInfo>,
+    pub target_slot: Cow<'static, str>,
+    /// Target is cleared with the color before drawing, otherwise the content is loaded.
+    pub clear_color: Option<Color>,
+    pub vertex_count: u32,
+
+    pub(crate) bind_resource_sizes: BindResourceSizes,
+    pub(crate) bind_group_count: ReflectedBindGroupCount,
+    pub(crate) switch: NodeSwitch,
+    pub(crate) state: RenderNodeState,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum RenderNodeState {
+    Creating,
+    PipelineQueued {
+        pipeline_id: render_resource::CachedRenderPipelineId,
+    },
+    ReadyToRun {
+        node: Box<RenderNodeImpl>,
+    },
+    Err(String),
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct RenderNodeImpl {
+    debug_name: Cow<'static, str>,
+    layouts: Vec<(u32, render_resource::BindGroupLayout)>,
+    pipeline: render_resource::RenderPipeline,
+    bind_resources: NodeResources,
+    input_slots: Vec<SlotInfo>,
+    output_slots: Vec<SlotInfo>,
+    target_slot: Cow<'static, str>,
+    clear_color: Option<Color>,
+    vertex_count: u32,
+    switch: NodeSwitch,
+}
+
+impl render_graph::Node for RenderNodeImpl {
+    fn input(&self) -> Vec<SlotInfo> {
+        self.input_slots.clone()
+    }
+
+    fn output(&self) -> Vec<SlotInfo> {
+        self.output_slots.clone()
+    }
+
+    fn run(
+        &self,
+        graph: &mut render_graph::RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), render_graph::NodeRunError> {
+        let render_device = render_context.render_device().clone();
+        let node_error = |err: BindResourceError| err.into_node_run_error(&self.debug_name);
+        let target = graph.get_input_texture(self.target_slot.clone())?.clone();
+        graph.set_output(
+            self.target_slot.clone(),
+            render_graph::SlotValue::TextureView(target.clone()),
+        )?;
+        if !self.switch.is_enabled() {
+            debug!(
+                "Render node {:?} is disabled, publishing previous outputs",
+                &self.debug_name
+            );
+            self.bind_resources
+                .set_previous_output_slots(graph, &render_device, world)
+                .map_err(node_error)?;
+            return Ok(());
+        }
+        self.bind_resources
+            .release_transient_resources()
+            .map_err(node_error)?;
+        let bind_groups = self
+            .bind_resources
+            .set_bind_groups(&render_device, graph, &self.layouts, Some(&self.debug_name))
+            .map_err(node_error)?;
+        let allocated_bytes = self.bind_resources.allocated_bytes().map_err(node_error)?;
+        NodeMemoryUsage::record_in(world, self, allocated_bytes);
+        self.bind_resources
+            .set_output_slots(graph, &render_device, world)
+            .map_err(node_error)?;
+        let buffers_to_clear = self
+            .bind_resources
+            .buffers_to_clear(graph, &render_device)
+            .map_err(node_error)?;
+
+        let load = match self.clear_color {
+            Some(color) => render_resource::LoadOp::Clear(color.into()),
+            None => render_resource::LoadOp::Load,
+        };
+        let command_encoder = render_context.command_encoder();
+        command_encoder.push_debug_group(&self.debug_name);
+        for buffer in &buffers_to_clear {
+            command_encoder.clear_buffer(buffer, 0, None);
+        }
+        {
+            let mut pass =
+                command_encoder.begin_render_pass(&render_resource::RenderPassDescriptor {
+                    label: Some(&self.debug_name),
+                    color_attachments: &[Some(render_resource::RenderPassColorAttachment {
+                        view: &target,
+                        resolve_target: None,
+                        ops: render_resource::Operations { load, store: true },
+                    })],
+                    depth_stencil_attachment: None,
+                });
+
+            for (group, bind_group) in &bind_groups {
+                let offsets = self.bind_resources.dynamic_offsets(graph, *group);
+                pass.set_bind_group(*group, bind_group, &offsets);
+            }
+            pass.set_pipeline(&self.pipeline);
+            pass.draw(0..self.vertex_count, 0..1);
+
+            debug!(
+                "Drawn Render pass {:?} with {} vertices",
+                &self.debug_name, self.vertex_count
+            );
+        }
+        command_encoder.pop_debug_group();
+        WrittenResources::mark_in(world, &render_graph::SlotValue::TextureView(target));
+        self.bind_resources
+            .mark_written(graph, world)
+            .map_err(node_error)?;
+        Ok(())
+    }
+}
+
+impl RenderNode {
+    /// Sizes of the buffers bound during the last run of the node.
+    pub fn bind_resource_sizes(&self) -> &BindResourceSizes {
+        &self.bind_resource_sizes
+    }
+
+    /// Switch that allows to skip the node runs without recreating the pipeline.
+    pub fn switch(&self) -> &NodeSwitch {
+        &self.switch
+    }
+
+    /// Debug name, or the label, or the type name of the node if neither is defined.
+    pub fn debug_name(&self) -> Cow<'static, str> {
+        self.debug_name
+            .clone()
+            .or_else(|| self.label.clone())
+            .unwrap_or(Cow::Borrowed(type_name::<RenderNodeImpl>()))
+    }
+
+    fn slots(&self) -> (Vec<SlotInfo>, Vec<SlotInfo>) {
+        let (mut input_slots, mut output_slots) =
+            BindResourceCreationInfo::input_output_slot_info(&self.binding_resource_info);
+        let target = SlotInfo::new(self.target_slot.clone(), SlotType::TextureView);
+        input_slots.push(target.clone());
+        output_slots.push(target);
+        (input_slots, output_slots)
+    }
+}
+
+impl NodeProvider for RenderNode {
+    fn update(&mut self, world: &mut World) {
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let new_state = match &self.state {
+            RenderNodeState::Creating => RenderNodeState::PipelineQueued {
+                pipeline_id: pipeline_cache.queue_render_pipeline(self.pipeline_descriptor.clone()),
+            },
+            RenderNodeState::PipelineQueued { pipeline_id } => {
+                match pipeline_cache.get_render_pipeline_state(*pipeline_id) {
+                    render_resource::CachedPipelineState::Ok(
+                        render_resource::Pipeline::RenderPipeline(pipeline),
+                    ) => {
+                        let Some(layouts) = pipeline_bind_group_layouts(
+                            &self.binding_resource_info,
+                            &self.pipeline_descriptor.layout,
+                            |index| pipeline.get_bind_group_layout(index).into(),
+                            &self.bind_group_count,
+                        ) else {
+                            return;
+                        };
+                        match layouts {
+                            Ok(layouts) => {
+                                let (input_slots, output_slots) = self.slots();
+                                RenderNodeState::ReadyToRun {
+                                    node: Box::new(RenderNodeImpl {
+                                        debug_name: self.debug_name(),
+                                        layouts,
+                                        pipeline: pipeline.clone(),
+                                        bind_resources: NodeResources::from_bind_resource_info(
+                                            self.binding_resource_info.clone(),
+                                            self.bind_resource_sizes.clone(),
+                                        ),
+                                        input_slots,
+                                        output_slots,
+                                        target_slot: self.target_slot.clone(),
+                                        clear_color: self.clear_color,
+                                        vertex_count: self.vertex_count,
+                                        switch: self.switch.clone(),
+                                    }),
+                                }
+                            }
+                            Err(err) => RenderNodeState::Err(err),
+                        }
+                    }
+                    render_resource::CachedPipelineState::Err(err) => {
+                        RenderNodeState::Err(err.to_string())
+                    }
+                    _ => {
+                        return;
+                    }
+                }
+            }
+            _ => {
+                return;
+            }
+        };
+        debug!("Render node state after update: {:?}", &new_state);
+        self.state = new_state;
+    }
+
+    fn state(&self) -> ProviderState {
+        match &self.state {
+            RenderNodeState::ReadyToRun { .. } => ProviderState::CanCreateNode,
+            RenderNodeState::Err(s) => ProviderState::Err(s.clone()),
+            _ => ProviderState::Updating,
+        }
+    }
+
+    fn add_node_to_graph(
+        &self,
+        graph: &mut render_graph::RenderGraph,
+        node_name: Cow<'static, str>,
+    ) {
+        match &self.state {
+            RenderNodeState::ReadyToRun { node } => {
+                let node = node.as_ref().clone();
+                debug!("Added node impl: {:?} {:?}", &node_name, &node);
+                add_or_replace_graph_node(graph, node_name, node);
+            }
+            _ => {
+                let (input, output) = self.slots();
+                let node = DummyNode { input, output };
+                debug!("Added dummy node: {:?} {:?}", &node_name, &node);
+                add_or_replace_graph_node(graph, node_name, node);
+            }
+        };
+    }
+}
+
+impl ExtractComponent for RenderNode {
+    type Query = (&'static Self, Entity);
+    type Filter = Changed<Self>;
+    type Out = (Self, MainWorldEntity);
+
+    fn extract_component(item: QueryItem<'_, Self::Query>) -> Option<Self::Out> {
+        Some((item.0.clone(), MainWorldEntity(item.1)))
+    }
+}
@@ -1,17 +1,143 @@
+use crate::node::memo::{SlotValueId, WrittenResources};
 use bevy::prelude::*;
 use bevy::utils::HashMap;
-use bevy_render::render_graph::OutputSlotError;
-use bevy_render::render_resource::TextureViewDescriptor;
+use bevy_render::render_asset::RenderAssets;
+use bevy_render::render_graph::{InputSlotError, NodeRunError, OutputSlotError};
+use bevy_render::render_resource::{
+    BufferAddress, StorageTextureAccess, TextureDimension, TextureViewDescriptor,
+    TextureViewDimension,
+};
 use bevy_render::renderer::RenderDevice;
-use bevy_render::{render_graph, render_resource};
+use bevy_render::{render_graph, render_resource, Render, RenderApp, RenderSet};
 use std::borrow::Cow;
 use std::fmt::Debug;
-use std::sync::{Arc, Mutex};
+use std::num::NonZeroU32;
+use std::ops::Deref;
+use std::sync::{Arc, Mutex, MutexGuard};
+use thiserror::Error;
+
+/// Function evaluated against the graph context of the node run, it may capture its environment.
+/// Functions are compared by the pointer, so only the clones of the same function are equal.
+pub struct GraphContextFn<T>(pub Arc<dyn Fn(&render_graph::RenderGraphContext) -> T + Send + Sync>);
+
+impl<T> GraphContextFn<T> {
+    pub fn new(f: impl Fn(&render_graph::RenderGraphContext) -> T + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+
+    pub fn call(&self, graph_context: &render_graph::RenderGraphContext) -> T {
+        (self.0)(graph_context)
+    }
+}
+
+impl<T> Clone for GraphContextFn<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Debug for GraphContextFn<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("GraphContextFn")
+            .field(&Arc::as_ptr(&self.0))
+            .finish()
+    }
+}
+
+impl<T> PartialEq for GraphContextFn<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl<T> Eq for GraphContextFn<T> {}
+
+impl<T> std::hash::Hash for GraphContextFn<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.0).cast::<()>().hash(state);
+    }
+}
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum BindResourceCreationStrategy<T: Clone + Debug + PartialEq> {
     Static(T),
-    FromGraphContext(fn(&render_graph::RenderGraphContext) -> T),
+    FromGraphContext(GraphContextFn<T>),
+    /// Evaluated only once on the first run, the created resource is reused after that.
+    Deferred(GraphContextFn<T>),
+}
+
+impl<T: Clone + Debug + PartialEq> BindResourceCreationStrategy<T> {
+    pub fn from_graph_context(
+        f: impl Fn(&render_graph::RenderGraphContext) -> T + Send + Sync + 'static,
+    ) -> Self {
+        Self::FromGraphContext(GraphContextFn::new(f))
+    }
+
+    fn resolve(self, graph_context: &render_graph::RenderGraphContext) -> T {
+        match self {
+            BindResourceCreationStrategy::Static(s) => s,
+            BindResourceCreationStrategy::FromGraphContext(f)
+            | BindResourceCreationStrategy::Deferred(f) => f.call(graph_context),
+        }
+    }
+
+    fn is_deferred(&self) -> bool {
+        matches!(self, BindResourceCreationStrategy::Deferred(_))
+    }
+}
+
+impl<T: Clone + Debug + PartialEq + 'static> BindResourceCreationStrategy<T> {
+    /// Applies `extend` to the static descriptor, returns its result: whether the descriptor
+    /// is changed. Descriptors from the graph context are extended on each evaluation.
+    fn extend(&mut self, extend: impl Fn(&mut T) -> bool + Send + Sync + 'static) -> bool {
+        match self {
+            BindResourceCreationStrategy::Static(descriptor) => extend(descriptor),
+            BindResourceCreationStrategy::FromGraphContext(f)
+            | BindResourceCreationStrategy::Deferred(f) => {
+                let inner = f.clone();
+                *f = GraphContextFn::new(move |graph_context| {
+                    let mut descriptor = inner.call(graph_context);
+                    extend(&mut descriptor);
+                    descriptor
+                });
+                true
+            }
+        }
+    }
+}
+
+impl BindResourceCreationStrategy<render_resource::BufferDescriptor<'static>> {
+    /// Adds the usages to the created buffer, returns `false` if the static descriptor
+    /// already has them. Descriptors from the graph context are extended on each evaluation.
+    pub(crate) fn add_usages(&mut self, usages: render_resource::BufferUsages) -> bool {
+        self.extend(move |descriptor| {
+            let missing = !descriptor.usage.contains(usages);
+            descriptor.usage |= usages;
+            missing
+        })
+    }
+}
+
+impl BindResourceCreationStrategy<render_resource::TextureDescriptor<'static>> {
+    /// Adds the usages to the created texture, see the buffer `add_usages`.
+    pub(crate) fn add_usages(&mut self, usages: render_resource::TextureUsages) -> bool {
+        self.extend(move |descriptor| {
+            let missing = !descriptor.usage.contains(usages);
+            descriptor.usage |= usages;
+            missing
+        })
+    }
+}
+
+/// Texture view that is not managed by the crate, e.g. a render target of another render system.
+/// The user is responsible for keeping the view valid while it is bound to the node.
+#[derive(Clone, Debug)]
+pub struct ExternalTextureView(pub render_resource::TextureView);
+
+impl PartialEq for ExternalTextureView {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.id() == other.0.id()
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -19,6 +145,18 @@ pub enum BindResourceCreationDescriptor {
     Buffer(BindResourceCreationStrategy<render_resource::BufferDescriptor<'static>>),
     Sampler(BindResourceCreationStrategy<render_resource::SamplerDescriptor<'static>>),
     Texture(BindResourceCreationStrategy<render_resource::TextureDescriptor<'static>>),
+    /// Texture bound with the view created from the descriptor, the access and the view format
+    /// must match the storage texture declaration in the shader.
+    StorageTexture {
+        texture: BindResourceCreationStrategy<render_resource::TextureDescriptor<'static>>,
+        view: TextureViewDescriptor<'static>,
+        access: StorageTextureAccess,
+    },
+    /// Sampled textures bound as `binding_array<texture_2d<f32>>`, one texture is created for
+    /// each descriptor. Requires `TEXTURE_BINDING_ARRAY` device feature. The output slot holds
+    /// the view of the first texture, the graph slots cannot hold arrays.
+    TextureArray(Vec<BindResourceCreationStrategy<render_resource::TextureDescriptor<'static>>>),
+    ExternalTextureView(ExternalTextureView),
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -26,6 +164,12 @@ pub enum StaticBindResourceCreationDescriptor {
     Buffer(render_resource::BufferDescriptor<'static>),
     Sampler(render_resource::SamplerDescriptor<'static>),
     Texture(render_resource::TextureDescriptor<'static>),
+    StorageTexture(
+        render_resource::TextureDescriptor<'static>,
+        TextureViewDescriptor<'static>,
+    ),
+    TextureArray(Vec<render_resource::TextureDescriptor<'static>>),
+    ExternalTextureView(ExternalTextureView),
 }
 
 impl StaticBindResourceCreationDescriptor {
@@ -42,6 +186,26 @@ impl StaticBindResourceCreationDescriptor {
                 let default_view = texture.create_view(&TextureViewDescriptor::default());
                 OwnBindResource::Texture(texture, default_view)
             }
+            StaticBindResourceCreationDescriptor::StorageTexture(texture_descriptor, view) => {
+                let texture = render_device.create_texture(texture_descriptor);
+                let view = texture.create_view(view);
+                OwnBindResource::Texture(texture, view)
+            }
+            StaticBindResourceCreationDescriptor::TextureArray(texture_descriptors) => {
+                OwnBindResource::TextureArray(
+                    texture_descriptors
+                        .iter()
+                        .map(|texture_descriptor| {
+                            let texture = render_device.create_texture(texture_descriptor);
+                            let view = texture.create_view(&TextureViewDescriptor::default());
+                            (texture, view)
+                        })
+                        .collect(),
+                )
+            }
+            StaticBindResourceCreationDescriptor::ExternalTextureView(view) => {
+                OwnBindResource::TextureView(view.0.clone())
+            }
         }
     }
 }
@@ -53,23 +217,44 @@ impl BindResourceCreationDescriptor {
     ) -> StaticBindResourceCreationDescriptor {
         match self {
             BindResourceCreationDescriptor::Buffer(b) => {
-                StaticBindResourceCreationDescriptor::Buffer(match b {
-                    BindResourceCreationStrategy::Static(s) => s,
-                    BindResourceCreationStrategy::FromGraphContext(f) => f(graph_context),
-                })
+                StaticBindResourceCreationDescriptor::Buffer(b.resolve(graph_context))
             }
             BindResourceCreationDescriptor::Sampler(s) => {
-                StaticBindResourceCreationDescriptor::Sampler(match s {
-                    BindResourceCreationStrategy::Static(s) => s,
-                    BindResourceCreationStrategy::FromGraphContext(f) => f(graph_context),
-                })
+                StaticBindResourceCreationDescriptor::Sampler(s.resolve(graph_context))
             }
             BindResourceCreationDescriptor::Texture(t) => {
-                StaticBindResourceCreationDescriptor::Texture(match t {
-                    BindResourceCreationStrategy::Static(s) => s,
-                    BindResourceCreationStrategy::FromGraphContext(f) => f(graph_context),
-                })
+                StaticBindResourceCreationDescriptor::Texture(t.resolve(graph_context))
+            }
+            BindResourceCreationDescriptor::StorageTexture { texture, view, .. } => {
+                StaticBindResourceCreationDescriptor::StorageTexture(
+                    texture.resolve(graph_context),
+                    view,
+                )
             }
+            BindResourceCreationDescriptor::TextureArray(textures) => {
+                StaticBindResourceCreationDescriptor::TextureArray(
+                    textures
+                        .into_iter()
+                        .map(|texture| texture.resolve(graph_context))
+                        .collect(),
+                )
+            }
+            BindResourceCreationDescriptor::ExternalTextureView(view) => {
+                StaticBindResourceCreationDescriptor::ExternalTextureView(view)
+            }
+        }
+    }
+
+    pub(crate) fn is_deferred(&self) -> bool {
+        match self {
+            BindResourceCreationDescriptor::Buffer(b) => b.is_deferred(),
+            BindResourceCreationDescriptor::Sampler(s) => s.is_deferred(),
+            BindResourceCreationDescriptor::Texture(t)
+            | BindResourceCreationDescriptor::StorageTexture { texture: t, .. } => t.is_deferred(),
+            BindResourceCreationDescriptor::TextureArray(textures) => textures
+                .iter()
+                .any(BindResourceCreationStrategy::is_deferred),
+            BindResourceCreationDescriptor::ExternalTextureView(_) => false,
         }
     }
 
@@ -77,9 +262,116 @@ impl BindResourceCreationDescriptor {
         match self {
             BindResourceCreationDescriptor::Buffer(_) => render_graph::SlotType::Buffer,
             BindResourceCreationDescriptor::Sampler(_) => render_graph::SlotType::Sampler,
-            BindResourceCreationDescriptor::Texture(_) => render_graph::SlotType::TextureView,
+            BindResourceCreationDescriptor::Texture(_)
+            | BindResourceCreationDescriptor::StorageTexture { .. }
+            | BindResourceCreationDescriptor::TextureArray(_)
+            | BindResourceCreationDescriptor::ExternalTextureView(_) => {
+                render_graph::SlotType::TextureView
+            }
+        }
+    }
+}
+
+/// Layout of the arguments consumed by indirect draw and dispatch commands.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IndirectArgs {
+    /// `vertex_count, instance_count, first_vertex, first_instance`
+    Draw,
+    /// `index_count, instance_count, first_index, base_vertex, first_instance`
+    DrawIndexed,
+    /// `x, y, z` workgroups
+    Dispatch,
+}
+
+impl IndirectArgs {
+    pub fn size(&self) -> BufferAddress {
+        let words = match self {
+            IndirectArgs::Draw => 4,
+            IndirectArgs::DrawIndexed => 5,
+            IndirectArgs::Dispatch => 3,
+        };
+        words * std::mem::size_of::<u32>() as BufferAddress
+    }
+}
+
+pub struct TextureViewSourcesPlugin;
+
+impl Plugin for TextureViewSourcesPlugin {
+    fn build(&self, _app: &mut App) {}
+
+    fn finish(&self, app: &mut App) {
+        let render_app = app
+            .get_sub_app_mut(RenderApp)
+            .expect("Cannot find Render Plugin");
+        render_app.init_resource::<TextureViewSources>();
+        render_app.add_systems(
+            Render,
+            TextureViewSources::clear_system.in_set(RenderSet::Cleanup),
+        );
+    }
+}
+
+/// Textures of the views published by the nodes of the crate in the current frame, cleared after
+/// the frame is rendered. The slots pass only the views, so the nodes that copy or read back
+/// the input texture resolve it here or in the prepared images.
+#[derive(Resource, Debug, Default)]
+pub struct TextureViewSources(
+    Mutex<HashMap<render_resource::TextureViewId, render_resource::Texture>>,
+);
+
+impl TextureViewSources {
+    pub fn insert(&self, view: &render_resource::TextureView, texture: &render_resource::Texture) {
+        self.0
+            .lock()
+            .expect("Texture view sources mutex is poisoned")
+            .insert(view.id(), texture.clone());
+    }
+
+    /// Texture of the view published in the current frame, or of the prepared image with the view.
+    pub fn find(
+        world: &World,
+        view: &render_resource::TextureView,
+    ) -> Option<render_resource::Texture> {
+        let published = world.get_resource::<Self>().and_then(|sources| {
+            sources
+                .0
+                .lock()
+                .expect("Texture view sources mutex is poisoned")
+                .get(&view.id())
+                .cloned()
+        });
+        published.or_else(|| {
+            world
+                .get_resource::<RenderAssets<Image>>()?
+                .iter()
+                .find(|(_, image)| image.texture_view.id() == view.id())
+                .map(|(_, image)| image.texture.clone())
+        })
+    }
+
+    /// Registers the textures of the resource if the sources are available in the Render World.
+    pub(crate) fn insert_in(world: &World, resource: &OwnBindResource) {
+        let Some(sources) = world.get_resource::<Self>() else {
+            return;
+        };
+        match resource {
+            OwnBindResource::Texture(texture, view) => sources.insert(view, texture),
+            OwnBindResource::TextureArray(textures) => {
+                for (texture, view) in textures {
+                    sources.insert(view, texture);
+                }
+            }
+            _ => {}
         }
     }
+
+    fn clear_system(sources: Res<Self>) {
+        sources
+            .0
+            .lock()
+            .expect("Texture view sources mutex is poisoned")
+            .clear();
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -87,6 +379,9 @@ pub enum OwnBindResource {
     Buffer(render_resource::Buffer),
     Sampler(render_resource::Sampler),
     Texture(render_resource::Texture, render_resource::TextureView),
+    TextureView(render_resource::TextureView),
+    /// Not empty, the builder rejects empty texture arrays.
+    TextureArray(Vec<(render_resource::Texture, render_resource::TextureView)>),
 }
 
 impl OwnBindResource {
@@ -94,17 +389,148 @@ impl OwnBindResource {
         match self {
             OwnBindResource::Buffer(buffer) => render_graph::SlotValue::Buffer(buffer.clone()),
             OwnBindResource::Sampler(sampler) => render_graph::SlotValue::Sampler(sampler.clone()),
-            OwnBindResource::Texture(_, view) => render_graph::SlotValue::TextureView(view.clone()),
+            OwnBindResource::Texture(_, view) | OwnBindResource::TextureView(view) => {
+                render_graph::SlotValue::TextureView(view.clone())
+            }
+            OwnBindResource::TextureArray(textures) => {
+                render_graph::SlotValue::TextureView(textures[0].1.clone())
+            }
+        }
+    }
+
+    /// Estimated GPU memory of the resource, the external texture views are not owned.
+    pub(crate) fn allocated_bytes(&self) -> u64 {
+        match self {
+            OwnBindResource::Buffer(buffer) => buffer.size(),
+            OwnBindResource::Texture(texture, _) => texture_allocated_bytes(texture),
+            OwnBindResource::TextureArray(textures) => textures
+                .iter()
+                .map(|(texture, _)| texture_allocated_bytes(texture))
+                .sum(),
+            OwnBindResource::Sampler(_) | OwnBindResource::TextureView(_) => 0,
+        }
+    }
+
+    /// Views of the texture array, empty for the rest of the resources.
+    pub(crate) fn texture_array_views(&self) -> Vec<&wgpu::TextureView> {
+        match self {
+            OwnBindResource::TextureArray(textures) => {
+                textures.iter().map(|(_, view)| view.deref()).collect()
+            }
+            _ => Vec::new(),
         }
     }
 
+    /// Binding of the single resource, the texture arrays are bound with
+    /// `texture_array_views` instead.
     pub(crate) fn as_binding_resource(&self) -> render_resource::BindingResource {
         match self {
             OwnBindResource::Buffer(buffer) => buffer.as_entire_binding(),
             OwnBindResource::Sampler(sampler) => render_resource::BindingResource::Sampler(sampler),
-            OwnBindResource::Texture(_, view) => {
+            OwnBindResource::Texture(_, view) | OwnBindResource::TextureView(view) => {
                 render_resource::BindingResource::TextureView(view)
             }
+            OwnBindResource::TextureArray(textures) => {
+                render_resource::BindingResource::TextureView(&textures[0].1)
+            }
+        }
+    }
+}
+
+fn texture_allocated_bytes(texture: &render_resource::Texture) -> u64 {
+    let (block_width, block_height) = texture.format().block_dimensions();
+    let block_size = texture.format().block_size(None).unwrap_or(4);
+    let mip_levels: u64 = (0..texture.mip_level_count())
+        .map(|level| {
+            let size = texture.size().mip_level_size(level, texture.dimension());
+            u64::from(size.width.div_ceil(block_width))
+                * u64::from(size.height.div_ceil(block_height))
+                * u64::from(size.depth_or_array_layers)
+        })
+        .sum();
+    mip_levels * u64::from(block_size) * u64::from(texture.sample_count())
+}
+
+/// Handling of the input bind resources without a value in the graph context of the node run.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MissingInputMode {
+    /// The node run fails, the error is logged with the node label and the input slots.
+    #[default]
+    Error,
+    /// Missing buffer input is bound to a zero-initialized buffer of the size, and missing
+    /// sampler input to the default sampler. Texture inputs still fail, their format and
+    /// dimension are unknown. The size must be a non-zero multiple of 4.
+    Placeholder { buffer_size: BufferAddress },
+}
+
+/// Failure of the bind resources of the node run. `NodeRunError` has only the slot error
+/// variants, so the node run logs the error with the node name before the conversion.
+#[derive(Error, Debug)]
+pub enum BindResourceError {
+    #[error("Bind resource index {index} is out of range, the node has {count} bind resources")]
+    MissingIndex { index: usize, count: usize },
+    #[error("Bind resource `{name}` is not an output, its direction is {direction}")]
+    NotOutput {
+        name: Cow<'static, str>,
+        direction: &'static str,
+    },
+    #[error("Input `{name}` of node {node:?} is missing, expected inputs {expected:?}, available {available:?}")]
+    MissingInput {
+        name: Cow<'static, str>,
+        node: Option<String>,
+        expected: Vec<Cow<'static, str>>,
+        available: Vec<Cow<'static, str>>,
+    },
+    #[error("Input `{0}` is an Entity slot, that cannot be bound as a GPU resource")]
+    EntityInput(Cow<'static, str>),
+    #[error("{0} mutex is poisoned")]
+    Poisoned(&'static str),
+    #[error(transparent)]
+    NodeRunError(#[from] NodeRunError),
+}
+
+impl From<InputSlotError> for BindResourceError {
+    fn from(err: InputSlotError) -> Self {
+        Self::NodeRunError(err.into())
+    }
+}
+
+impl From<OutputSlotError> for BindResourceError {
+    fn from(err: OutputSlotError) -> Self {
+        Self::NodeRunError(err.into())
+    }
+}
+
+impl BindResourceError {
+    /// Logs the error of the node run with the node name and converts it to `NodeRunError`.
+    pub(crate) fn into_node_run_error(self, node: &str) -> NodeRunError {
+        if !matches!(self, Self::NodeRunError(_)) {
+            error!("Bind resources of node {:?} failed: {}", node, self);
+        }
+        self.into()
+    }
+}
+
+/// `NodeRunError` has only the slot and the sub graph errors, so the mapping is lossy:
+/// the missing and the invalid resources are reported as the invalid slots, and the poisoned
+/// mutex as the invalid input slot labeled with the mutex name. The original error is logged by
+/// the nodes before the conversion.
+impl From<BindResourceError> for NodeRunError {
+    fn from(err: BindResourceError) -> Self {
+        match err {
+            BindResourceError::MissingIndex { index, .. } => {
+                OutputSlotError::InvalidSlot(index.into()).into()
+            }
+            BindResourceError::NotOutput { name, .. } => {
+                OutputSlotError::InvalidSlot(name.into()).into()
+            }
+            BindResourceError::MissingInput { name, .. } | BindResourceError::EntityInput(name) => {
+                InputSlotError::InvalidSlot(name.into()).into()
+            }
+            BindResourceError::Poisoned(name) => {
+                InputSlotError::InvalidSlot(format!("<poisoned {} mutex>", name).into()).into()
+            }
+            BindResourceError::NodeRunError(err) => err,
         }
     }
 }
@@ -116,14 +542,239 @@ pub enum BindResourceDirection {
     InputOutput(render_graph::SlotType),
 }
 
+/// Created by the node builders, or with [`Self::new`] for the nodes declared manually,
+/// that sets the defaults of the optional fields.
 #[derive(Clone, Debug, PartialEq)]
 pub struct BindResourceCreationInfo {
     pub name: Cow<'static, str>,
+    pub group: u32,
     pub binding: u32,
     pub direction: BindResourceDirection,
+    /// Storage buffer is declared with `read` access in the shader.
+    pub read_only: bool,
+    /// Input buffer is declared as `var<uniform>` in the shader, e.g. `UniformBufferNode` output.
+    pub uniform: bool,
+    pub dynamic_offset: Option<DynamicOffset>,
+    /// Buffer is filled with zeros before each dispatch, e.g. for atomic accumulation.
+    pub clear_before_dispatch: bool,
+    pub lifetime: ResourceLifetime,
+}
+
+/// Lifetime of the output resource created by the node.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ResourceLifetime {
+    /// Created once and reused by the following runs while the descriptor is not changed,
+    /// so the content persists across frames, e.g. accumulators.
+    #[default]
+    Persistent,
+    /// Created anew on each run, e.g. scratch buffers. Recreated outputs are never equal
+    /// to the previous run, so a memoized node with transient outputs is always run.
+    Transient,
+}
+
+/// Dynamic offset of the input buffer binding, e.g. the offset returned by
+/// `DynamicStorageBufferNode::push`. Only `binding_size` bytes starting at the offset are visible
+/// to the shader. Dynamic offsets cannot be reflected from the shader, so the bind group layout
+/// must be declared explicitly with `has_dynamic_offset`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DynamicOffset {
+    pub binding_size: render_resource::BufferSize,
+    pub strategy: DynamicOffsetStrategy,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DynamicOffsetStrategy {
+    Static(u32),
+    FromGraphContext(GraphContextFn<u32>),
+}
+
+impl DynamicOffsetStrategy {
+    pub fn from_graph_context(
+        offset: impl Fn(&render_graph::RenderGraphContext) -> u32 + Send + Sync + 'static,
+    ) -> Self {
+        Self::FromGraphContext(GraphContextFn::new(offset))
+    }
+}
+
+impl DynamicOffset {
+    pub fn new(binding_size: render_resource::BufferSize, strategy: DynamicOffsetStrategy) -> Self {
+        Self {
+            binding_size,
+            strategy,
+        }
+    }
+
+    pub(crate) fn offset(&self, graph: &render_graph::RenderGraphContext) -> u32 {
+        match &self.strategy {
+            DynamicOffsetStrategy::Static(offset) => *offset,
+            DynamicOffsetStrategy::FromGraphContext(from_graph) => from_graph.call(graph),
+        }
+    }
 }
 
 impl BindResourceCreationInfo {
+    /// Persistent resource without the optional settings, that are set through the fields.
+    pub fn new(
+        name: impl Into<Cow<'static, str>>,
+        group: u32,
+        binding: u32,
+        direction: BindResourceDirection,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            group,
+            binding,
+            direction,
+            read_only: false,
+            uniform: false,
+            dynamic_offset: None,
+            clear_before_dispatch: false,
+            lifetime: ResourceLifetime::Persistent,
+        }
+    }
+
+    /// Layout entry of the storage or the uniform buffer resource, that can be used to declare
+    /// the bind group layout explicitly. `None` for the rest of the resources.
+    pub fn storage_buffer_layout_entry(
+        &self,
+        visibility: render_resource::ShaderStages,
+    ) -> Option<render_resource::BindGroupLayoutEntry> {
+        let is_buffer = match &self.direction {
+            BindResourceDirection::Input(slot_type)
+            | BindResourceDirection::InputOutput(slot_type) => {
+                *slot_type == render_graph::SlotType::Buffer
+            }
+            BindResourceDirection::Output(descriptor) => {
+                descriptor.to_slot_type() == render_graph::SlotType::Buffer
+            }
+        };
+        let ty = if self.uniform {
+            render_resource::BufferBindingType::Uniform
+        } else {
+            render_resource::BufferBindingType::Storage {
+                read_only: self.read_only,
+            }
+        };
+        is_buffer.then_some(render_resource::BindGroupLayoutEntry {
+            binding: self.binding,
+            visibility,
+            ty: render_resource::BindingType::Buffer {
+                ty,
+                has_dynamic_offset: self.dynamic_offset.is_some(),
+                min_binding_size: None,
+            },
+            count: None,
+        })
+    }
+
+    /// Layout entry of the storage texture output, that can be used to declare the bind group
+    /// layout explicitly. `None` for the rest of the resources, or when the format or the view
+    /// dimension is defined neither by the view nor by the static texture descriptor.
+    pub fn storage_texture_layout_entry(
+        &self,
+        visibility: render_resource::ShaderStages,
+    ) -> Option<render_resource::BindGroupLayoutEntry> {
+        let BindResourceDirection::Output(BindResourceCreationDescriptor::StorageTexture {
+            texture,
+            view,
+            access,
+        }) = &self.direction
+        else {
+            return None;
+        };
+        let static_texture = match texture {
+            BindResourceCreationStrategy::Static(texture) => Some(texture),
+            _ => None,
+        };
+        let format = view
+            .format
+            .or(static_texture.map(|texture| texture.format))?;
+        let view_dimension =
+            view.dimension
+                .or(static_texture.map(|texture| match texture.dimension {
+                    TextureDimension::D1 => TextureViewDimension::D1,
+                    TextureDimension::D2 => TextureViewDimension::D2,
+                    TextureDimension::D3 => TextureViewDimension::D3,
+                }))?;
+        Some(render_resource::BindGroupLayoutEntry {
+            binding: self.binding,
+            visibility,
+            ty: render_resource::BindingType::StorageTexture {
+                access: *access,
+                format,
+                view_dimension,
+            },
+            count: None,
+        })
+    }
+
+    /// Layout entry inferred from the direction and the slot type: storage and uniform buffers, storage
+    /// textures, filterable 2D float textures and texture arrays, and filtering samplers. `None` when the binding
+    /// type cannot be inferred, e.g. for the output textures that can be bound in both ways.
+    pub fn layout_entry(
+        &self,
+        visibility: render_resource::ShaderStages,
+    ) -> Option<render_resource::BindGroupLayoutEntry> {
+        if let Some(entry) = self
+            .storage_buffer_layout_entry(visibility)
+            .or_else(|| self.storage_texture_layout_entry(visibility))
+        {
+            return Some(entry);
+        }
+        let slot_type = match &self.direction {
+            BindResourceDirection::Input(slot_type)
+            | BindResourceDirection::InputOutput(slot_type) => *slot_type,
+            BindResourceDirection::Output(BindResourceCreationDescriptor::Sampler(_)) => {
+                render_graph::SlotType::Sampler
+            }
+            BindResourceDirection::Output(BindResourceCreationDescriptor::ExternalTextureView(
+                _,
+            )) => render_graph::SlotType::TextureView,
+            BindResourceDirection::Output(BindResourceCreationDescriptor::TextureArray(
+                textures,
+            )) => {
+                return Some(render_resource::BindGroupLayoutEntry {
+                    binding: self.binding,
+                    visibility,
+                    ty: render_resource::BindingType::Texture {
+                        sample_type: render_resource::TextureSampleType::Float { filterable: true },
+                        view_dimension: TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: NonZeroU32::new(textures.len() as u32),
+                });
+            }
+            BindResourceDirection::Output(_) => return None,
+        };
+        let ty = match slot_type {
+            render_graph::SlotType::TextureView => render_resource::BindingType::Texture {
+                sample_type: render_resource::TextureSampleType::Float { filterable: true },
+                view_dimension: TextureViewDimension::D2,
+                multisampled: false,
+            },
+            render_graph::SlotType::Sampler => render_resource::BindingType::Sampler(
+                render_resource::SamplerBindingType::Filtering,
+            ),
+            _ => return None,
+        };
+        Some(render_resource::BindGroupLayoutEntry {
+            binding: self.binding,
+            visibility,
+            ty,
+            count: None,
+        })
+    }
+
+    /// Sorted distinct bind group indices of the resources.
+    pub(crate) fn groups<'a>(
+        iterator: impl IntoIterator<Item = &'a BindResourceCreationInfo>,
+    ) -> Vec<u32> {
+        let mut groups: Vec<u32> = iterator.into_iter().map(|info| info.group).collect();
+        groups.sort_unstable();
+        groups.dedup();
+        groups
+    }
+
     pub(crate) fn input_output_slot_info<'a>(
         iterator: impl IntoIterator<Item = &'a BindResourceCreationInfo>,
     ) -> (Vec<render_graph::SlotInfo>, Vec<render_graph::SlotInfo>) {
@@ -158,80 +809,370 @@ impl BindResourceCreationInfo {
     }
 }
 
+/// Bindings declared once for the sub graph and shared by its nodes, for the shaders that
+/// follow the convention with globals in the lower bind group and per-node resources above.
+/// Each binding is an input of the node, connected to the output slot of the source node.
+#[derive(Clone, Debug, Default)]
+pub struct SharedBindings {
+    pub(crate) group: u32,
+    pub(crate) bindings: Vec<SharedBinding>,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct SharedBinding {
+    pub(crate) name: Cow<'static, str>,
+    pub(crate) binding: u32,
+    pub(crate) slot_type: render_graph::SlotType,
+    pub(crate) source_node: Cow<'static, str>,
+    pub(crate) source_slot: Cow<'static, str>,
+    pub(crate) read_only: bool,
+}
+
+impl SharedBindings {
+    pub fn new(group: u32) -> Self {
+        Self {
+            group,
+            bindings: default(),
+        }
+    }
+
+    pub fn add(
+        mut self,
+        name: impl Into<Cow<'static, str>>,
+        binding: u32,
+        slot_type: render_graph::SlotType,
+        source_node: impl Into<Cow<'static, str>>,
+        source_slot: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        self.bindings.push(SharedBinding {
+            name: name.into(),
+            binding,
+            slot_type,
+            source_node: source_node.into(),
+            source_slot: source_slot.into(),
+            read_only: false,
+        });
+        self
+    }
+
+    /// Shared buffer declared as `var<storage, read>` in the shaders of the nodes.
+    pub fn add_read_only_buffer(
+        mut self,
+        name: impl Into<Cow<'static, str>>,
+        binding: u32,
+        source_node: impl Into<Cow<'static, str>>,
+        source_slot: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        self = self.add(
+            name,
+            binding,
+            render_graph::SlotType::Buffer,
+            source_node,
+            source_slot,
+        );
+        if let Some(shared) = self.bindings.last_mut() {
+            shared.read_only = true;
+        }
+        self
+    }
+
+    pub fn group(&self) -> u32 {
+        self.group
+    }
+
+    pub(crate) fn bind_resource_info(&self) -> impl Iterator<Item = BindResourceCreationInfo> + '_ {
+        self.bindings.iter().map(|shared| BindResourceCreationInfo {
+            name: shared.name.clone(),
+            group: self.group,
+            binding: shared.binding,
+            direction: BindResourceDirection::Input(shared.slot_type),
+            read_only: shared.read_only,
+            uniform: false,
+            dynamic_offset: None,
+            clear_before_dispatch: false,
+            lifetime: ResourceLifetime::Persistent,
+        })
+    }
+}
+
+/// Bind group layouts shared by several nodes, one per bind group index. The layouts are created
+/// from the entries once, on the first use in the Render World, and the same layout objects are
+/// used by the pipeline descriptors of all nodes, so the pipeline layouts are cached once.
+#[derive(Clone, Debug)]
+pub struct SharedBindGroupLayout {
+    label: Option<Cow<'static, str>>,
+    entries: Arc<Vec<Vec<render_resource::BindGroupLayoutEntry>>>,
+    layouts: Arc<Mutex<Option<Vec<render_resource::BindGroupLayout>>>>,
+}
+
+impl SharedBindGroupLayout {
+    pub fn new(
+        label: Option<Cow<'static, str>>,
+        entries: Vec<Vec<render_resource::BindGroupLayoutEntry>>,
+    ) -> Self {
+        Self {
+            label,
+            entries: Arc::new(entries),
+            layouts: default(),
+        }
+    }
+
+    /// Entries of the layouts, empty for the layouts shared with [`Self::from_layouts`].
+    pub fn entries(&self) -> &[Vec<render_resource::BindGroupLayoutEntry>] {
+        &self.entries
+    }
+
+    /// Shares the layouts that are already created.
+    pub fn from_layouts(layouts: Vec<render_resource::BindGroupLayout>) -> Self {
+        Self {
+            label: None,
+            entries: default(),
+            layouts: Arc::new(Mutex::new(Some(layouts))),
+        }
+    }
+
+    /// Layouts if they're already created.
+    pub fn layouts(&self) -> Option<Vec<render_resource::BindGroupLayout>> {
+        self.lock().clone()
+    }
+
+    pub(crate) fn get_or_create(
+        &self,
+        render_device: &RenderDevice,
+    ) -> Vec<render_resource::BindGroupLayout> {
+        self.lock()
+            .get_or_insert_with(|| {
+                self.entries
+                    .iter()
+                    .map(|entries| {
+                        render_device.create_bind_group_layout(
+                            &render_resource::BindGroupLayoutDescriptor {
+                                label: self.label.as_deref(),
+                                entries,
+                            },
+                        )
+                    })
+                    .collect()
+            })
+            .clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<Vec<render_resource::BindGroupLayout>>> {
+        self.layouts
+            .lock()
+            .expect("Shared bind group layout mutex is poisoned")
+    }
+}
+
+/// Actual sizes of the buffers bound by the node during the last run, keyed by bind resource name.
+/// Shared between the main and the render world, so it can be read from the main world component.
+#[derive(Component, Debug, Clone, Default)]
+pub struct BindResourceSizes(Arc<Mutex<HashMap<Cow<'static, str>, BufferAddress>>>);
+
+impl BindResourceSizes {
+    pub fn get(&self, name: &str) -> Option<BufferAddress> {
+        self.0
+            .lock()
+            .expect("Bind Resource sizes mutex is poisoned")
+            .get(name)
+            .copied()
+    }
+
+    pub fn snapshot(&self) -> HashMap<Cow<'static, str>, BufferAddress> {
+        self.0
+            .lock()
+            .expect("Bind Resource sizes mutex is poisoned")
+            .clone()
+    }
+
+    fn update(&self, sizes: HashMap<Cow<'static, str>, BufferAddress>) {
+        *self
+            .0
+            .lock()
+            .expect("Bind Resource sizes mutex is poisoned") = sizes;
+    }
+}
+
+type BindResourceCache = HashMap<usize, (StaticBindResourceCreationDescriptor, OwnBindResource)>;
+
 #[derive(Debug, Clone)]
 pub(crate) struct NodeResources {
     bind_resource_info: Vec<BindResourceCreationInfo>,
-    bind_resource_cache:
-        Arc<Mutex<HashMap<usize, (StaticBindResourceCreationDescriptor, OwnBindResource)>>>,
+    bind_resource_cache: Arc<Mutex<BindResourceCache>>,
+    bind_resource_sizes: BindResourceSizes,
+    missing_input_mode: MissingInputMode,
+    placeholders: Arc<Mutex<HashMap<usize, OwnBindResource>>>,
 }
 
 impl NodeResources {
     pub(crate) fn from_bind_resource_info(
         bind_resource_info: Vec<BindResourceCreationInfo>,
+        bind_resource_sizes: BindResourceSizes,
     ) -> Self {
         Self {
             bind_resource_info,
             bind_resource_cache: default(),
+            bind_resource_sizes,
+            missing_input_mode: default(),
+            placeholders: default(),
         }
     }
 
-    pub(crate) fn set_bind_group(
+    pub(crate) fn with_missing_input_mode(mut self, missing_input_mode: MissingInputMode) -> Self {
+        self.missing_input_mode = missing_input_mode;
+        self
+    }
+
+    /// Drops the cached transient outputs, so they are created again by the current run.
+    /// Must be called once per run before the bind groups are created.
+    pub(crate) fn release_transient_resources(&self) -> Result<(), BindResourceError> {
+        self.lock_cache()?.retain(|index, _| {
+            self.bind_resource_info
+                .get(*index)
+                .is_some_and(|info| info.lifetime == ResourceLifetime::Persistent)
+        });
+        Ok(())
+    }
+
+    /// Creates a bind group for each of the layouts, the resources are matched to the layout
+    /// by their group index.
+    pub(crate) fn set_bind_groups(
         &self,
         render_device: &RenderDevice,
         graph: &render_graph::RenderGraphContext,
-        layout: &render_resource::BindGroupLayout,
-    ) -> Result<render_resource::BindGroup, render_graph::NodeRunError> {
-        let mut entries: Vec<render_resource::BindGroupEntry> = default();
-        let mut output_resources: Vec<(u32, OwnBindResource)> = default();
+        layouts: &[(u32, render_resource::BindGroupLayout)],
+        label: Option<&str>,
+    ) -> Result<Vec<(u32, render_resource::BindGroup)>, BindResourceError> {
+        let mut entries: Vec<(u32, render_resource::BindGroupEntry)> = default();
+        let mut own_resources: Vec<(u32, u32, OwnBindResource)> = default();
+        let mut sizes: HashMap<Cow<'static, str>, BufferAddress> = default();
 
         for (index, info) in self.bind_resource_info.iter().enumerate() {
             match &info.direction {
-                BindResourceDirection::Input(_) | BindResourceDirection::InputOutput(_) => {
-                    if let Ok(value) = graph.get_input(info.name.clone()) {
-                        entries.push(render_resource::BindGroupEntry {
-                            binding: info.binding,
-                            resource: slot_value_to_bind_resource(value),
-                        });
-                    } else {
-                        return Err(render_graph::NodeRunError::InputSlotError(
-                            render_graph::InputSlotError::InvalidSlot(info.name.clone().into()),
+                BindResourceDirection::Input(slot_type)
+                | BindResourceDirection::InputOutput(slot_type) => {
+                    if let Some(value) = bindable_input(graph, info.name.clone(), *slot_type) {
+                        if let render_graph::SlotValue::Buffer(buffer) = value {
+                            sizes.insert(info.name.clone(), buffer.size());
+                        }
+                        entries.push((
+                            info.group,
+                            render_resource::BindGroupEntry {
+                                binding: info.binding,
+                                resource: slot_value_to_bind_resource(
+                                    &info.name,
+                                    value,
+                                    info.dynamic_offset.as_ref(),
+                                )?,
+                            },
                         ));
+                    } else if let Some(placeholder) =
+                        self.get_placeholder(index, info, *slot_type, render_device, label)?
+                    {
+                        own_resources.push((info.group, info.binding, placeholder));
+                    } else {
+                        return Err(BindResourceError::MissingInput {
+                            name: info.name.clone(),
+                            node: label.map(str::to_string),
+                            expected: self.input_names().cloned().collect(),
+                            available: self
+                                .bind_resource_info
+                                .iter()
+                                .filter_map(|info| match info.direction {
+                                    BindResourceDirection::Input(slot_type)
+                                    | BindResourceDirection::InputOutput(slot_type) => {
+                                        bindable_input(graph, info.name.clone(), slot_type)
+                                            .map(|_| info.name.clone())
+                                    }
+                                    BindResourceDirection::Output(_) => None,
+                                })
+                                .collect(),
+                        });
                     }
                 }
                 BindResourceDirection::Output(_) => {
-                    output_resources.push((
-                        info.binding,
-                        self.get_output_resource(index, graph, render_device)?,
-                    ));
+                    let resource = self.get_output_resource(index, graph, render_device)?;
+                    if let OwnBindResource::Buffer(buffer) = &resource {
+                        sizes.insert(info.name.clone(), buffer.size());
+                    }
+                    own_resources.push((info.group, info.binding, resource));
                 }
             }
         }
+        self.bind_resource_sizes.update(sizes);
 
-        for (binding, output_resource) in &output_resources {
-            entries.push(render_resource::BindGroupEntry {
-                binding: *binding,
-                resource: output_resource.as_binding_resource(),
-            });
+        let texture_array_views: Vec<Vec<&wgpu::TextureView>> = own_resources
+            .iter()
+            .map(|(_, _, own_resource)| own_resource.texture_array_views())
+            .collect();
+        for ((group, binding, own_resource), views) in
+            own_resources.iter().zip(&texture_array_views)
+        {
+            let resource = match own_resource {
+                OwnBindResource::TextureArray(_) => {
+                    render_resource::BindingResource::TextureViewArray(views)
+                }
+                _ => own_resource.as_binding_resource(),
+            };
+            entries.push((
+                *group,
+                render_resource::BindGroupEntry {
+                    binding: *binding,
+                    resource,
+                },
+            ));
         }
-        let bind_group = render_device.create_bind_group(None, layout, &entries);
 
-        Ok(bind_group)
+        let bind_groups = layouts
+            .iter()
+            .map(|(group, layout)| {
+                let group_entries: Vec<render_resource::BindGroupEntry> = entries
+                    .iter()
+                    .filter(|(entry_group, _)| entry_group == group)
+                    .map(|(_, entry)| entry.clone())
+                    .collect();
+                (
+                    *group,
+                    render_device.create_bind_group(label, layout, &group_entries),
+                )
+            })
+            .collect();
+
+        Ok(bind_groups)
+    }
+
+    /// Dynamic offsets of the bind group, ordered by the binding index as required by wgpu.
+    pub(crate) fn dynamic_offsets(
+        &self,
+        graph: &render_graph::RenderGraphContext,
+        group: u32,
+    ) -> Vec<u32> {
+        let mut offsets: Vec<(u32, u32)> = self
+            .bind_resource_info
+            .iter()
+            .filter(|info| info.group == group)
+            .filter_map(|info| {
+                let dynamic_offset = info.dynamic_offset.as_ref()?;
+                Some((info.binding, dynamic_offset.offset(graph)))
+            })
+            .collect();
+        offsets.sort_unstable_by_key(|(binding, _)| *binding);
+        offsets.into_iter().map(|(_, offset)| offset).collect()
     }
 
     pub(crate) fn set_output_slots(
         &self,
         graph: &mut render_graph::RenderGraphContext,
         render_device: &RenderDevice,
-    ) -> Result<(), render_graph::NodeRunError> {
+        world: &World,
+    ) -> Result<(), BindResourceError> {
         for (index, info) in self.bind_resource_info.iter().enumerate() {
             match info.direction {
                 BindResourceDirection::Output(_) => {
+                    let resource = self.get_output_resource(index, graph, render_device)?;
+                    TextureViewSources::insert_in(world, &resource);
                     let label: render_graph::SlotLabel = info.name.clone().into();
-                    graph.set_output(
-                        label,
-                        self.get_output_resource(index, graph, render_device)?
-                            .to_slot_value(),
-                    )?;
+                    graph.set_output(label, resource.to_slot_value())?;
                 }
                 BindResourceDirection::InputOutput(_) => {
                     let label: render_graph::SlotLabel = info.name.clone().into();
@@ -244,25 +1185,211 @@ impl NodeResources {
         Ok(())
     }
 
+    /// Publishes the outputs of the skipped run: the resources created by the previous run
+    /// for `Output` and the input values for `InputOutput` bind resources.
+    pub(crate) fn set_previous_output_slots(
+        &self,
+        graph: &mut render_graph::RenderGraphContext,
+        render_device: &RenderDevice,
+        world: &World,
+    ) -> Result<(), BindResourceError> {
+        for (index, info) in self.bind_resource_info.iter().enumerate() {
+            if let BindResourceDirection::Output(_) = info.direction {
+                let resource = match self.cached_output_resource(index)? {
+                    Some(resource) => resource,
+                    None => self.get_output_resource(index, graph, render_device)?,
+                };
+                TextureViewSources::insert_in(world, &resource);
+                let label: render_graph::SlotLabel = info.name.clone().into();
+                graph.set_output(label, resource.to_slot_value())?;
+            } else if let BindResourceDirection::InputOutput(_) = info.direction {
+                let label: render_graph::SlotLabel = info.name.clone().into();
+                graph.set_output(label.clone(), graph.get_input(label)?.clone())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Buffers of the bind resources that must be cleared before the dispatch.
+    pub(crate) fn buffers_to_clear(
+        &self,
+        graph: &render_graph::RenderGraphContext,
+        render_device: &RenderDevice,
+    ) -> Result<Vec<render_resource::Buffer>, BindResourceError> {
+        let mut buffers = Vec::new();
+        for (index, info) in self.bind_resource_info.iter().enumerate() {
+            if !info.clear_before_dispatch {
+                continue;
+            }
+            match info.direction {
+                BindResourceDirection::Output(_) => {
+                    if let OwnBindResource::Buffer(buffer) =
+                        self.get_output_resource(index, graph, render_device)?
+                    {
+                        buffers.push(buffer);
+                    }
+                }
+                BindResourceDirection::InputOutput(_) => {
+                    buffers.push(graph.get_input_buffer(info.name.clone())?.clone());
+                }
+                _ => {}
+            }
+        }
+        Ok(buffers)
+    }
+
+    /// Identities of the input values and of the output resources created by the node.
+    pub(crate) fn resource_ids(
+        &self,
+        graph: &render_graph::RenderGraphContext,
+    ) -> Result<Vec<SlotValueId>, BindResourceError> {
+        let cache = self.lock_cache()?;
+        let mut ids: Vec<SlotValueId> = graph.inputs().iter().map(SlotValueId::from).collect();
+        ids.extend((0..self.bind_resource_info.len()).filter_map(|index| {
+            cache
+                .get(&index)
+                .map(|(_, resource)| SlotValueId::from(&resource.to_slot_value()))
+        }));
+        Ok(ids)
+    }
+
+    /// Estimated GPU memory of the output resources created by the node.
+    pub(crate) fn allocated_bytes(&self) -> Result<u64, BindResourceError> {
+        Ok(self
+            .lock_cache()?
+            .values()
+            .map(|(_, resource)| resource.allocated_bytes())
+            .sum())
+    }
+
+    /// Marks the resources that can be written by the node: outputs and `InputOutput` inputs.
+    pub(crate) fn mark_written(
+        &self,
+        graph: &render_graph::RenderGraphContext,
+        world: &World,
+    ) -> Result<(), BindResourceError> {
+        for (index, info) in self.bind_resource_info.iter().enumerate() {
+            match info.direction {
+                BindResourceDirection::Output(_) => {
+                    if let Some(resource) = self.cached_output_resource(index)? {
+                        WrittenResources::mark_in(world, &resource.to_slot_value());
+                    }
+                }
+                BindResourceDirection::InputOutput(_) => {
+                    if let Ok(value) = graph.get_input(info.name.clone()) {
+                        WrittenResources::mark_in(world, value);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn lock_cache(&self) -> Result<MutexGuard<'_, BindResourceCache>, BindResourceError> {
+        self.bind_resource_cache
+            .lock()
+            .map_err(|_| BindResourceError::Poisoned("Bind Resource cache"))
+    }
+
+    fn cached_output_resource(
+        &self,
+        index: usize,
+    ) -> Result<Option<OwnBindResource>, BindResourceError> {
+        Ok(self
+            .lock_cache()?
+            .get(&index)
+            .map(|(_, resource)| resource.clone()))
+    }
+
+    fn input_names(&self) -> impl Iterator<Item = &Cow<'static, str>> {
+        self.bind_resource_info
+            .iter()
+            .filter(|info| !matches!(info.direction, BindResourceDirection::Output(_)))
+            .map(|info| &info.name)
+    }
+
+    /// Placeholder of the missing input, created once and reused by the next runs.
+    /// The replacement is logged once, when the placeholder is created.
+    fn get_placeholder(
+        &self,
+        index: usize,
+        info: &BindResourceCreationInfo,
+        slot_type: render_graph::SlotType,
+        render_device: &RenderDevice,
+        label: Option<&str>,
+    ) -> Result<Option<OwnBindResource>, BindResourceError> {
+        let MissingInputMode::Placeholder { buffer_size } = self.missing_input_mode else {
+            return Ok(None);
+        };
+        let mut placeholders = self
+            .placeholders
+            .lock()
+            .map_err(|_| BindResourceError::Poisoned("Placeholders"))?;
+        if let Some(placeholder) = placeholders.get(&index) {
+            return Ok(Some(placeholder.clone()));
+        }
+        let placeholder = match slot_type {
+            render_graph::SlotType::Buffer => OwnBindResource::Buffer(render_device.create_buffer(
+                &render_resource::BufferDescriptor {
+                    label: Some("placeholder"),
+                    size: buffer_size,
+                    usage: if info.uniform {
+                        render_resource::BufferUsages::UNIFORM
+                    } else {
+                        render_resource::BufferUsages::STORAGE
+                    },
+                    mapped_at_creation: false,
+                },
+            )),
+            render_graph::SlotType::Sampler => OwnBindResource::Sampler(
+                render_device.create_sampler(&render_resource::SamplerDescriptor::default()),
+            ),
+            _ => return Ok(None),
+        };
+        warn!(
+            "Input `{}` of node {:?} is missing, bound to the placeholder",
+            &info.name, label
+        );
+        placeholders.insert(index, placeholder.clone());
+        Ok(Some(placeholder))
+    }
+
     pub(crate) fn get_output_resource(
         &self,
         index: usize,
         graph: &render_graph::RenderGraphContext,
         render_device: &RenderDevice,
-    ) -> Result<OwnBindResource, render_graph::NodeRunError> {
-        let Some(BindResourceCreationInfo {
-            direction: BindResourceDirection::Output(descriptor),
-            ..
-        }) = self.bind_resource_info.get(index)
-        else {
-            return Err(render_graph::NodeRunError::OutputSlotError(
-                OutputSlotError::InvalidSlot(index.into()),
-            ));
+    ) -> Result<OwnBindResource, BindResourceError> {
+        let info = self
+            .bind_resource_info
+            .get(index)
+            .ok_or(BindResourceError::MissingIndex {
+                index,
+                count: self.bind_resource_info.len(),
+            })?;
+        let descriptor = match &info.direction {
+            BindResourceDirection::Output(descriptor) => descriptor,
+            BindResourceDirection::Input(_) => {
+                return Err(BindResourceError::NotOutput {
+                    name: info.name.clone(),
+                    direction: "Input",
+                })
+            }
+            BindResourceDirection::InputOutput(_) => {
+                return Err(BindResourceError::NotOutput {
+                    name: info.name.clone(),
+                    direction: "InputOutput",
+                })
+            }
         };
-        let mut cache = self
-            .bind_resource_cache
-            .lock()
-            .expect("Bind Resource cache mutex is poisoned");
+        let mut cache = self.lock_cache()?;
+        if descriptor.is_deferred() {
+            if let Some((_, cached_resource)) = cache.get(&index) {
+                return Ok(cached_resource.clone());
+            }
+        }
         let static_descriptor = descriptor.clone().into_static(graph);
         if let Some((cached_static_descriptor, cached_resource)) = cache.get(&index) {
             if cached_static_descriptor == &static_descriptor {
@@ -280,11 +1407,34 @@ impl NodeResources {
     }
 }
 
-fn slot_value_to_bind_resource(
-    slot_value: &render_graph::SlotValue,
-) -> render_resource::BindingResource {
-    match slot_value {
-        render_graph::SlotValue::Buffer(buffer) => buffer.as_entire_binding(),
+/// Value of the input slot that can be bound, entity values are accepted only by entity slots.
+fn bindable_input<'a>(
+    graph: &'a render_graph::RenderGraphContext,
+    name: Cow<'static, str>,
+    slot_type: render_graph::SlotType,
+) -> Option<&'a render_graph::SlotValue> {
+    graph.get_input(name).ok().filter(|value| {
+        !matches!(value, render_graph::SlotValue::Entity(_))
+            || slot_type == render_graph::SlotType::Entity
+    })
+}
+
+fn slot_value_to_bind_resource<'a>(
+    name: &str,
+    slot_value: &'a render_graph::SlotValue,
+    dynamic_offset: Option<&DynamicOffset>,
+) -> Result<render_resource::BindingResource<'a>, BindResourceError> {
+    Ok(match slot_value {
+        render_graph::SlotValue::Buffer(buffer) => match dynamic_offset {
+            Some(dynamic_offset) => {
+                render_resource::BindingResource::Buffer(render_resource::BufferBinding {
+                    buffer,
+                    offset: 0,
+                    size: Some(dynamic_offset.binding_size),
+                })
+            }
+            None => buffer.as_entire_binding(),
+        },
         render_graph::SlotValue::TextureView(texture_view) => {
             render_resource::BindingResource::TextureView(texture_view)
         }
@@ -292,6 +1442,8 @@ fn slot_value_to_bind_resource(
         render_graph::SlotValue::Sampler(sampler) => {
             render_resource::BindingResource::Sampler(sampler)
         }
-        render_graph::SlotValue::Entity(_) => todo!(),
-    }
+        render_graph::SlotValue::Entity(_) => {
+            return Err(BindResourceError::EntityInput(name.to_string().into()));
+        }
+    })
 }
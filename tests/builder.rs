@@ -0,0 +1,1907 @@
+use bevy::prelude::*;
+use bevy_node_plumber::prelude::*;
+use bevy_node_plumber::NodeProvider;
+use bevy_render::render_graph::{self, RenderGraph, SlotType};
+use bevy_render::render_resource::{
+    BindingType, BufferBindingType, BufferSize, BufferUsages, CachedComputePipelineId, Extent3d,
+    PipelineCache, SamplerDescriptor, ShaderDefVal, ShaderStages, StorageTextureAccess,
+    TextureDescriptor, TextureDimension, TextureFormat, TextureUsages, TextureViewDescriptor,
+    TextureViewDimension,
+};
+use bevy_render::renderer::{RenderContext, RenderDevice, RenderQueue};
+use bevy_render::RenderApp;
+
+mod common;
+
+fn node_builder() -> builder::ComputeNodeBuilder {
+    builder::ComputeNodeBuilder::default()
+        .shader(Handle::default())
+        .entry_point("main")
+        .dispatch_workgroups_strategy(DispatchWorkgroupsStrategy::Static(1, 1, 1))
+}
+
+#[test]
+fn read_only_storage_buffer_layout_entry() {
+    let node = node_builder()
+        .bind_resource()
+        .name("positions")
+        .binding(0)
+        .read_only()
+        .input()
+        .buffer()
+        .add()
+        .bind_resource()
+        .name("output")
+        .binding(1)
+        .input_output()
+        .buffer()
+        .add()
+        .build()
+        .unwrap();
+
+    let read_only: Vec<bool> = node
+        .binding_resource_info
+        .iter()
+        .map(|info| {
+            let entry = info
+                .storage_buffer_layout_entry(ShaderStages::COMPUTE)
+                .unwrap();
+            let BindingType::Buffer {
+                ty: BufferBindingType::Storage { read_only },
+                ..
+            } = entry.ty
+            else {
+                panic!("Storage buffer layout entry expected");
+            };
+            read_only
+        })
+        .collect();
+    assert_eq!(read_only, vec![true, false]);
+}
+
+#[test]
+fn read_only_is_rejected_for_written_resources() {
+    let result = node_builder()
+        .bind_resource()
+        .name("output")
+        .read_only()
+        .input_output()
+        .buffer()
+        .add()
+        .build();
+
+    assert!(matches!(
+        result,
+        Err(builder::BuilderError::ValidationError(_))
+    ));
+}
+
+#[test]
+fn shared_bindings_move_node_resources_to_next_group() {
+    let shared = SharedBindings::new(0).add(
+        "globals",
+        0,
+        bevy_render::render_graph::SlotType::Buffer,
+        "globals_node",
+        input::SLOT_NAME,
+    );
+    let node = node_builder()
+        .shared_bindings(&shared)
+        .bind_resource()
+        .name("buffer")
+        .binding(0)
+        .input_output()
+        .buffer()
+        .add()
+        .build()
+        .unwrap();
+
+    let groups: Vec<(&str, u32)> = node
+        .binding_resource_info
+        .iter()
+        .map(|info| (info.name.as_ref(), info.group))
+        .collect();
+    assert_eq!(groups, vec![("globals", 0), ("buffer", 1)]);
+}
+
+#[test]
+fn bind_group_index_is_resolved_for_every_resource_without_group() {
+    let node = node_builder()
+        .bind_resource()
+        .name("before")
+        .binding(0)
+        .input_output()
+        .buffer()
+        .add()
+        .bind_group_index(2)
+        .bind_resource()
+        .name("explicit")
+        .group(0)
+        .binding(1)
+        .input_output()
+        .buffer()
+        .add()
+        .bind_resource()
+        .name("after")
+        .binding(1)
+        .input_output()
+        .buffer()
+        .add()
+        .build()
+        .unwrap();
+
+    assert_eq!(node.bind_group_index, 2);
+    let groups: Vec<(&str, u32)> = node
+        .binding_resource_info
+        .iter()
+        .map(|info| (info.name.as_ref(), info.group))
+        .collect();
+    assert_eq!(groups, vec![("before", 2), ("explicit", 0), ("after", 2)]);
+}
+
+#[test]
+fn push_constants_must_fit_declared_ranges() {
+    use bevy_render::render_resource::PushConstantRange;
+
+    let with_push_constants = |bytes: Vec<u8>| {
+        node_builder()
+            .push_constant_ranges(vec![PushConstantRange {
+                stages: ShaderStages::COMPUTE,
+                range: 0..8,
+            }])
+            .push_constants_strategy(PushConstantsStrategy::Static(bytes))
+            .build()
+    };
+
+    assert!(with_push_constants(vec![0; 8]).is_ok());
+    assert!(matches!(
+        with_push_constants(vec![0; 12]),
+        Err(builder::BuilderError::ValidationError(_))
+    ));
+    assert!(matches!(
+        with_push_constants(vec![0; 6]),
+        Err(builder::BuilderError::ValidationError(_))
+    ));
+}
+
+#[test]
+fn dispatch_chunking_covers_large_dispatch() {
+    // 2^32 - 1 elements with 64 invocations per workgroup
+    let workgroups = DispatchWorkgroupsStrategy::cover_extent((u32::MAX, 1, 1), (64, 1, 1));
+    let chunking = DispatchChunking::new((65535, 1, 1));
+    let chunks = chunking.chunks(workgroups);
+
+    assert_eq!(workgroups, (67_108_864, 1, 1));
+    assert_eq!(chunks.len(), 1025);
+    assert_eq!(chunks[1], ((65535, 0, 0), (65535, 1, 1)));
+    assert_eq!(chunks.last(), Some(&((67_107_840, 0, 0), (1024, 1, 1))));
+    assert_eq!(
+        chunks.iter().map(|(_, count)| count.0 as u64).sum::<u64>(),
+        workgroups.0 as u64
+    );
+}
+
+#[test]
+fn dispatch_chunking_offset_must_not_overlap_push_constants() {
+    use bevy_render::render_resource::PushConstantRange;
+
+    let with_chunking = |chunking: DispatchChunking| {
+        node_builder()
+            .push_constant_ranges(vec![PushConstantRange {
+                stages: ShaderStages::COMPUTE,
+                range: 0..20,
+            }])
+            .push_constants_strategy(PushConstantsStrategy::Static(vec![0; 8]))
+            .dispatch_chunking(chunking)
+            .build()
+    };
+
+    assert!(matches!(
+        with_chunking(DispatchChunking::new((16, 16, 16))),
+        Err(builder::BuilderError::ValidationError(_))
+    ));
+    assert!(
+        with_chunking(DispatchChunking::new((16, 16, 16)).with_push_constant_offset(8)).is_ok()
+    );
+}
+
+#[test]
+fn storage_texture_layout_entry_uses_access_and_view_format() {
+    let texture = TextureDescriptor {
+        label: Some("storage"),
+        size: Extent3d {
+            width: 16,
+            height: 16,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: TextureFormat::Rgba8Unorm,
+        usage: TextureUsages::STORAGE_BINDING | TextureUsages::COPY_SRC,
+        view_formats: &[TextureFormat::Rgba8Unorm],
+    };
+    let view = TextureViewDescriptor {
+        format: Some(TextureFormat::Rgba8Unorm),
+        ..default()
+    };
+    let node = node_builder()
+        .bind_resource()
+        .name("image")
+        .binding(0)
+        .output()
+        .storage_texture(
+            texture.clone(),
+            view.clone(),
+            StorageTextureAccess::WriteOnly,
+        )
+        .add()
+        .build()
+        .unwrap();
+
+    let entry = node.binding_resource_info[0]
+        .storage_texture_layout_entry(ShaderStages::COMPUTE)
+        .unwrap();
+    assert_eq!(
+        entry.ty,
+        BindingType::StorageTexture {
+            access: StorageTextureAccess::WriteOnly,
+            format: TextureFormat::Rgba8Unorm,
+            view_dimension: TextureViewDimension::D2,
+        }
+    );
+
+    let result = node_builder()
+        .bind_resource()
+        .name("image")
+        .output()
+        .storage_texture(
+            TextureDescriptor {
+                usage: TextureUsages::COPY_SRC,
+                ..texture
+            },
+            view,
+            StorageTextureAccess::WriteOnly,
+        )
+        .add()
+        .build();
+    assert!(matches!(
+        result,
+        Err(builder::BuilderError::ValidationError(_))
+    ));
+}
+
+#[test]
+fn render_node_declares_fullscreen_triangle_and_target_slots() {
+    let render_node = builder::RenderNodeBuilder::default()
+        .fragment_shader(Handle::default())
+        .fragment_entry_point("fragment")
+        .target_format(TextureFormat::Rgba8Unorm)
+        .target_slot("target")
+        .bind_resource()
+        .name("colors")
+        .binding(0)
+        .read_only()
+        .input()
+        .buffer()
+        .add()
+        .build()
+        .unwrap();
+    assert_eq!(render_node.vertex_count, 3);
+
+    let mut graph = RenderGraph::default();
+    render_node.add_node_to_graph(&mut graph, "render".into());
+    let state = graph.get_node_state("render").unwrap();
+    let inputs: Vec<_> = state
+        .input_slots
+        .iter()
+        .map(|slot| slot.name.clone())
+        .collect();
+    assert_eq!(inputs, vec!["colors", "target"]);
+    let target = state.output_slots.get_slot("target").unwrap();
+    assert_eq!(target.slot_type, SlotType::TextureView);
+
+    let result = builder::RenderNodeBuilder::default()
+        .fragment_entry_point("fragment")
+        .target_format(TextureFormat::Rgba8Unorm)
+        .target_slot("target")
+        .build();
+    assert!(matches!(
+        result,
+        Err(builder::BuilderError::ValueNotDefined("fragment_shader"))
+    ));
+}
+
+#[test]
+fn debug_name_falls_back_to_label() {
+    let mut node = node_builder().label("labeled").build().unwrap();
+    node.debug_name = None;
+    assert_eq!(node.debug_name(), "labeled");
+    node.label = None;
+    assert!(node.debug_name().contains("ComputeNodeImpl"));
+
+    let mut render_node = builder::RenderNodeBuilder::default()
+        .label("labeled_render")
+        .fragment_shader(Handle::default())
+        .fragment_entry_point("fragment")
+        .target_format(TextureFormat::Rgba8Unorm)
+        .target_slot("target")
+        .build()
+        .unwrap();
+    render_node.debug_name = None;
+    assert_eq!(render_node.debug_name(), "labeled_render");
+}
+
+#[test]
+fn render_node_rejects_shared_binding() {
+    let result = builder::RenderNodeBuilder::default()
+        .fragment_shader(Handle::default())
+        .fragment_entry_point("fragment")
+        .target_format(TextureFormat::Rgba8Unorm)
+        .target_slot("target")
+        .bind_resource()
+        .name("colors")
+        .binding(0)
+        .input()
+        .buffer()
+        .add()
+        .bind_resource()
+        .name("sampler")
+        .binding(0)
+        .input()
+        .sampler()
+        .add()
+        .build();
+
+    assert!(matches!(
+        result,
+        Err(builder::BuilderError::ValidationError(message)) if message.contains("`sampler`")
+    ));
+}
+
+#[test]
+fn sampler_and_texture_outputs_from_graph_context() {
+    let node = node_builder()
+        .bind_resource()
+        .name("sampler")
+        .binding(0)
+        .output()
+        .sampler_from_graph_context(|_| SamplerDescriptor::default())
+        .add()
+        .bind_resource()
+        .name("texture")
+        .binding(1)
+        .output()
+        .texture_from_graph_context(|_| TextureDescriptor {
+            label: None,
+            size: Extent3d::default(),
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Rgba8Unorm,
+            usage: TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        })
+        .add()
+        .build()
+        .unwrap();
+
+    let mut graph = RenderGraph::default();
+    node.add_node_to_graph(&mut graph, "node".into());
+    let outputs = &graph.get_node_state("node").unwrap().output_slots;
+    assert_eq!(
+        outputs.get_slot("sampler").unwrap().slot_type,
+        SlotType::Sampler
+    );
+    assert_eq!(
+        outputs.get_slot("texture").unwrap().slot_type,
+        SlotType::TextureView
+    );
+}
+
+fn dynamic_offset(offset: u32) -> DynamicOffset {
+    DynamicOffset::new(
+        BufferSize::new(16).unwrap(),
+        DynamicOffsetStrategy::Static(offset),
+    )
+}
+
+#[test]
+fn dynamic_offsets_require_explicit_layout() {
+    let result = node_builder()
+        .bind_resource()
+        .name("instances")
+        .binding(0)
+        .dynamic_offset(dynamic_offset(256))
+        .input()
+        .buffer()
+        .add()
+        .build();
+    assert!(matches!(
+        result,
+        Err(builder::BuilderError::ValidationError(_))
+    ));
+
+    let result = node_builder()
+        .bind_resource()
+        .name("output")
+        .dynamic_offset(dynamic_offset(0))
+        .output()
+        .build_buffer("output", 16, BufferUsages::STORAGE, false)
+        .add()
+        .build();
+    assert!(matches!(
+        result,
+        Err(builder::BuilderError::ValidationError(_))
+    ));
+}
+
+#[test]
+fn dynamic_offsets_from_graph_context_are_passed_to_set_bind_group() {
+    let Some(mut app) = common::gpu_app() else {
+        return;
+    };
+    let shader = common::add_shader(
+        &mut app,
+        r#"
+@group(0) @binding(0) var<storage, read> instances: array<u32, 4>;
+@group(0) @binding(1) var<storage, read_write> output: array<u32, 4>;
+
+@compute @workgroup_size(4)
+fn main(@builtin(local_invocation_index) index: u32) {
+    output[index] = instances[index];
+}
+"#,
+    );
+    let offset = 256;
+    let node = node_builder()
+        .shader(shader)
+        .derive_layout()
+        .bind_resource()
+        .name("instances")
+        .binding(0)
+        .read_only()
+        .dynamic_offset(DynamicOffset::new(
+            BufferSize::new(16).unwrap(),
+            DynamicOffsetStrategy::from_graph_context(move |_| offset),
+        ))
+        .input()
+        .buffer()
+        .add()
+        .bind_resource()
+        .name("output")
+        .binding(1)
+        .output()
+        .build_buffer(
+            "output",
+            16,
+            BufferUsages::STORAGE | BufferUsages::COPY_SRC,
+            false,
+        )
+        .add()
+        .build()
+        .unwrap();
+    let instances = input::BufferVecNode::<u32>::new(BufferUsages::STORAGE);
+    for value in 0..128 {
+        instances.push(value);
+    }
+    let output = output::OutputBuffer::default();
+    app.world.spawn(output.clone());
+    let node_entity = app.world.spawn(node.clone()).id();
+    let sub_graph = builder::SubGraphBuilder::default()
+        .name("dynamic_offsets".into())
+        .add_node("instances", instances)
+        .add_node_provider("node".into(), node_entity, &node)
+        .add_node("output", output.clone())
+        .add_node_edge(RenderGraph::INPUT_NODE_NAME, "instances")
+        .add_slot_edge("instances", input::SLOT_NAME, "node", "instances")
+        .add_slot_edge("node", "output", "output", output::SLOT_NAME)
+        .trigger(graph::SubGraphTrigger::once())
+        .build()
+        .unwrap();
+    app.world.spawn(sub_graph);
+    app.finish();
+    app.cleanup();
+
+    for _ in 0..100 {
+        app.update();
+        if output.buffer_ready() {
+            break;
+        }
+    }
+    assert_eq!(
+        node.dispatched_dynamic_offsets().group(0),
+        Some(vec![offset])
+    );
+    assert_eq!(output.take_pod_vec::<u32>().unwrap(), vec![64, 65, 66, 67]);
+}
+
+#[test]
+fn fraction_of_max_workgroups_is_clamped() {
+    assert_eq!(
+        DispatchWorkgroupsStrategy::fraction_of_max_workgroups((0.5, 0.0, 2.0), 65535),
+        (32767, 1, 65535)
+    );
+    assert_eq!(
+        DispatchWorkgroupsStrategy::fraction_of_max_workgroups((f32::NAN, -1.0, 0.25), 0),
+        (1, 1, 1)
+    );
+}
+
+#[test]
+fn entity_bind_resources_are_rejected() {
+    let result = node_builder()
+        .bind_resource()
+        .name("camera")
+        .input()
+        .entity()
+        .add()
+        .build();
+    assert!(matches!(
+        result,
+        Err(builder::BuilderError::ValidationError(_))
+    ));
+}
+
+#[test]
+fn output_buffer_without_storage_usage_is_rejected() {
+    let with_usage = |usage| {
+        node_builder()
+            .bind_resource()
+            .name("output")
+            .output()
+            .build_buffer("output", 16, usage, false)
+            .add()
+            .build()
+    };
+
+    assert!(with_usage(BufferUsages::STORAGE | BufferUsages::COPY_SRC).is_ok());
+    match with_usage(BufferUsages::COPY_SRC) {
+        Err(builder::BuilderError::ValidationError(message)) => {
+            assert!(message.contains("`output`"));
+            assert!(message.contains("STORAGE"));
+        }
+        other => panic!("Expected validation error, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn additional_passes_require_bind_group_layout() {
+    let result = node_builder()
+        .add_pass("finalize", DispatchWorkgroupsStrategy::Static(1, 1, 1))
+        .build();
+
+    assert!(matches!(
+        result,
+        Err(builder::BuilderError::ValidationError(_))
+    ));
+}
+
+#[test]
+fn dispatched_workgroups_are_empty_before_first_run() {
+    let node = node_builder().build().unwrap();
+    let extracted = node.clone();
+
+    assert!(node.dispatched_workgroups().snapshot().is_empty());
+    assert_eq!(extracted.dispatched_workgroups().main(), None);
+}
+
+#[test]
+fn derived_layout_entries_follow_bind_resources() {
+    let node = node_builder()
+        .derive_layout()
+        .bind_resource()
+        .name("input")
+        .binding(0)
+        .read_only()
+        .input()
+        .buffer()
+        .add()
+        .bind_resource()
+        .name("sampler")
+        .group(1)
+        .binding(2)
+        .input()
+        .sampler()
+        .add()
+        .build()
+        .unwrap();
+
+    assert_eq!(node.layout_entries.len(), 2);
+    assert!(matches!(
+        node.layout_entries[0][0].ty,
+        BindingType::Buffer {
+            ty: BufferBindingType::Storage { read_only: true },
+            ..
+        }
+    ));
+    assert_eq!(node.layout_entries[1][0].binding, 2);
+    assert!(matches!(
+        node.layout_entries[1][0].ty,
+        BindingType::Sampler(_)
+    ));
+}
+
+#[test]
+fn derived_layout_rejects_shared_binding() {
+    let result = node_builder()
+        .derive_layout()
+        .bind_resource()
+        .name("first")
+        .binding(0)
+        .input()
+        .buffer()
+        .add()
+        .bind_resource()
+        .name("second")
+        .binding(0)
+        .input()
+        .buffer()
+        .add()
+        .build();
+
+    assert!(matches!(
+        result,
+        Err(builder::BuilderError::ValidationError(message)) if message.contains("`second`")
+    ));
+}
+
+#[test]
+fn clear_before_dispatch_is_declared_per_output_buffer() {
+    let with_clear = |usage| {
+        node_builder()
+            .bind_resource()
+            .name("histogram")
+            .clear_before_dispatch()
+            .output()
+            .build_buffer("histogram", 256, usage, false)
+            .add()
+            .build()
+    };
+
+    let node = with_clear(BufferUsages::STORAGE | BufferUsages::COPY_DST).unwrap();
+    assert!(node.binding_resource_info[0].clear_before_dispatch);
+    assert!(matches!(
+        with_clear(BufferUsages::STORAGE),
+        Err(builder::BuilderError::ValidationError(_))
+    ));
+
+    let sampler = node_builder()
+        .bind_resource()
+        .name("sampler")
+        .clear_before_dispatch()
+        .input()
+        .sampler()
+        .add()
+        .build();
+    assert!(matches!(
+        sampler,
+        Err(builder::BuilderError::ValidationError(message)) if message == "Only buffers can be cleared"
+    ));
+}
+
+#[test]
+fn cleared_output_buffer_is_zeroed_before_every_run() {
+    let Some(mut app) = common::gpu_app() else {
+        return;
+    };
+    let shader = common::add_shader(
+        &mut app,
+        r#"
+@group(0) @binding(0) var<storage, read_write> counters: array<u32>;
+
+@compute @workgroup_size(1)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    counters[id.x] = counters[id.x] + 1u;
+}
+"#,
+    );
+    let node = node_builder()
+        .shader(shader)
+        .dispatch_workgroups_strategy(DispatchWorkgroupsStrategy::Static(3, 1, 1))
+        .bind_resource()
+        .name("counters")
+        .binding(0)
+        .clear_before_dispatch()
+        .output()
+        .build_buffer(
+            "counters",
+            12,
+            BufferUsages::STORAGE | BufferUsages::COPY_DST | BufferUsages::COPY_SRC,
+            false,
+        )
+        .add()
+        .build()
+        .unwrap();
+    let output = output::OutputBuffer::default();
+    app.world.spawn(output.clone());
+    let node_entity = app.world.spawn(node.clone()).id();
+    let request = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let sub_graph = builder::SubGraphBuilder::default()
+        .name("cleared_counters".into())
+        .add_node_provider("node".into(), node_entity, &node)
+        .add_node("output", output.clone())
+        .add_slot_edge("node", "counters", "output", output::SLOT_NAME)
+        .trigger(graph::SubGraphTrigger::Manual(request.clone()))
+        .build()
+        .unwrap();
+    app.world.spawn(sub_graph);
+    app.finish();
+    app.cleanup();
+
+    for _ in 0..2 {
+        request.store(true, std::sync::atomic::Ordering::Relaxed);
+        let mut counters = None;
+        for _ in 0..100 {
+            app.update();
+            if output.buffer_ready() {
+                counters = Some(output.take_pod_vec::<u32>().unwrap());
+                break;
+            }
+        }
+        assert_eq!(counters, Some(vec![1, 1, 1]));
+    }
+}
+
+#[test]
+fn cleared_output_buffer_is_zeroed_before_every_render_pass() {
+    let Some(mut app) = common::gpu_app() else {
+        return;
+    };
+    let shader = common::add_shader(
+        &mut app,
+        r#"
+@group(0) @binding(0) var<storage, read_write> fragments: array<atomic<u32>>;
+
+@vertex
+fn vertex(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32(index >> 1u), f32(index & 1u)) * 2.0;
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+@fragment
+fn fragment() -> @location(0) vec4<f32> {
+    atomicAdd(&fragments[0], 1u);
+    return vec4<f32>(1.0);
+}
+"#,
+    );
+    let mut target = Image::new_fill(
+        Extent3d::default(),
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8Unorm,
+    );
+    target.texture_descriptor.usage =
+        TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST;
+    let target = app.world.resource_mut::<Assets<Image>>().add(target);
+    // The fullscreen vertex shader is loaded by the core pipeline plugin, that is not added
+    let node = builder::RenderNodeBuilder::default()
+        .vertex(bevy_render::render_resource::VertexState {
+            shader: shader.clone(),
+            shader_defs: vec![],
+            entry_point: "vertex".into(),
+            buffers: vec![],
+        })
+        .fragment_shader(shader)
+        .fragment_entry_point("fragment")
+        .target_format(TextureFormat::Rgba8Unorm)
+        .target_slot("target")
+        .bind_resource()
+        .name("fragments")
+        .binding(0)
+        .clear_before_dispatch()
+        .output()
+        .build_buffer(
+            "fragments",
+            4,
+            BufferUsages::STORAGE | BufferUsages::COPY_DST | BufferUsages::COPY_SRC,
+            false,
+        )
+        .add()
+        .build()
+        .unwrap();
+    let output = output::OutputBuffer::default();
+    app.world.spawn(output.clone());
+    let node_entity = app.world.spawn(node.clone()).id();
+    let request = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let sub_graph = builder::SubGraphBuilder::default()
+        .name("cleared_fragments".into())
+        .add_node("target", input::InputTextureNode::from_image(target))
+        .add_node_provider("node".into(), node_entity, &node)
+        .add_node("output", output.clone())
+        .add_slot_edge("target", input::SLOT_NAME, "node", "target")
+        .add_slot_edge("node", "fragments", "output", output::SLOT_NAME)
+        .trigger(graph::SubGraphTrigger::Manual(request.clone()))
+        .build()
+        .unwrap();
+    app.world.spawn(sub_graph);
+    app.finish();
+    app.cleanup();
+
+    for _ in 0..2 {
+        request.store(true, std::sync::atomic::Ordering::Relaxed);
+        let mut fragments = None;
+        for _ in 0..100 {
+            app.update();
+            if output.buffer_ready() {
+                fragments = Some(output.take_pod_vec::<u32>().unwrap());
+                break;
+            }
+        }
+        assert_eq!(fragments, Some(vec![1]));
+    }
+}
+
+#[test]
+fn graph_context_strategies_accept_capturing_closures() {
+    let scale = 4u64;
+    let node = builder::ComputeNodeBuilder::default()
+        .shader(Handle::default())
+        .entry_point("main")
+        .dispatch_workgroups_from_graph_context(move |_| (scale as u32, 1, 1))
+        .bind_resource()
+        .name("scaled")
+        .binding(0)
+        .output()
+        .buffer_from_graph_context(move |_| bevy_render::render_resource::BufferDescriptor {
+            label: None,
+            size: 16 * scale,
+            usage: BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        })
+        .add()
+        .build()
+        .unwrap();
+    assert!(matches!(
+        node.dispatch_workgroups_strategy,
+        DispatchWorkgroupsStrategy::FromGraphContext(_)
+    ));
+
+    let bytes = scale.to_ne_bytes().to_vec();
+    let node = node_builder()
+        .push_constant_ranges(vec![bevy_render::render_resource::PushConstantRange {
+            stages: ShaderStages::COMPUTE,
+            range: 0..8,
+        }])
+        .push_constants_strategy(PushConstantsStrategy::from_graph_context(move |_| {
+            bytes.clone()
+        }))
+        .build()
+        .unwrap();
+    assert!(matches!(
+        node.push_constants_strategy,
+        Some(PushConstantsStrategy::FromGraphContext(_))
+    ));
+
+    let f = GraphContextFn::new(move |_| scale);
+    assert_eq!(f.clone(), f);
+    assert_ne!(GraphContextFn::new(move |_| scale), f);
+}
+
+#[test]
+fn transient_lifetime_is_declared_per_output() {
+    let usages = BufferUsages::STORAGE | BufferUsages::COPY_SRC;
+    let node = node_builder()
+        .bind_resource()
+        .name("accumulator")
+        .group(0)
+        .output()
+        .build_buffer("accumulator", 16, usages, false)
+        .add()
+        .bind_resource()
+        .name("scratch")
+        .group(1)
+        .transient()
+        .output()
+        .build_buffer("scratch", 16, usages, false)
+        .add()
+        .build()
+        .unwrap();
+    assert_eq!(
+        node.binding_resource_info[0].lifetime,
+        ResourceLifetime::Persistent
+    );
+    assert_eq!(
+        node.binding_resource_info[1].lifetime,
+        ResourceLifetime::Transient
+    );
+
+    let transient_input = node_builder()
+        .bind_resource()
+        .name("input")
+        .transient()
+        .input()
+        .buffer()
+        .add()
+        .build();
+    assert!(matches!(
+        transient_input,
+        Err(builder::BuilderError::ValidationError(_))
+    ));
+}
+
+#[test]
+fn static_dispatch_coverage_issues() {
+    let coverage = DispatchWorkgroupsStrategy::coverage_issue;
+    assert_eq!(coverage((4, 1, 1), (64, 1, 1), (256, 1, 1)), None);
+    assert_eq!(coverage((5, 1, 1), (64, 1, 1), (257, 1, 1)), None);
+
+    let under = coverage((4, 1, 1), (64, 1, 1), (257, 1, 1)).unwrap();
+    assert!(under.contains("cover 256 of 257 elements"), "{}", under);
+    let over = coverage((1, 8, 1), (1, 8, 1), (1, 16, 1)).unwrap();
+    assert!(over.starts_with("y: 8 workgroups"), "{}", over);
+    assert!(over.contains("2 workgroups are enough"), "{}", over);
+
+    // The validation only warns, the node is still built
+    node_builder()
+        .expected_coverage((64, 1, 1), (1000, 1, 1))
+        .build()
+        .unwrap();
+}
+
+#[test]
+fn reflect_workgroup_size_of_entry_points() {
+    let source = r#"
+@group(0) @binding(0) var texture: texture_storage_2d<rgba8unorm, write>;
+
+@compute @workgroup_size(8, 8)
+fn init(@builtin(global_invocation_id) id: vec3<u32>) {}
+
+@compute
+@workgroup_size( 64u )
+fn update_main(@builtin(global_invocation_id) id: vec3<u32>) {}
+
+fn update(x: u32) -> u32 { return x; }
+
+/*
+@compute @workgroup_size(4)
+fn commented() {}
+*/
+"#;
+    let reflect = bevy_node_plumber::node::reflect_workgroup_size;
+    assert_eq!(reflect(source, "init"), Some((8, 8, 1)));
+    assert_eq!(reflect(source, "update_main"), Some((64, 1, 1)));
+    assert_eq!(reflect(source, "update"), None);
+    assert_eq!(reflect(source, "commented"), None);
+    assert_eq!(reflect(source, "missing"), None);
+
+    assert_eq!(
+        DispatchWorkgroupsStrategy::cover_extent((640, 481, 1), (8, 8, 1)),
+        (80, 61, 1)
+    );
+}
+
+#[test]
+fn shader_reflection_composes_imports_and_shader_defs() {
+    use bevy_node_plumber::node::reflect::{ShaderReflection, ShaderReflectionError};
+
+    let mut shaders = Assets::<Shader>::default();
+    let import = shaders.add(Shader::from_wgsl(
+        r#"
+#define_import_path plumber::index
+
+fn linear_index(id: vec3<u32>) -> u32 {
+    return id.y * 1024u + id.x;
+}
+"#,
+        "index.wgsl",
+    ));
+    let shader = shaders.add(Shader::from_wgsl(
+        r#"
+#import plumber::index::linear_index
+
+/* @compute @workgroup_size(1)
+fn main() {} */
+
+#ifdef WIDE_WORKGROUPS
+@compute @workgroup_size(128)
+#else
+@compute @workgroup_size(32, 2)
+#endif
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = linear_index(id);
+}
+"#,
+        "main.wgsl",
+    ));
+
+    let reflect = |shader_defs: &[ShaderDefVal]| {
+        ShaderReflection::from_shader(&shaders, &shader, shader_defs)
+            .unwrap()
+            .workgroup_size("main")
+    };
+    assert_eq!(reflect(&[]), Some((32, 2, 1)));
+    assert_eq!(reflect(&["WIDE_WORKGROUPS".into()]), Some((128, 1, 1)));
+
+    shaders.remove(&import);
+    assert!(matches!(
+        ShaderReflection::from_shader(&shaders, &shader, &[]),
+        Err(ShaderReflectionError::NotLoaded(_))
+    ));
+}
+
+#[test]
+fn missing_input_mode_is_set_on_compute_node() {
+    assert_eq!(
+        node_builder().build().unwrap().missing_input_mode,
+        MissingInputMode::Error
+    );
+    let mode = MissingInputMode::Placeholder { buffer_size: 64 };
+    let node = node_builder().missing_input_mode(mode).build().unwrap();
+    assert_eq!(node.missing_input_mode, mode);
+}
+
+#[test]
+fn placeholder_buffer_size_is_validated() {
+    for buffer_size in [0, 6] {
+        let result = node_builder()
+            .missing_input_mode(MissingInputMode::Placeholder { buffer_size })
+            .build();
+        assert!(
+            matches!(&result, Err(builder::BuilderError::ValidationError(message)) if message.contains(&buffer_size.to_string())),
+            "{:?}",
+            result.err()
+        );
+    }
+}
+
+/// Deploys the chain node with its `input` left unconnected, the sub graph is never triggered.
+fn deploy_unconnected_chain_node(name: &'static str, mode: MissingInputMode) -> Option<App> {
+    let mut app = common::gpu_app()?;
+    let shader = common::add_shader(&mut app, common::CHAIN_SHADER);
+    let mut node = common::chain_node(&shader, "plus_one");
+    node.missing_input_mode = mode;
+    let provider_entity = app.world.spawn(node.clone()).id();
+    let sub_graph = builder::SubGraphBuilder::default()
+        .name(name.into())
+        .trigger(graph::SubGraphTrigger::Manual(default()))
+        .add_node_provider("node".into(), provider_entity, &node)
+        .build()
+        .unwrap();
+    app.world.spawn(sub_graph);
+    app.finish();
+    app.cleanup();
+    for _ in 0..100 {
+        if common::is_deployed(&app, name) {
+            return Some(app);
+        }
+        app.update();
+    }
+    panic!("Sub graph {name} is not deployed");
+}
+
+/// Runs the deployed node with an Entity value in its buffer input, the value cannot be bound
+/// and the input is handled as missing. Returns the run result and the read back output.
+fn run_with_entity_input(
+    app: &App,
+    name: &str,
+) -> (Result<(), render_graph::NodeRunError>, Option<Vec<u32>>) {
+    let world = &app.sub_app(RenderApp).world;
+    let render_device = world.resource::<RenderDevice>();
+    let render_queue = world.resource::<RenderQueue>();
+    let sub_graph = world.resource::<RenderGraph>().get_sub_graph(name).unwrap();
+    let node_state = sub_graph.get_node_state("node").unwrap();
+    let inputs = [render_graph::SlotValue::Entity(Entity::PLACEHOLDER)];
+    let mut outputs = vec![None; node_state.output_slots.len()];
+    let mut render_context = RenderContext::new(render_device.clone());
+    let result = node_state.node.run(
+        &mut render_graph::RenderGraphContext::new(sub_graph, node_state, &inputs, &mut outputs),
+        &mut render_context,
+        world,
+    );
+    render_queue.submit(render_context.finish());
+    let Some(render_graph::SlotValue::Buffer(output)) = outputs[0].take() else {
+        return (result, None);
+    };
+
+    let staging = render_device.create_buffer(&bevy_render::render_resource::BufferDescriptor {
+        label: Some("staging"),
+        size: output.size(),
+        usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let mut encoder = render_device.create_command_encoder(&default());
+    encoder.copy_buffer_to_buffer(&output, 0, &staging, 0, output.size());
+    render_queue.submit([encoder.finish()]);
+    staging
+        .slice(..)
+        .map_async(wgpu::MapMode::Read, |result| result.unwrap());
+    render_device.wgpu_device().poll(wgpu::Maintain::Wait);
+    let values = bevy::core::cast_slice(&staging.slice(..).get_mapped_range()).to_vec();
+    (result, Some(values))
+}
+
+#[test]
+fn missing_input_fails_the_node_run() {
+    let Some(app) = deploy_unconnected_chain_node("missing_input_error", MissingInputMode::Error)
+    else {
+        return;
+    };
+    let (result, output) = run_with_entity_input(&app, "missing_input_error");
+    assert!(matches!(
+        result,
+        Err(render_graph::NodeRunError::InputSlotError(
+            render_graph::InputSlotError::InvalidSlot(render_graph::SlotLabel::Name(name))
+        )) if name == "input"
+    ));
+    assert_eq!(output, None);
+}
+
+#[test]
+fn missing_input_is_bound_to_placeholder() {
+    let Some(app) = deploy_unconnected_chain_node(
+        "missing_input_placeholder",
+        MissingInputMode::Placeholder { buffer_size: 12 },
+    ) else {
+        return;
+    };
+    for _ in 0..2 {
+        let (result, output) = run_with_entity_input(&app, "missing_input_placeholder");
+        assert!(result.is_ok(), "{:?}", result);
+        // The placeholder is zero-initialized
+        assert_eq!(output, Some(vec![1, 1, 1]));
+    }
+}
+
+#[test]
+fn shared_layout_is_reused_by_nodes() {
+    let shared_layout = SharedBindGroupLayout::new(Some("shared".into()), vec![vec![]]);
+    let first = node_builder()
+        .shared_layout(shared_layout.clone())
+        .add_pass("finalize", DispatchWorkgroupsStrategy::Static(1, 1, 1))
+        .build()
+        .unwrap();
+    let second = node_builder()
+        .shared_layout(shared_layout.clone())
+        .build()
+        .unwrap();
+    assert!(first.shared_layout.is_some());
+    assert!(second.shared_layout.is_some());
+    assert!(shared_layout.layouts().is_none());
+
+    let result = node_builder()
+        .shared_layout(shared_layout)
+        .derive_layout()
+        .build();
+    assert!(matches!(
+        result,
+        Err(builder::BuilderError::ValidationError(_))
+    ));
+}
+
+#[test]
+fn shared_layout_is_set_on_nodes_by_update() {
+    let Some(mut app) = common::gpu_app() else {
+        return;
+    };
+    app.finish();
+    app.cleanup();
+    let shared_layout = SharedBindGroupLayout::new(Some("shared".into()), vec![vec![]]);
+    let mut first = node_builder()
+        .shared_layout(shared_layout.clone())
+        .build()
+        .unwrap();
+    let mut second = node_builder()
+        .shared_layout(shared_layout.clone())
+        .build()
+        .unwrap();
+
+    let render_world = &mut app.sub_app_mut(RenderApp).world;
+    first.update(render_world);
+    second.update(render_world);
+
+    let layouts = shared_layout
+        .layouts()
+        .expect("Created by the first update");
+    assert_eq!(layouts.len(), 1);
+    assert_eq!(first.pipeline_descriptor.layout, layouts);
+    assert_eq!(second.pipeline_descriptor.layout, layouts);
+}
+
+#[test]
+fn builder_with_shader_defs_overrides_only_defs() {
+    let base = node_builder()
+        .label("simulation")
+        .bind_resource()
+        .name("values")
+        .binding(0)
+        .input()
+        .buffer()
+        .add();
+    let small = base
+        .with_shader_defs(vec![ShaderDefVal::UInt("WORKGROUP_SIZE".into(), 64)])
+        .build()
+        .unwrap();
+    let large = base
+        .with_shader_defs(vec![ShaderDefVal::UInt("WORKGROUP_SIZE".into(), 256)])
+        .build()
+        .unwrap();
+
+    assert_ne!(
+        small.pipeline_descriptor.shader_defs,
+        large.pipeline_descriptor.shader_defs
+    );
+    assert_eq!(
+        small.pipeline_descriptor.entry_point,
+        large.pipeline_descriptor.entry_point
+    );
+    assert_eq!(small.binding_resource_info, large.binding_resource_info);
+    assert_eq!(small.label, large.label);
+    assert!(base
+        .build()
+        .unwrap()
+        .pipeline_descriptor
+        .shader_defs
+        .is_empty());
+}
+
+#[test]
+fn builders_with_shader_defs_queue_distinct_pipelines() {
+    let Some(mut app) = common::gpu_app() else {
+        return;
+    };
+    app.finish();
+    app.cleanup();
+    let base = node_builder().label("simulation");
+    let defs = [
+        vec![ShaderDefVal::UInt("WORKGROUP_SIZE".into(), 64)],
+        vec![ShaderDefVal::UInt("WORKGROUP_SIZE".into(), 256)],
+    ];
+    let mut pipeline_cache = app
+        .sub_app_mut(RenderApp)
+        .world
+        .resource_mut::<PipelineCache>();
+    let ids: Vec<CachedComputePipelineId> = defs
+        .iter()
+        .map(|defs| {
+            let node = base.with_shader_defs(defs.clone()).build().unwrap();
+            pipeline_cache.queue_compute_pipeline(node.pipeline_descriptor)
+        })
+        .collect();
+    // The queued pipelines are inserted into the cache by the processing
+    pipeline_cache.process_queue();
+
+    assert_ne!(ids[0], ids[1]);
+    for (id, defs) in ids.iter().zip(&defs) {
+        assert_eq!(
+            &pipeline_cache
+                .get_compute_pipeline_descriptor(*id)
+                .shader_defs,
+            defs
+        );
+    }
+}
+
+#[test]
+fn cloned_builder_keeps_bind_resource_errors() {
+    let base = node_builder()
+        .bind_resource()
+        .binding(0)
+        .input()
+        .buffer()
+        .add();
+    let errors = base.clone().build_all().unwrap_err();
+    assert!(matches!(
+        errors[..],
+        [builder::BuilderError::ValueNotDefined("name")]
+    ));
+    assert!(matches!(
+        base.with_shader_defs(vec![]).build(),
+        Err(builder::BuilderError::ValueNotDefined("name"))
+    ));
+}
+
+#[test]
+fn build_all_reports_every_error() {
+    let errors = builder::ComputeNodeBuilder::default()
+        .bind_resource()
+        .name("first")
+        .binding(0)
+        .input()
+        .buffer()
+        .add()
+        .bind_resource()
+        .name("second")
+        .binding(0)
+        .input()
+        .buffer()
+        .add()
+        .bind_resource()
+        .binding(1)
+        .input()
+        .buffer()
+        .add()
+        .build_all()
+        .unwrap_err();
+
+    let messages: Vec<String> = errors.iter().map(ToString::to_string).collect();
+    assert_eq!(messages.len(), 5, "{:?}", messages);
+    assert!(messages.iter().any(|m| m.contains("`name`")));
+    assert!(messages.iter().any(|m| m.contains("`second`")));
+    for value in ["shader", "entry_point", "dispatch_workgroups_strategy"] {
+        assert!(messages.iter().any(|m| m.contains(&format!("`{}`", value))));
+    }
+
+    assert!(node_builder().build_all().is_ok());
+}
+
+#[test]
+fn read_only_shared_buffer_layout_entry() {
+    let shared = SharedBindings::new(0)
+        .add_read_only_buffer("globals", 0, "globals_node", input::SLOT_NAME)
+        .add(
+            "counters",
+            1,
+            SlotType::Buffer,
+            "counters_node",
+            input::SLOT_NAME,
+        );
+    let node = node_builder()
+        .shared_bindings(&shared)
+        .derive_layout()
+        .build()
+        .unwrap();
+
+    let read_only: Vec<bool> = node.layout_entries[0]
+        .iter()
+        .map(|entry| match entry.ty {
+            BindingType::Buffer {
+                ty: BufferBindingType::Storage { read_only },
+                ..
+            } => read_only,
+            _ => panic!("Expected storage buffer, got {:?}", entry.ty),
+        })
+        .collect();
+    assert_eq!(read_only, vec![true, false]);
+}
+
+#[test]
+fn layout_mismatches_against_reflected_shader() {
+    let source = r#"
+struct Params { scale: f32 }
+
+@group(0) @binding(0) var<storage> positions: array<vec4<f32>>;
+// @group(0) @binding(5) var<storage> commented: array<u32>;
+@group(0) @binding(1) var<storage, read_write> output: array<u32>;
+@group(0) @binding(2) var<uniform> params: Params;
+@group(1) @binding(0) var image: texture_storage_2d<rgba8unorm, write>;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    var index: u32 = id.x;
+}
+"#;
+    let mut shaders = Assets::<Shader>::default();
+    let shader = shaders.add(Shader::from_wgsl(source, "layout_mismatches.wgsl"));
+
+    let entries = bevy_node_plumber::node::reflect_layout_entries(source).unwrap();
+    let summary: Vec<_> = entries
+        .iter()
+        .map(|entry| {
+            (
+                entry.group,
+                entry.binding,
+                entry.name.as_deref(),
+                entry.kind,
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (
+                0,
+                0,
+                Some("positions"),
+                ReflectedBindingKind::StorageBuffer { read_only: true }
+            ),
+            (
+                0,
+                1,
+                Some("output"),
+                ReflectedBindingKind::StorageBuffer { read_only: false }
+            ),
+            (0, 2, Some("params"), ReflectedBindingKind::UniformBuffer),
+            (1, 0, Some("image"), ReflectedBindingKind::StorageTexture),
+        ]
+    );
+
+    let node = node_builder()
+        .shader(shader.clone())
+        .bind_resource()
+        .name("positions")
+        .binding(0)
+        .read_only()
+        .input()
+        .buffer()
+        .add()
+        .bind_resource()
+        .name("output")
+        .binding(1)
+        .input_output()
+        .buffer()
+        .add()
+        .bind_resource()
+        .name("params")
+        .binding(2)
+        .input()
+        .buffer()
+        .add()
+        .bind_resource()
+        .name("image")
+        .group(1)
+        .binding(0)
+        .input()
+        .texture_view()
+        .add()
+        .build()
+        .unwrap();
+    assert_eq!(node.reflected_layout_entries(&shaders), Some(entries));
+    assert_eq!(node.layout_mismatches(&shaders), Some(vec![]));
+
+    let node = node_builder()
+        .shader(shader)
+        .bind_resource()
+        .name("positions")
+        .binding(0)
+        .input()
+        .buffer()
+        .add()
+        .bind_resource()
+        .name("output")
+        .binding(3)
+        .input_output()
+        .buffer()
+        .add()
+        .bind_resource()
+        .name("params")
+        .binding(2)
+        .input()
+        .sampler()
+        .add()
+        .build()
+        .unwrap();
+    let mismatches = node.layout_mismatches(&shaders).unwrap();
+    assert_eq!(mismatches.len(), 5, "{:?}", mismatches);
+    assert!(mismatches[0].contains("read_only false"));
+    assert!(mismatches[1].contains("\"output\" at group 0 binding 3"));
+    assert!(mismatches[2].contains("Sampler"));
+
+    assert_eq!(
+        node_builder().build().unwrap().layout_mismatches(&shaders),
+        None
+    );
+}
+
+#[test]
+fn bind_resource_errors_keep_slot_error_variant() {
+    use bevy_render::render_graph::{InputSlotError, NodeRunError, OutputSlotError, SlotLabel};
+
+    let err = BindResourceError::MissingIndex { index: 3, count: 2 };
+    assert!(err.to_string().contains("index 3"));
+    assert!(matches!(
+        NodeRunError::from(err),
+        NodeRunError::OutputSlotError(OutputSlotError::InvalidSlot(SlotLabel::Index(3)))
+    ));
+
+    let err = BindResourceError::NotOutput {
+        name: "positions".into(),
+        direction: "Input",
+    };
+    assert!(err.to_string().contains("`positions` is not an output"));
+    assert!(matches!(
+        NodeRunError::from(err),
+        NodeRunError::OutputSlotError(OutputSlotError::InvalidSlot(SlotLabel::Name(name))) if name == "positions"
+    ));
+
+    let err = BindResourceError::MissingInput {
+        name: "params".into(),
+        node: Some("node".to_string()),
+        expected: vec!["params".into()],
+        available: vec![],
+    };
+    assert!(matches!(
+        NodeRunError::from(err),
+        NodeRunError::InputSlotError(InputSlotError::InvalidSlot(SlotLabel::Name(name))) if name == "params"
+    ));
+
+    let err = BindResourceError::Poisoned("Node resources");
+    assert!(matches!(
+        NodeRunError::from(err),
+        NodeRunError::InputSlotError(InputSlotError::InvalidSlot(SlotLabel::Name(name))) if name == "<poisoned Node resources mutex>"
+    ));
+
+    let err = BindResourceError::from(InputSlotError::InvalidSlot(0.into()));
+    assert!(matches!(
+        NodeRunError::from(err),
+        NodeRunError::InputSlotError(InputSlotError::InvalidSlot(SlotLabel::Index(0)))
+    ));
+}
+
+#[test]
+fn copy_src_is_added_to_output_buffer() {
+    let mut node = node_builder()
+        .bind_resource()
+        .name("result")
+        .binding(0)
+        .output()
+        .build_buffer("result", 64, BufferUsages::STORAGE, false)
+        .add()
+        .bind_resource()
+        .name("positions")
+        .binding(1)
+        .input()
+        .buffer()
+        .add()
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        node.add_output_buffer_usages("result", BufferUsages::COPY_SRC),
+        Ok(true)
+    );
+    assert_eq!(
+        node.add_output_buffer_usages("result", BufferUsages::COPY_SRC),
+        Ok(false)
+    );
+    let BindResourceDirection::Output(BindResourceCreationDescriptor::Buffer(
+        bevy_node_plumber::resource::BindResourceCreationStrategy::Static(descriptor),
+    )) = &node.binding_resource_info[0].direction
+    else {
+        panic!("Output buffer is expected");
+    };
+    assert_eq!(
+        descriptor.usage,
+        BufferUsages::STORAGE | BufferUsages::COPY_SRC
+    );
+    assert_eq!(
+        node.add_output_buffer_usages("positions", BufferUsages::COPY_SRC),
+        Ok(false)
+    );
+    assert!(node
+        .add_output_buffer_usages("missing", BufferUsages::COPY_SRC)
+        .is_err());
+}
+
+#[test]
+fn copy_src_is_added_to_output_texture() {
+    let descriptor = TextureDescriptor {
+        label: Some("image"),
+        size: Extent3d::default(),
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: TextureFormat::Rgba8Unorm,
+        usage: TextureUsages::STORAGE_BINDING,
+        view_formats: &[],
+    };
+    let mut node = node_builder()
+        .bind_resource()
+        .name("image")
+        .binding(0)
+        .output()
+        .storage_texture(
+            descriptor,
+            TextureViewDescriptor::default(),
+            StorageTextureAccess::WriteOnly,
+        )
+        .add()
+        .bind_resource()
+        .name("result")
+        .binding(1)
+        .output()
+        .build_buffer("result", 64, BufferUsages::STORAGE, false)
+        .add()
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        node.add_output_texture_usages("image", TextureUsages::COPY_SRC),
+        Ok(true)
+    );
+    assert_eq!(
+        node.add_output_texture_usages("image", TextureUsages::COPY_SRC),
+        Ok(false)
+    );
+    let BindResourceDirection::Output(BindResourceCreationDescriptor::StorageTexture {
+        texture: bevy_node_plumber::resource::BindResourceCreationStrategy::Static(descriptor),
+        ..
+    }) = &node.binding_resource_info[0].direction
+    else {
+        panic!("Output storage texture is expected");
+    };
+    assert_eq!(
+        descriptor.usage,
+        TextureUsages::STORAGE_BINDING | TextureUsages::COPY_SRC
+    );
+    assert!(node
+        .add_output_texture_usages("result", TextureUsages::COPY_SRC)
+        .is_err());
+}
+
+#[test]
+fn reflected_layout_entries_follow_shader_defs_and_usage() {
+    let source = r#"
+@group(0) @binding(0) var<storage, read_write> values: array<u32>;
+/*
+@group(0) @binding(1) var<storage> commented: array<u32>;
+*/
+#ifdef WITH_SCALE
+@group(0) @binding(2) var<uniform> scale: u32;
+#endif
+@group(1) @binding(0) var<storage> unused: array<u32>;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+#ifdef WITH_SCALE
+    values[id.x] *= scale;
+#else
+    values[id.x] += 1u;
+#endif
+}
+"#;
+    let mut shaders = Assets::<Shader>::default();
+    let shader = shaders.add(Shader::from_wgsl(source, "reflected_layout.wgsl"));
+    let reflected = |shader_defs: Vec<ShaderDefVal>| {
+        node_builder()
+            .shader(shader.clone())
+            .shader_defs(shader_defs)
+            .build()
+            .unwrap()
+            .reflected_layout_entries(&shaders)
+            .unwrap()
+            .into_iter()
+            .map(|entry| (entry.group, entry.binding, entry.visibility))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        reflected(vec![]),
+        vec![(0, 0, ShaderStages::COMPUTE), (1, 0, ShaderStages::NONE)]
+    );
+    assert_eq!(
+        reflected(vec!["WITH_SCALE".into()]),
+        vec![
+            (0, 0, ShaderStages::COMPUTE),
+            (0, 2, ShaderStages::COMPUTE),
+            (1, 0, ShaderStages::NONE)
+        ]
+    );
+}
+
+#[test]
+fn texture_array_is_declared_with_element_count() {
+    let texture = TextureDescriptor {
+        label: Some("layer"),
+        size: Extent3d {
+            width: 8,
+            height: 8,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: TextureFormat::Rgba8Unorm,
+        usage: TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    };
+    let source = r#"
+@group(0) @binding(0) var layers: binding_array<texture_2d<f32>, 3>;
+@group(0) @binding(1) var unsized_layers: binding_array<texture_2d<f32>>;
+"#;
+    let mut shaders = Assets::<Shader>::default();
+    let shader = shaders.add(Shader::from_wgsl(source, "texture_array.wgsl"));
+    let entries = bevy_node_plumber::node::reflect_layout_entries(source).unwrap();
+    assert_eq!(entries[0].kind, ReflectedBindingKind::Texture);
+    assert_eq!(entries[0].count, Some(3));
+    assert_eq!(entries[1].kind, ReflectedBindingKind::Texture);
+    assert_eq!(entries[1].count, None);
+
+    let node_with_layers = |count: usize| {
+        node_builder()
+            .shader(shader.clone())
+            .bind_resource()
+            .name("layers")
+            .binding(0)
+            .output()
+            .texture_array(vec![texture.clone(); count])
+            .add()
+            .bind_resource()
+            .name("unsized_layers")
+            .binding(1)
+            .output()
+            .texture_array(vec![texture.clone(); 2])
+            .add()
+            .build()
+    };
+    let node = node_with_layers(3).unwrap();
+    assert_eq!(
+        node.binding_resource_info[0].direction.clone(),
+        BindResourceDirection::Output(BindResourceCreationDescriptor::TextureArray(vec![
+            bevy_node_plumber::resource::BindResourceCreationStrategy::Static(
+                texture.clone()
+            );
+            3
+        ]))
+    );
+    let entry = node.binding_resource_info[0]
+        .layout_entry(ShaderStages::COMPUTE)
+        .unwrap();
+    assert_eq!(entry.count, std::num::NonZeroU32::new(3));
+    assert!(matches!(entry.ty, BindingType::Texture { .. }));
+    assert_eq!(node.layout_mismatches(&shaders), Some(vec![]));
+
+    let mismatches = node_with_layers(4)
+        .unwrap()
+        .layout_mismatches(&shaders)
+        .unwrap();
+    assert_eq!(mismatches.len(), 1, "{:?}", mismatches);
+    assert!(mismatches[0].contains("Some(4) elements"));
+
+    assert!(node_with_layers(0).is_err());
+    let result = node_builder()
+        .bind_resource()
+        .name("layers")
+        .output()
+        .texture_array(vec![TextureDescriptor {
+            usage: TextureUsages::STORAGE_BINDING,
+            ..texture
+        }])
+        .add()
+        .build();
+    assert!(result.is_err());
+}
+
+#[test]
+fn bind_group_missing_in_derived_layout_is_an_error() {
+    let Some(mut app) = common::gpu_app() else {
+        return;
+    };
+    let shader = common::add_shader(
+        &mut app,
+        r#"
+@group(0) @binding(0) var<storage, read_write> values: array<u32>;
+
+@compute @workgroup_size(1)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    values[id.x] = id.x;
+}
+"#,
+    );
+    let usages = BufferUsages::STORAGE;
+    let node = node_builder()
+        .shader(shader)
+        .bind_resource()
+        .name("values")
+        .output()
+        .build_buffer("values", 16, usages, false)
+        .add()
+        .bind_resource()
+        .name("unused")
+        .group(1)
+        .output()
+        .build_buffer("unused", 16, usages, false)
+        .add()
+        .build()
+        .unwrap();
+    let provider_entity = common::spawn_single_node_graph(&mut app, "unused_group", node);
+
+    let mut error = None;
+    for _ in 0..20 {
+        app.update();
+        error = app
+            .world
+            .get::<graph::ProviderStatus>(provider_entity)
+            .and_then(|status| status.error.clone());
+        if error.is_some() {
+            break;
+        }
+    }
+    let error = error.expect("Provider error must be mirrored to the main world");
+    assert!(
+        error.contains("Bind group index 1 is not used by the shader"),
+        "{}",
+        error
+    );
+    assert!(!common::is_deployed(&app, "unused_group"));
+}
+
+#[test]
+fn in_place_buffer_matches_manual_builder() {
+    let shader = Handle::<Shader>::weak_from_u128(42);
+    let preset = builder::ComputeNodeBuilder::in_place_buffer::<[f32; 4]>(
+        shader.clone(),
+        "main",
+        "particles",
+        64,
+    )
+    .build()
+    .unwrap();
+    let manual = builder::ComputeNodeBuilder::default()
+        .shader(shader)
+        .entry_point("main")
+        .dispatch_workgroups_strategy(DispatchWorkgroupsStrategy::PerBufferElement {
+            slot: "particles".into(),
+            element_size: 16,
+            workgroup_size: 64,
+        })
+        .bind_resource()
+        .name("particles")
+        .binding(0)
+        .input_output()
+        .buffer()
+        .add()
+        .build()
+        .unwrap();
+
+    assert_eq!(preset.binding_resource_info, manual.binding_resource_info);
+    // The descriptor is not comparable, the debug output contains every field
+    assert_eq!(
+        format!("{:?}", preset.pipeline_descriptor),
+        format!("{:?}", manual.pipeline_descriptor)
+    );
+    assert!(matches!(
+        &preset.dispatch_workgroups_strategy,
+        DispatchWorkgroupsStrategy::PerBufferElement {
+            slot,
+            element_size: 16,
+            workgroup_size: 64,
+        } if slot == "particles"
+    ));
+}
+
+/// Outputs an Entity slot, that is fed to a compute node input declared as Entity.
+struct EntitySourceNode;
+
+impl render_graph::Node for EntitySourceNode {
+    fn output(&self) -> Vec<render_graph::SlotInfo> {
+        vec![render_graph::SlotInfo::new("entity", SlotType::Entity)]
+    }
+
+    fn run(
+        &self,
+        graph: &mut render_graph::RenderGraphContext,
+        _render_context: &mut RenderContext,
+        _world: &World,
+    ) -> Result<(), render_graph::NodeRunError> {
+        graph.set_output("entity", Entity::PLACEHOLDER)?;
+        Ok(())
+    }
+}
+
+#[test]
+fn entity_input_is_reported_as_node_run_error() {
+    let Some(mut app) = common::gpu_app() else {
+        return;
+    };
+    let shader = common::add_shader(&mut app, common::CHAIN_SHADER);
+    let mut node = common::chain_node(&shader, "plus_one");
+    // The builder rejects Entity inputs, the node is patched to get one into `set_bind_group`.
+    node.binding_resource_info[0].direction = BindResourceDirection::Input(SlotType::Entity);
+    let provider_entity = app.world.spawn(node.clone()).id();
+    let sub_graph = builder::SubGraphBuilder::default()
+        .name("entity_input".into())
+        .add_node("entity", EntitySourceNode)
+        .add_node_provider("node".into(), provider_entity, &node)
+        .add_slot_edge("entity", "entity", "node", "input")
+        .build()
+        .unwrap();
+    app.world.spawn(sub_graph);
+    app.finish();
+    app.cleanup();
+
+    let panic = (0..100)
+        .find_map(|_| std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| app.update())).err())
+        .expect("The node run should fail");
+    let message = panic.downcast_ref::<String>().cloned().unwrap_or_default();
+    assert_eq!(
+        message,
+        format!(
+            "Error running render graph: {}",
+            render_graph::NodeRunError::from(render_graph::InputSlotError::InvalidSlot(
+                "input".into()
+            ))
+        )
+    );
+}
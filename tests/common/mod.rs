@@ -0,0 +1,334 @@
+//! Render App with a real render device for the tests that run the nodes on GPU.
+//! A software adapter (e.g. llvmpipe) is enough. The tests fail without an adapter, unless
+//! they are skipped explicitly with `SKIP_GPU_TESTS` environment variable.
+#![allow(dead_code)]
+
+use bevy::prelude::*;
+use bevy::render::renderer::{
+    initialize_renderer, RenderAdapter, RenderAdapterInfo, RenderDevice, RenderInstance,
+    RenderQueue,
+};
+use bevy::render::settings::{RenderCreation, WgpuSettings};
+use bevy::render::RenderPlugin;
+use bevy::window::ExitCondition;
+use bevy_node_plumber::prelude::*;
+use bevy_node_plumber::NodeProvider;
+use bevy_render::render_graph::{
+    Node, NodeRunError, RenderGraph, RenderGraphContext, SlotInfo, SlotType, SlotValue,
+};
+use bevy_render::render_resource::{
+    BufferUsages, Extent3d, StorageTextureAccess, TextureDescriptor, TextureDimension,
+    TextureFormat, TextureUsages, TextureViewDescriptor,
+};
+use bevy_render::renderer::RenderContext;
+use bevy_render::RenderApp;
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+type Renderer = (
+    RenderDevice,
+    RenderQueue,
+    RenderAdapterInfo,
+    RenderAdapter,
+    RenderInstance,
+);
+
+/// The renderer is shared by the tests, the instance is never dropped because
+/// the EGL display is terminated with it for every context in the process.
+fn renderer() -> Option<Renderer> {
+    static RENDERER: OnceLock<Option<Renderer>> = OnceLock::new();
+    RENDERER
+        .get_or_init(|| {
+            let settings = WgpuSettings::default();
+            let backends = settings.backends?;
+            let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+                backends,
+                ..default()
+            });
+            let has_adapter = instance.enumerate_adapters(backends).any(|adapter| {
+                adapter
+                    .get_downlevel_capabilities()
+                    .flags
+                    .contains(wgpu::DownlevelFlags::COMPUTE_SHADERS)
+            });
+            if !has_adapter {
+                return None;
+            }
+            let (device, queue, adapter_info, adapter) =
+                bevy::tasks::block_on(initialize_renderer(
+                    &instance,
+                    &settings,
+                    &wgpu::RequestAdapterOptions {
+                        power_preference: settings.power_preference,
+                        compatible_surface: None,
+                        ..default()
+                    },
+                ));
+            Some((
+                device,
+                queue,
+                adapter_info,
+                adapter,
+                RenderInstance(Arc::new(instance)),
+            ))
+        })
+        .clone()
+}
+
+/// Environment variable that allows to skip the GPU tests on machines without an adapter.
+pub const SKIP_GPU_TESTS: &str = "SKIP_GPU_TESTS";
+
+/// Returns `None` if there is no adapter that supports compute shaders and the GPU tests are
+/// skipped with [`SKIP_GPU_TESTS`], panics otherwise so the test doesn't pass vacuously.
+pub fn gpu_app() -> Option<App> {
+    let Some((device, queue, adapter_info, adapter, instance)) = renderer() else {
+        assert!(
+            std::env::var_os(SKIP_GPU_TESTS).is_some(),
+            "No adapter with compute shaders support, set {} to skip the GPU tests",
+            SKIP_GPU_TESTS
+        );
+        eprintln!("No adapter with compute shaders support, the GPU test is skipped");
+        return None;
+    };
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(AssetPlugin::default())
+        .add_plugins(WindowPlugin {
+            primary_window: None,
+            exit_condition: ExitCondition::DontExit,
+            close_when_requested: false,
+        })
+        .add_plugins(RenderPlugin {
+            render_creation: RenderCreation::Manual(device, queue, adapter_info, adapter, instance),
+        })
+        .add_plugins(ImagePlugin::default())
+        .add_plugins(NodePlumberPlugin);
+    Some(app)
+}
+
+pub fn add_shader(app: &mut App, source: &'static str) -> Handle<Shader> {
+    app.world
+        .resource_mut::<Assets<Shader>>()
+        .add(Shader::from_wgsl(source, "test.wgsl"))
+}
+
+/// Spawns the sub graph with one node provider and finishes the app.
+pub fn spawn_single_node_graph<T: NodeProvider>(
+    app: &mut App,
+    name: &'static str,
+    provider: T,
+) -> Entity {
+    let provider_entity = app.world.spawn(provider.clone()).id();
+    let sub_graph = builder::SubGraphBuilder::default()
+        .name(Cow::Borrowed(name))
+        .add_node_provider("node".into(), provider_entity, &provider)
+        .build()
+        .unwrap();
+    app.world.spawn(sub_graph);
+    app.finish();
+    app.cleanup();
+    provider_entity
+}
+
+pub fn is_deployed(app: &App, name: &str) -> bool {
+    app.sub_app(RenderApp)
+        .world
+        .resource::<RenderGraph>()
+        .get_sub_graph(name)
+        .is_some()
+}
+
+/// Entry points that map the input buffer of 3 elements to the output buffer.
+pub const CHAIN_SHADER: &str = r#"
+@group(0) @binding(0) var<storage, read> input: array<u32>;
+@group(0) @binding(1) var<storage, read_write> output: array<u32>;
+
+@compute @workgroup_size(1)
+fn plus_one(@builtin(global_invocation_id) id: vec3<u32>) {
+    output[id.x] = input[id.x] + 1u;
+}
+
+@compute @workgroup_size(1)
+fn double(@builtin(global_invocation_id) id: vec3<u32>) {
+    output[id.x] = input[id.x] * 2u;
+}
+"#;
+
+pub fn chain_node(shader: &Handle<Shader>, entry_point: &'static str) -> compute::ComputeNode {
+    builder::ComputeNodeBuilder::default()
+        .shader(shader.clone())
+        .entry_point(entry_point)
+        .dispatch_workgroups_strategy(DispatchWorkgroupsStrategy::Static(3, 1, 1))
+        .bind_resource()
+        .name("input")
+        .binding(0)
+        .read_only()
+        .input()
+        .buffer()
+        .add()
+        .bind_resource()
+        .name("output")
+        .binding(1)
+        .output()
+        .build_buffer(
+            entry_point,
+            12,
+            BufferUsages::STORAGE | BufferUsages::COPY_SRC,
+            false,
+        )
+        .add()
+        .build()
+        .unwrap()
+}
+
+/// Writes `gradient_pixel` of each texel into the storage texture.
+pub const GRADIENT_SHADER: &str = r#"
+@group(0) @binding(0) var image: texture_storage_2d<rgba8unorm, write>;
+
+@compute @workgroup_size(1)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    textureStore(image, id.xy, vec4<f32>(f32(id.x), f32(id.y), 7.0, 255.0) / 255.0);
+}
+"#;
+
+pub fn gradient_pixel(x: u8, y: u8) -> [u8; 4] {
+    [x, y, 7, 255]
+}
+
+/// Compute node that fills its `image` output texture of the size.
+pub fn gradient_node(shader: &Handle<Shader>, size: Extent3d) -> compute::ComputeNode {
+    builder::ComputeNodeBuilder::default()
+        .shader(shader.clone())
+        .entry_point("main")
+        .dispatch_workgroups_strategy(DispatchWorkgroupsStrategy::Static(
+            size.width,
+            size.height,
+            1,
+        ))
+        .bind_resource()
+        .name("image")
+        .binding(0)
+        .output()
+        .storage_texture(
+            TextureDescriptor {
+                label: Some("image"),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: TextureFormat::Rgba8Unorm,
+                usage: TextureUsages::STORAGE_BINDING | TextureUsages::COPY_SRC,
+                view_formats: &[],
+            },
+            TextureViewDescriptor::default(),
+            StorageTextureAccess::WriteOnly,
+        )
+        .add()
+        .build()
+        .unwrap()
+}
+
+/// Requests the manually triggered sub graph and returns the values read back by the output.
+pub fn run_and_read(
+    app: &mut App,
+    request: &AtomicBool,
+    output: &output::OutputBuffer,
+) -> Vec<u32> {
+    request.store(true, Ordering::Relaxed);
+    for _ in 0..100 {
+        app.update();
+        if output.buffer_ready() {
+            return output.take_pod_vec().unwrap();
+        }
+    }
+    panic!("Output buffer is not read back");
+}
+
+/// Records the values received by its `in` slot.
+#[derive(Clone)]
+pub struct SlotProbeNode {
+    slot_type: SlotType,
+    pub received: Arc<Mutex<Vec<SlotValue>>>,
+}
+
+impl SlotProbeNode {
+    pub fn new(slot_type: SlotType) -> Self {
+        Self {
+            slot_type,
+            received: default(),
+        }
+    }
+}
+
+impl Node for SlotProbeNode {
+    fn input(&self) -> Vec<SlotInfo> {
+        vec![SlotInfo::new("in", self.slot_type)]
+    }
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        _render_context: &mut RenderContext,
+        _world: &World,
+    ) -> Result<(), NodeRunError> {
+        self.received
+            .lock()
+            .unwrap()
+            .push(graph.get_input(0)?.clone());
+        Ok(())
+    }
+}
+
+/// Runs the sub graph with the `out` slot of the source connected to the probe, and returns
+/// the received values. The render graph panics if the source leaves the output unset.
+pub fn probe_output<T: Node>(
+    app: &mut App,
+    name: &'static str,
+    source: T,
+    slot_type: SlotType,
+) -> Vec<SlotValue> {
+    let sub_graph = builder::SubGraphBuilder::default()
+        .name(Cow::Borrowed(name))
+        .add_node("source", source)
+        .add_node_edge(RenderGraph::INPUT_NODE_NAME, "source");
+    probe_slot(app, sub_graph, "source", "out", slot_type)
+}
+
+/// Runs the sub graph with the slot of the node connected to the probe, and returns the
+/// received values.
+pub fn probe_slot(
+    app: &mut App,
+    sub_graph: builder::SubGraphBuilder,
+    node: &'static str,
+    slot: &'static str,
+    slot_type: SlotType,
+) -> Vec<SlotValue> {
+    let probe = SlotProbeNode::new(slot_type);
+    let sub_graph = sub_graph
+        .add_node("probe", probe.clone())
+        .add_slot_edge(node, slot, "probe", "in")
+        .build()
+        .unwrap();
+    app.world.spawn(sub_graph);
+    app.finish();
+    app.cleanup();
+    for _ in 0..100 {
+        app.update();
+        if probe.received.lock().unwrap().len() >= 2 {
+            break;
+        }
+    }
+    let received = probe.received.lock().unwrap().clone();
+    received
+}
+
+/// View of the `FallbackImage` published by the nodes without a prepared texture.
+pub fn fallback_texture_view(app: &App) -> bevy_render::render_resource::TextureView {
+    app.sub_app(RenderApp)
+        .world
+        .resource::<bevy_render::texture::FallbackImage>()
+        .d2
+        .texture_view
+        .clone()
+}
@@ -0,0 +1,133 @@
+use bevy::prelude::*;
+use bevy_node_plumber::prelude::*;
+use bevy_render::render_graph::{Node, RenderGraph, SlotType, SlotValue};
+use bevy_render::render_resource::{
+    Extent3d, Origin3d, TextureDimension, TextureFormat, TextureUsages,
+};
+use std::borrow::Cow;
+
+mod common;
+
+#[test]
+fn copy_node_slots_match_the_copied_resource() {
+    let buffer_copy = copy::CopyNode::buffer().with_range(0..256);
+    assert_eq!(buffer_copy.input()[0].slot_type, SlotType::Buffer);
+    assert_eq!(buffer_copy.output()[0].slot_type, SlotType::Buffer);
+    assert_eq!(buffer_copy.output()[0].name, copy::OUT_SLOT_NAME);
+
+    let texture_copy = copy::CopyNode::texture(Handle::default());
+    assert_eq!(texture_copy.input()[0].slot_type, SlotType::TextureView);
+    assert_eq!(texture_copy.output()[0].slot_type, SlotType::TextureView);
+    assert_eq!(texture_copy.input()[0].name, copy::SLOT_NAME);
+}
+
+#[test]
+#[should_panic(expected = "must be aligned")]
+fn copy_node_range_must_be_aligned() {
+    let _ = copy::CopyNode::buffer().with_range(0..6);
+}
+
+#[test]
+fn ordering_node_passes_the_slot_type() {
+    let buffer_ordering = ordering::OrderingNode::buffer();
+    assert_eq!(buffer_ordering.input()[0].name, ordering::SLOT_NAME);
+    assert_eq!(buffer_ordering.input()[0].slot_type, SlotType::Buffer);
+    assert_eq!(buffer_ordering.output()[0].name, ordering::OUT_SLOT_NAME);
+    assert_eq!(buffer_ordering.output()[0].slot_type, SlotType::Buffer);
+
+    let view_ordering = ordering::OrderingNode::texture_view();
+    assert_eq!(view_ordering.output()[0].slot_type, SlotType::TextureView);
+}
+
+#[test]
+fn texture_region_is_copied_from_input_view() {
+    let Some(mut app) = common::gpu_app() else {
+        return;
+    };
+    let shader = common::add_shader(&mut app, common::GRADIENT_SHADER);
+    let size = Extent3d {
+        width: 3,
+        height: 2,
+        depth_or_array_layers: 1,
+    };
+    let compute_node = common::gradient_node(&shader, size);
+    let mut destination = Image::new_fill(
+        size,
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8Unorm,
+    );
+    destination.texture_descriptor.usage =
+        TextureUsages::COPY_DST | TextureUsages::COPY_SRC | TextureUsages::TEXTURE_BINDING;
+    let destination = app.world.resource_mut::<Assets<Image>>().add(destination);
+    // Only the columns starting from 1 are copied, the first column keeps zeros
+    let copy_node = copy::CopyNode::texture(destination).with_region(
+        Origin3d { x: 1, y: 0, z: 0 },
+        Extent3d {
+            width: 2,
+            height: 2,
+            depth_or_array_layers: 1,
+        },
+    );
+    let compute_entity = app.world.spawn(compute_node.clone()).id();
+    let output = output::OutputTexture::default();
+    app.world.spawn(output.clone());
+    let sub_graph = builder::SubGraphBuilder::default()
+        .name(Cow::Borrowed("texture_copy"))
+        .add_node_provider("compute".into(), compute_entity, &compute_node)
+        .add_node("copy", copy_node)
+        .add_node("output", output.clone())
+        .add_slot_edge("compute", "image", "copy", copy::SLOT_NAME)
+        .add_slot_edge("copy", copy::OUT_SLOT_NAME, "output", output::SLOT_NAME)
+        .build()
+        .unwrap();
+    app.world.spawn(sub_graph);
+    app.finish();
+    app.cleanup();
+
+    for _ in 0..100 {
+        app.update();
+        if output.image_ready() {
+            break;
+        }
+    }
+    let image = output.take_image().unwrap();
+    let expected: Vec<u8> = (0..2)
+        .flat_map(|y| {
+            (0..3).flat_map(move |x| match x {
+                0 => [0; 4],
+                _ => common::gradient_pixel(x, y),
+            })
+        })
+        .collect();
+    assert_eq!(image.data, expected);
+}
+
+#[test]
+fn skipped_texture_copy_outputs_fallback_view() {
+    let Some(mut app) = common::gpu_app() else {
+        return;
+    };
+    let handles = app.world.resource::<Assets<Image>>().get_handle_provider();
+    let source = input::InputTextureNode::from_image(handles.reserve_handle().typed());
+    let copy_node = copy::CopyNode::texture(handles.reserve_handle().typed());
+    let sub_graph = builder::SubGraphBuilder::default()
+        .name(Cow::Borrowed("skipped_texture_copy"))
+        .add_node("source", source)
+        .add_node("copy", copy_node)
+        .add_node_edge(RenderGraph::INPUT_NODE_NAME, "source")
+        .add_slot_edge("source", input::SLOT_NAME, "copy", copy::SLOT_NAME);
+    let received = common::probe_slot(
+        &mut app,
+        sub_graph,
+        "copy",
+        copy::OUT_SLOT_NAME,
+        SlotType::TextureView,
+    );
+
+    let fallback = common::fallback_texture_view(&app);
+    assert!(!received.is_empty());
+    for value in received {
+        assert!(matches!(value, SlotValue::TextureView(view) if view.id() == fallback.id()));
+    }
+}
@@ -0,0 +1,62 @@
+use bevy_node_plumber::diagnostic;
+use bevy_node_plumber::prelude::*;
+use bevy_render::render_graph::RenderGraph;
+use bevy_render::render_resource::BufferUsages;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+mod common;
+
+#[test]
+fn gpu_memory_usage_is_reported_per_node_between_runs() {
+    let Some(mut app) = common::gpu_app() else {
+        return;
+    };
+    app.add_plugins(diagnostic::GpuMemoryUsagePlugin);
+    let shader = common::add_shader(&mut app, common::CHAIN_SHADER);
+    let mut plus_one = common::chain_node(&shader, "plus_one");
+    plus_one.memoize = true;
+    let plus_one_entity = app.world.spawn(plus_one.clone()).id();
+    let small = input::BufferVecNode::<u32>::new(BufferUsages::STORAGE);
+    let large = input::BufferVecNode::<u32>::new(BufferUsages::STORAGE);
+    for value in 0..3 {
+        small.push(value);
+    }
+    for value in 0..100 {
+        large.push(value);
+    }
+    let request = Arc::new(AtomicBool::new(false));
+    let sub_graph = builder::SubGraphBuilder::default()
+        .name("memory".into())
+        .add_node("small", small)
+        .add_node("large", large)
+        .add_node_provider("plus_one".into(), plus_one_entity, &plus_one)
+        .add_node_edge(RenderGraph::INPUT_NODE_NAME, "small")
+        .add_node_edge(RenderGraph::INPUT_NODE_NAME, "large")
+        .add_slot_edge("small", input::SLOT_NAME, "plus_one", "input")
+        .trigger(graph::SubGraphTrigger::Manual(request.clone()))
+        .build()
+        .unwrap();
+    app.world.spawn(sub_graph);
+    app.finish();
+    app.cleanup();
+    while !common::is_deployed(&app, "memory") {
+        app.update();
+    }
+    app.update();
+    assert_eq!(
+        app.world.resource::<diagnostic::NodeMemoryUsage>().total(),
+        0
+    );
+
+    request.store(true, Ordering::Relaxed);
+    // The nodes run only once, the usage is reported on the following frames as well
+    for _ in 0..5 {
+        app.update();
+    }
+    let usage = app.world.resource::<diagnostic::NodeMemoryUsage>();
+    assert_eq!(usage.get("memory/small"), Some(12));
+    assert_eq!(usage.get("memory/large"), Some(400));
+    assert_eq!(usage.get("memory/plus_one"), Some(12));
+    assert_eq!(usage.total(), 424);
+}
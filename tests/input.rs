@@ -0,0 +1,312 @@
+use bevy::math::{UVec4, Vec3, Vec4};
+use bevy_node_plumber::prelude::*;
+use bevy_render::render_graph::{Node, RenderGraph, SlotType, SlotValue};
+use bevy_render::render_resource::{
+    BindingType, BufferBindingType, BufferUsages, SamplerDescriptor, ShaderStages, ShaderType,
+};
+use bevy_render::renderer::{RenderDevice, RenderQueue};
+
+mod common;
+
+#[test]
+fn uniform_buffer_node_keeps_value_until_upload() {
+    let params = Vec4::new(640.0, 480.0, 1.5, 0.0);
+    let node = input::UniformBufferNode::new(params);
+    let extracted = node.clone();
+
+    assert_eq!(extracted.get(), params);
+    assert_eq!(InputBuffer::<Vec4>::size(&node), 0);
+
+    node.set(Vec4::ZERO);
+    assert_eq!(extracted.get(), Vec4::ZERO);
+    assert_eq!(InputBuffer::<Vec4>::size(&extracted), 0);
+}
+
+#[test]
+fn uniform_buffer_node_reuses_uploaded_buffer() {
+    let Some(mut app) = common::gpu_app() else {
+        return;
+    };
+    app.finish();
+    let device = app.world.resource::<RenderDevice>();
+    let queue = app.world.resource::<RenderQueue>();
+    let node = input::UniformBufferNode::new(Vec3::ONE);
+    let extracted = node.clone();
+
+    let buffer = extracted.write_buffer(device, queue).unwrap();
+    assert_eq!(buffer.size(), Vec3::min_size().get());
+    assert_eq!(InputBuffer::<Vec3>::size(&node), buffer.size());
+
+    // The buffer is reused for the next value of the same type
+    node.set(Vec3::ZERO);
+    let rewritten = extracted.write_buffer(device, queue).unwrap();
+    assert_eq!(rewritten.id(), buffer.id());
+}
+
+#[test]
+fn sampler_node_outputs_sampler_slot() {
+    let sampler = input::SamplerNode::new(SamplerDescriptor::default());
+    let outputs = sampler.output();
+    assert_eq!(outputs.len(), 1);
+    assert_eq!(outputs[0].name, input::SLOT_NAME);
+    assert_eq!(outputs[0].slot_type, SlotType::Sampler);
+}
+
+#[test]
+fn asset_buffer_node_outputs_buffer_slot() {
+    let asset = input::BufferAsset::from_pod(&[1u32, 2, 3]);
+    assert_eq!(asset.data.len(), 12);
+    assert_eq!(&asset.data[4..8], &2u32.to_ne_bytes());
+
+    let node = input::AssetBufferNode::new(Default::default());
+    let outputs = node.output();
+    assert_eq!(outputs.len(), 1);
+    assert_eq!(outputs[0].name, input::SLOT_NAME);
+    assert_eq!(outputs[0].slot_type, SlotType::Buffer);
+    assert!(!node.is_prepared(&bevy::prelude::World::new()));
+}
+
+#[test]
+fn persistent_buffer_node_is_resized_without_upload() {
+    let usages = bevy_render::render_resource::BufferUsages::STORAGE;
+    let node = input::PersistentBufferNode::new(64, usages);
+    let extracted = node.clone();
+    assert!(!extracted.is_allocated());
+    assert_eq!(extracted.size(), 64);
+    assert_eq!(extracted.output()[0].slot_type, SlotType::Buffer);
+
+    node.resize(256);
+    assert_eq!(extracted.size(), 256);
+    assert!(!extracted.is_allocated());
+}
+
+#[test]
+fn ping_pong_buffer_node_outputs_read_and_write_slots() {
+    let usages = bevy_render::render_resource::BufferUsages::STORAGE;
+    let node = input::PingPongBufferNode::new(16, usages);
+    node.seed(&[1.0f32, 2.0, 3.0, 4.0]);
+    let outputs = node.output();
+    assert_eq!(outputs.len(), 2);
+    assert_eq!(outputs[0].name, input::READ_SLOT_NAME);
+    assert_eq!(outputs[1].name, input::WRITE_SLOT_NAME);
+    assert!(outputs
+        .iter()
+        .all(|slot| slot.slot_type == SlotType::Buffer));
+    assert!(node.front_buffer().is_none());
+    assert_eq!(node.size(), 16);
+}
+
+#[derive(bevy::prelude::Asset, bevy::reflect::TypePath, Clone)]
+struct InitialConditions(Vec<f32>);
+
+impl bevy_render::render_asset::RenderAsset for InitialConditions {
+    type ExtractedAsset = Self;
+    type PreparedAsset = bevy_render::render_resource::Buffer;
+    type Param = bevy::ecs::system::lifetimeless::SRes<bevy_render::renderer::RenderDevice>;
+
+    fn extract_asset(&self) -> Self::ExtractedAsset {
+        self.clone()
+    }
+
+    fn prepare_asset(
+        extracted_asset: Self::ExtractedAsset,
+        render_device: &mut bevy::ecs::system::SystemParamItem<Self::Param>,
+    ) -> Result<
+        Self::PreparedAsset,
+        bevy_render::render_asset::PrepareAssetError<Self::ExtractedAsset>,
+    > {
+        Ok(render_device.create_buffer_with_data(
+            &bevy_render::render_resource::BufferInitDescriptor {
+                label: None,
+                contents: bevy::core::cast_slice(&extracted_asset.0),
+                usage: bevy_render::render_resource::BufferUsages::STORAGE,
+            },
+        ))
+    }
+}
+
+#[test]
+fn asset_buffer_node_accepts_custom_buffer_assets() {
+    let node =
+        input::AssetBufferNode::from_asset(bevy::prelude::Handle::<InitialConditions>::default());
+    assert_eq!(node.output()[0].slot_type, SlotType::Buffer);
+    assert!(!node.is_prepared(&bevy::prelude::World::new()));
+}
+
+#[test]
+fn asset_buffer_node_outputs_placeholder_until_asset_is_prepared() {
+    let Some(mut app) = common::gpu_app() else {
+        return;
+    };
+    bevy::prelude::AssetApp::init_asset::<InitialConditions>(&mut app).add_plugins(
+        bevy_render::render_asset::RenderAssetPlugin::<InitialConditions>::default(),
+    );
+    let asset = app
+        .world
+        .resource::<bevy::prelude::Assets<InitialConditions>>()
+        .get_handle_provider()
+        .reserve_handle()
+        .typed::<InitialConditions>();
+    let node = input::AssetBufferNode::from_asset(asset);
+    let received = common::probe_output(&mut app, "unprepared_asset", node, SlotType::Buffer);
+
+    let SlotValue::Buffer(placeholder) = &received[0] else {
+        panic!("Buffer expected");
+    };
+    assert!(received.len() > 1);
+    for value in &received {
+        assert!(matches!(value, SlotValue::Buffer(buffer) if buffer.id() == placeholder.id()));
+    }
+}
+
+#[test]
+fn view_depth_texture_node_outputs_placeholder_without_view() {
+    let Some(mut app) = common::gpu_app() else {
+        return;
+    };
+    let node = input::ViewDepthTextureNode::new(None);
+    let received = common::probe_output(&mut app, "no_view", node, SlotType::TextureView);
+
+    let SlotValue::TextureView(placeholder) = &received[0] else {
+        panic!("Texture view expected");
+    };
+    assert!(received.len() > 1);
+    for value in &received {
+        assert!(matches!(value, SlotValue::TextureView(view) if view.id() == placeholder.id()));
+    }
+}
+
+#[test]
+fn generated_buffer_node_accepts_capturing_generator() {
+    let scale = 3u32;
+    let node = input::GeneratedBufferNode::new(5, move |index| index as u32 * scale);
+    assert_eq!(InputBuffer::<u32>::size(&node), 20);
+    assert_eq!(node.output()[0].slot_type, SlotType::Buffer);
+}
+
+#[test]
+#[should_panic(expected = "zero-sized")]
+fn generated_buffer_node_rejects_zero_sized_elements() {
+    input::GeneratedBufferNode::new(5, |_| ());
+}
+
+#[test]
+fn input_texture_node_outputs_fallback_until_image_is_prepared() {
+    let Some(mut app) = common::gpu_app() else {
+        return;
+    };
+    let image = app
+        .world
+        .resource::<bevy::prelude::Assets<bevy::prelude::Image>>()
+        .get_handle_provider()
+        .reserve_handle()
+        .typed::<bevy::prelude::Image>();
+    let node = input::InputTextureNode::from_image(image);
+    let received = common::probe_output(&mut app, "unprepared_image", node, SlotType::TextureView);
+
+    let fallback = common::fallback_texture_view(&app);
+    assert!(!received.is_empty());
+    for value in received {
+        assert!(matches!(value, SlotValue::TextureView(view) if view.id() == fallback.id()));
+    }
+}
+
+#[test]
+fn uniform_buffer_node_is_bound_as_uniform_input() {
+    let Some(mut app) = common::gpu_app() else {
+        return;
+    };
+    let shader = common::add_shader(
+        &mut app,
+        r#"
+@group(0) @binding(0) var<uniform> params: vec4<u32>;
+@group(0) @binding(1) var<storage, read_write> output: array<u32, 4>;
+
+@compute @workgroup_size(4)
+fn main(@builtin(local_invocation_index) index: u32) {
+    output[index] = params[index] * 2u;
+}
+"#,
+    );
+    let node = builder::ComputeNodeBuilder::default()
+        .shader(shader)
+        .entry_point("main")
+        .dispatch_workgroups_strategy(DispatchWorkgroupsStrategy::Static(1, 1, 1))
+        .derive_layout()
+        .bind_resource()
+        .name("params")
+        .binding(0)
+        .uniform()
+        .input()
+        .buffer()
+        .add()
+        .bind_resource()
+        .name("output")
+        .binding(1)
+        .output()
+        .build_buffer(
+            "output",
+            16,
+            BufferUsages::STORAGE | BufferUsages::COPY_SRC,
+            false,
+        )
+        .add()
+        .build()
+        .unwrap();
+    let entry = node.binding_resource_info[0]
+        .layout_entry(ShaderStages::COMPUTE)
+        .unwrap();
+    assert!(matches!(
+        entry.ty,
+        BindingType::Buffer {
+            ty: BufferBindingType::Uniform,
+            ..
+        }
+    ));
+
+    let params = input::UniformBufferNode::new(UVec4::new(1, 2, 3, 4));
+    let output = output::OutputBuffer::default();
+    app.world.spawn(output.clone());
+    let node_entity = app.world.spawn(node.clone()).id();
+    let sub_graph = builder::SubGraphBuilder::default()
+        .name("uniform_input".into())
+        .add_node("params", params)
+        .add_node_provider("node".into(), node_entity, &node)
+        .add_node("output", output.clone())
+        .add_node_edge(RenderGraph::INPUT_NODE_NAME, "params")
+        .add_slot_edge("params", input::SLOT_NAME, "node", "params")
+        .add_slot_edge("node", "output", "output", output::SLOT_NAME)
+        .trigger(graph::SubGraphTrigger::once())
+        .build()
+        .unwrap();
+    app.world.spawn(sub_graph);
+    app.finish();
+    app.cleanup();
+
+    for _ in 0..100 {
+        app.update();
+        if output.buffer_ready() {
+            break;
+        }
+    }
+    assert_eq!(output.take_pod_vec::<u32>().unwrap(), vec![2, 4, 6, 8]);
+}
+
+#[test]
+fn uniform_is_rejected_for_storage_outputs() {
+    let result = builder::ComputeNodeBuilder::default()
+        .shader(Default::default())
+        .entry_point("main")
+        .dispatch_workgroups_strategy(DispatchWorkgroupsStrategy::Static(1, 1, 1))
+        .bind_resource()
+        .name("output")
+        .uniform()
+        .input_output()
+        .buffer()
+        .add()
+        .build();
+    assert!(matches!(
+        result,
+        Err(builder::BuilderError::ValidationError(_))
+    ));
+}
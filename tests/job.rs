@@ -0,0 +1,51 @@
+use bevy::ecs::system::CommandQueue;
+use bevy::prelude::*;
+use bevy_node_plumber::builder::BuilderError;
+use bevy_node_plumber::prelude::*;
+use bevy_node_plumber::resource::BindResourceCreationStrategy;
+
+#[test]
+fn compute_job_requires_dispatch() {
+    let mut world = World::new();
+    let mut queue = CommandQueue::default();
+    let mut commands = Commands::new(&mut queue, &world);
+    let result = ComputeJob::new(Handle::default(), "main")
+        .input(&[1.0f32, 2.0])
+        .run(&mut commands);
+    assert!(matches!(
+        result,
+        Err(BuilderError::ValueNotDefined("dispatch"))
+    ));
+    queue.apply(&mut world);
+    assert_eq!(world.entities().len(), 0);
+}
+
+#[test]
+fn compute_job_spawns_sub_graph() {
+    let mut world = World::new();
+    let mut queue = CommandQueue::default();
+    let mut commands = Commands::new(&mut queue, &world);
+    let handle = ComputeJob::new(Handle::default(), "main")
+        .input(&[1u8, 2, 3])
+        .dispatch(DispatchWorkgroupsStrategy::Static(1, 1, 1))
+        .run(&mut commands)
+        .unwrap();
+    queue.apply(&mut world);
+
+    assert_eq!(handle.entities().len(), 3);
+    assert_eq!(world.query::<&graph::SubGraph>().iter(&world).count(), 1);
+    let compute_node = world.query::<&compute::ComputeNode>().single(&world);
+    let output = compute_node
+        .binding_resource_info
+        .iter()
+        .find(|info| info.name == "output")
+        .unwrap();
+    match &output.direction {
+        BindResourceDirection::Output(BindResourceCreationDescriptor::Buffer(
+            BindResourceCreationStrategy::Static(descriptor),
+        )) => assert_eq!(descriptor.size, 4),
+        other => panic!("Expected static output buffer, got {:?}", other),
+    }
+    assert!(!handle.is_ready());
+    assert!(handle.take_result::<f32>().is_err());
+}
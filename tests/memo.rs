@@ -0,0 +1,138 @@
+use bevy::prelude::*;
+use bevy_node_plumber::prelude::*;
+use bevy_render::render_graph::{RenderGraph, SlotValue};
+use bevy_render::render_resource::BufferUsages;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+mod common;
+
+#[test]
+fn written_resources_are_tracked_by_identity() {
+    let written = memo::WrittenResources::default();
+    let entity = Entity::from_raw(7);
+    let id = memo::SlotValueId::from(&SlotValue::Entity(entity));
+
+    assert!(!written.is_written(&id));
+    written.mark(&SlotValue::Entity(entity));
+    assert!(written.is_written(&id));
+    assert!(!written.is_written(&memo::SlotValueId::Entity(Entity::from_raw(8))));
+}
+
+#[test]
+fn compute_node_memoization_is_opt_in() {
+    let builder = || {
+        builder::ComputeNodeBuilder::default()
+            .shader(Handle::default())
+            .entry_point("main")
+            .dispatch_workgroups_strategy(DispatchWorkgroupsStrategy::Static(1, 1, 1))
+    };
+
+    assert!(!builder().build().unwrap().memoize);
+    assert!(builder().memoize(true).build().unwrap().memoize);
+}
+
+/// `accumulate` adds the input to its output, so every dispatch changes the result.
+const ACCUMULATE_SHADER: &str = r#"
+@group(0) @binding(0) var<storage, read> input: array<u32>;
+@group(0) @binding(1) var<storage, read_write> output: array<u32>;
+
+@compute @workgroup_size(1)
+fn accumulate(@builtin(global_invocation_id) id: vec3<u32>) {
+    output[id.x] = output[id.x] + input[id.x];
+}
+"#;
+
+fn memoized_node(shader: &Handle<Shader>, entry_point: &'static str) -> compute::ComputeNode {
+    builder::ComputeNodeBuilder::default()
+        .shader(shader.clone())
+        .entry_point(entry_point)
+        .dispatch_workgroups_strategy(DispatchWorkgroupsStrategy::Static(3, 1, 1))
+        .memoize(true)
+        .bind_resource()
+        .name("input")
+        .binding(0)
+        .read_only()
+        .input()
+        .buffer()
+        .add()
+        .bind_resource()
+        .name("output")
+        .binding(1)
+        .output()
+        .build_buffer(
+            entry_point,
+            12,
+            BufferUsages::STORAGE | BufferUsages::COPY_SRC,
+            false,
+        )
+        .add()
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn memoized_nodes_rerun_only_after_upstream_change() {
+    let Some(mut app) = common::gpu_app() else {
+        return;
+    };
+    let accumulate_shader = common::add_shader(&mut app, ACCUMULATE_SHADER);
+    let chain_shader = common::add_shader(&mut app, common::CHAIN_SHADER);
+    let accumulate = memoized_node(&accumulate_shader, "accumulate");
+    let double = memoized_node(&chain_shader, "double");
+    let accumulate_entity = app.world.spawn(accumulate.clone()).id();
+    let double_entity = app.world.spawn(double.clone()).id();
+    let values = input::BufferVecNode::<u32>::new(BufferUsages::STORAGE);
+    for value in [1, 2, 3] {
+        values.push(value);
+    }
+    let output = output::OutputBuffer::default();
+    let request = Arc::new(AtomicBool::new(false));
+    let sub_graph = builder::SubGraphBuilder::default()
+        .name("memoized_chain".into())
+        .add_node("values", values.clone())
+        .add_node_provider("accumulate".into(), accumulate_entity, &accumulate)
+        .add_node_provider("double".into(), double_entity, &double)
+        .add_node("output", output.clone())
+        .add_node_edge(RenderGraph::INPUT_NODE_NAME, "values")
+        .add_slot_edge("values", input::SLOT_NAME, "accumulate", "input")
+        .add_slot_edge("accumulate", "output", "double", "input")
+        .add_slot_edge("double", "output", "output", output::SLOT_NAME)
+        .trigger(graph::SubGraphTrigger::Manual(request.clone()))
+        .build()
+        .unwrap();
+    app.world.spawn(sub_graph);
+    app.world.spawn(output.clone());
+    app.finish();
+    app.cleanup();
+    for _ in 0..100 {
+        if common::is_deployed(&app, "memoized_chain") {
+            break;
+        }
+        app.update();
+    }
+    assert!(common::is_deployed(&app, "memoized_chain"));
+
+    assert_eq!(
+        common::run_and_read(&mut app, &request, &output),
+        vec![2, 4, 6]
+    );
+    // The input is not uploaded again, so both nodes skip the dispatch
+    assert_eq!(
+        common::run_and_read(&mut app, &request, &output),
+        vec![2, 4, 6]
+    );
+
+    values.clear();
+    for value in [10, 20, 30] {
+        values.push(value);
+    }
+    assert_eq!(
+        common::run_and_read(&mut app, &request, &output),
+        vec![22, 44, 66]
+    );
+    assert_eq!(
+        common::run_and_read(&mut app, &request, &output),
+        vec![22, 44, 66]
+    );
+}
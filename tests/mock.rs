@@ -0,0 +1,423 @@
+use bevy::prelude::*;
+use bevy_node_plumber::diagnostic;
+use bevy_node_plumber::mock::{HeadlessRenderPlugin, MockNodeProvider};
+use bevy_node_plumber::prelude::*;
+use bevy_node_plumber::NodeProviderPlugin;
+use bevy_render::render_graph::{RenderGraph, SlotInfo, SlotType};
+use bevy_render::render_resource::{BufferUsages, TextureUsages};
+use bevy_render::RenderApp;
+
+const SUB_GRAPH_NAME: &str = "mock_sub_graph";
+
+fn headless_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(HeadlessRenderPlugin)
+        .add_plugins(graph::SubGraphPlugin)
+        .add_plugins(NodeProviderPlugin::<MockNodeProvider>::default());
+    app
+}
+
+fn spawn_sub_graph(app: &mut App, provider: MockNodeProvider) -> Entity {
+    let provider_entity = app.world.spawn(provider.clone()).id();
+    let sub_graph = builder::SubGraphBuilder::default()
+        .name(SUB_GRAPH_NAME.into())
+        .add_node_provider("mock_node".into(), provider_entity, &provider)
+        .build()
+        .unwrap();
+    app.world.spawn(sub_graph);
+    app.finish();
+    app.cleanup();
+    provider_entity
+}
+
+fn is_deployed(app: &App) -> bool {
+    app.sub_app(RenderApp)
+        .world
+        .resource::<RenderGraph>()
+        .get_sub_graph(SUB_GRAPH_NAME)
+        .is_some()
+}
+
+#[test]
+fn sub_graph_is_deployed_when_provider_is_ready() {
+    let mut app = headless_app();
+    let provider = MockNodeProvider::new(3);
+    spawn_sub_graph(&mut app, provider.clone());
+
+    app.update();
+    assert!(!is_deployed(&app));
+
+    for _ in 0..3 {
+        app.update();
+    }
+    assert!(provider.updates() >= 3);
+    assert!(is_deployed(&app));
+}
+
+#[test]
+fn provider_error_is_mirrored_to_main_world() {
+    let mut app = headless_app();
+    let provider_entity = spawn_sub_graph(&mut app, MockNodeProvider::failing("compile error"));
+
+    for _ in 0..3 {
+        app.update();
+    }
+    assert!(!is_deployed(&app));
+    let status = app
+        .world
+        .get::<graph::ProviderStatus>(provider_entity)
+        .expect("Provider status must be inserted");
+    assert_eq!(status.node_name, "mock_node");
+    assert_eq!(status.error.as_deref(), Some("compile error"));
+}
+
+#[test]
+fn failed_provider_marks_sub_graph_as_stuck() {
+    let mut app = headless_app();
+    spawn_sub_graph(&mut app, MockNodeProvider::failing("compile error"));
+
+    for _ in 0..3 {
+        app.update();
+    }
+    let mut sub_graphs = app.world.query::<&graph::SubGraph>();
+    assert!(sub_graphs.single(&app.world).is_stuck());
+}
+
+#[test]
+fn deployed_sub_graph_is_not_stuck() {
+    let mut app = headless_app();
+    spawn_sub_graph(&mut app, MockNodeProvider::new(2));
+
+    for _ in 0..5 {
+        app.update();
+    }
+    assert!(is_deployed(&app));
+    let mut sub_graphs = app.world.query::<&graph::SubGraph>();
+    assert!(!sub_graphs.single(&app.world).is_stuck());
+}
+
+#[test]
+fn status_log_reports_deployment_and_providers() {
+    let mut app = headless_app();
+    app.add_plugins(diagnostic::SubGraphStatusLogPlugin);
+    let provider = MockNodeProvider::new(2);
+    let provider_entity = app.world.spawn(provider.clone()).id();
+    let sub_graph = builder::SubGraphBuilder::default()
+        .name(SUB_GRAPH_NAME.into())
+        .add_node_provider("mock_node".into(), provider_entity, &provider)
+        .build()
+        .unwrap();
+    let sub_graph_entity = app
+        .world
+        .spawn((sub_graph, diagnostic::SubGraphStatusLog::default()))
+        .id();
+    app.finish();
+    app.cleanup();
+
+    app.update();
+    app.update();
+    let log = app
+        .world
+        .get::<diagnostic::SubGraphStatusLog>(sub_graph_entity)
+        .unwrap();
+    assert!(log.report().contains("waiting for providers"));
+    assert!(log.report().contains("`mock_node`: "), "{}", log.report());
+
+    for _ in 0..3 {
+        app.update();
+    }
+    let log = app
+        .world
+        .get::<diagnostic::SubGraphStatusLog>(sub_graph_entity)
+        .unwrap();
+    assert!(is_deployed(&app));
+    assert!(log
+        .report()
+        .starts_with("Sub graph `mock_sub_graph`: deployed"));
+    assert!(log.report().contains("`mock_node`: ready"));
+}
+
+#[test]
+fn removed_sub_graph_is_flagged_as_missing() {
+    let mut app = headless_app();
+    spawn_sub_graph(&mut app, MockNodeProvider::new(0));
+    for _ in 0..3 {
+        app.update();
+    }
+    assert!(is_deployed(&app));
+
+    let mut query = app.world.query::<&graph::SubGraph>();
+    assert!(!query.single(&app.world).is_missing());
+    app.sub_app_mut(RenderApp)
+        .world
+        .resource_mut::<RenderGraph>()
+        .remove_sub_graph(SUB_GRAPH_NAME);
+    app.update();
+    assert!(query.single(&app.world).is_missing());
+}
+
+#[test]
+fn outer_output_slot_is_published_by_runner_node() {
+    let mut app = headless_app();
+    let provider = MockNodeProvider::new(0).with_output("result", SlotType::Buffer);
+    let provider_entity = app.world.spawn(provider.clone()).id();
+    let sub_graph = builder::SubGraphBuilder::default()
+        .name(SUB_GRAPH_NAME.into())
+        .add_node_provider("mock_node".into(), provider_entity, &provider)
+        .add_outer_output_slot_edge("mock_node", "result", "data".into(), SlotType::Buffer)
+        .build()
+        .unwrap();
+    app.world.spawn(sub_graph);
+    app.finish();
+    app.cleanup();
+    for _ in 0..3 {
+        app.update();
+    }
+    assert!(is_deployed(&app));
+
+    let render_graph = app.sub_app(RenderApp).world.resource::<RenderGraph>();
+    let runner = render_graph.get_node_state(SUB_GRAPH_NAME).unwrap();
+    let slot = runner.output_slots.get_slot("data").unwrap();
+    assert_eq!(slot.slot_type, SlotType::Buffer);
+    let inner = render_graph.get_sub_graph(SUB_GRAPH_NAME).unwrap();
+    let capture = inner.get_node_state("outer_output.data").unwrap();
+    assert_eq!(capture.input_slots.len(), 1);
+}
+
+#[test]
+fn despawned_sub_graph_is_removed_from_render_graph() {
+    let mut app = headless_app();
+    spawn_sub_graph(&mut app, MockNodeProvider::new(0));
+    for _ in 0..3 {
+        app.update();
+    }
+    assert!(is_deployed(&app));
+
+    let mut query = app.world.query_filtered::<Entity, With<graph::SubGraph>>();
+    let entity = query.single(&app.world);
+    app.world.despawn(entity);
+    app.update();
+
+    assert!(!is_deployed(&app));
+    let render_graph = app.sub_app(RenderApp).world.resource::<RenderGraph>();
+    assert!(render_graph.get_node_state(SUB_GRAPH_NAME).is_err());
+}
+
+#[test]
+fn despawned_provider_is_evicted_from_cache() {
+    let mut app = headless_app();
+    let provider = MockNodeProvider::new(0);
+    let provider_entity = spawn_sub_graph(&mut app, provider.clone());
+    for _ in 0..3 {
+        app.update();
+    }
+    let updates = provider.updates();
+    assert!(updates > 0);
+
+    app.world.despawn(provider_entity);
+    for _ in 0..3 {
+        app.update();
+    }
+    assert_eq!(provider.updates(), updates);
+}
+
+#[test]
+fn sub_graph_run_events_are_not_sent_without_runs() {
+    let mut app = headless_app();
+    let provider = MockNodeProvider::new(0);
+    let provider_entity = app.world.spawn(provider.clone()).id();
+    let sub_graph = builder::SubGraphBuilder::default()
+        .name(SUB_GRAPH_NAME.into())
+        .trigger(graph::SubGraphTrigger::Manual(default()))
+        .add_node_provider("mock_node".into(), provider_entity, &provider)
+        .build()
+        .unwrap();
+    app.world.spawn(sub_graph);
+    app.finish();
+    app.cleanup();
+    for _ in 0..3 {
+        app.update();
+    }
+    assert!(is_deployed(&app));
+
+    // The manual trigger is never requested, so the runner node never runs
+    let events = app.world.resource::<Events<graph::SubGraphRun>>();
+    assert!(events.is_empty());
+}
+
+#[test]
+fn gpu_timings_are_disabled_without_timestamp_queries() {
+    let mut app = headless_app();
+    app.add_plugins(diagnostic::GpuTimingsPlugin::default());
+    app.finish();
+    app.cleanup();
+    app.update();
+
+    let timings = app.world.resource::<diagnostic::NodeTimings>();
+    assert!(timings.snapshot().is_empty());
+    assert!(timings.get("compute").is_none());
+    let render_world = &app.sub_app(RenderApp).world;
+    assert!(render_world.contains_resource::<diagnostic::NodeTimings>());
+    assert!(!render_world.contains_resource::<diagnostic::TimestampQuerySet>());
+}
+
+#[test]
+fn gpu_memory_usage_empty_without_nodes() {
+    let mut app = headless_app();
+    app.add_plugins(diagnostic::GpuMemoryUsagePlugin);
+    app.finish();
+    app.cleanup();
+    app.update();
+
+    let usage = app.world.resource::<diagnostic::NodeMemoryUsage>();
+    assert_eq!(usage.total(), 0);
+    assert!(usage.get("compute").is_none());
+    let render_world = &app.sub_app(RenderApp).world;
+    assert!(render_world.contains_resource::<diagnostic::NodeMemoryUsage>());
+}
+
+#[test]
+fn deferred_sub_graph_is_extracted_when_ready() {
+    let mut app = headless_app();
+    let provider = MockNodeProvider::new(0);
+    let provider_entity = app.world.spawn(provider.clone()).id();
+    let sub_graph = builder::SubGraphBuilder::default()
+        .name(SUB_GRAPH_NAME.into())
+        .ready_for_extraction(false)
+        .add_node_provider("mock_node".into(), provider_entity, &provider)
+        .build()
+        .unwrap();
+    let sub_graph_entity = app.world.spawn(sub_graph).id();
+    app.finish();
+    app.cleanup();
+
+    for _ in 0..3 {
+        app.update();
+    }
+    assert!(!is_deployed(&app));
+
+    app.world
+        .get_mut::<graph::SubGraph>(sub_graph_entity)
+        .unwrap()
+        .set_ready_for_extraction(true);
+    for _ in 0..3 {
+        app.update();
+    }
+    assert!(is_deployed(&app));
+}
+
+#[test]
+fn output_buffer_source_gets_copy_src_usage() {
+    let mut app = headless_app();
+    let provider = MockNodeProvider::new(0).with_output("result", SlotType::Buffer);
+    let provider_entity = app.world.spawn(provider.clone()).id();
+    let sub_graph = builder::SubGraphBuilder::default()
+        .name(SUB_GRAPH_NAME.into())
+        .add_node_provider("mock_node".into(), provider_entity, &provider)
+        .add_node("output", output::OutputBuffer::default())
+        .add_slot_edge("mock_node", "result", "output", output::SLOT_NAME)
+        .build()
+        .unwrap();
+    app.world.spawn(sub_graph);
+    app.finish();
+    app.cleanup();
+
+    app.update();
+    let provider = app.world.get::<MockNodeProvider>(provider_entity).unwrap();
+    assert_eq!(provider.output_usages("result"), BufferUsages::COPY_SRC);
+
+    for _ in 0..3 {
+        app.update();
+    }
+    assert!(is_deployed(&app));
+}
+
+#[test]
+fn copied_sources_get_copy_src_usage() {
+    let mut app = headless_app();
+    let provider = MockNodeProvider::new(0)
+        .with_output("positions", SlotType::Buffer)
+        .with_output("velocities", SlotType::Buffer)
+        .with_output("copied", SlotType::Buffer)
+        .with_output("passed", SlotType::Buffer)
+        .with_output("image", SlotType::TextureView)
+        .with_output("frame", SlotType::TextureView);
+    let provider_entity = app.world.spawn(provider.clone()).id();
+    let sub_graph = builder::SubGraphBuilder::default()
+        .name(SUB_GRAPH_NAME.into())
+        .add_node_provider("mock_node".into(), provider_entity, &provider)
+        .add_node(
+            "multi",
+            output::MultiOutputBuffer::new(["positions", "velocities"]),
+        )
+        .add_node("buffer_copy", copy::CopyNode::buffer())
+        .add_node("ordering", ordering::OrderingNode::buffer())
+        .add_node("texture_output", output::OutputTexture::default())
+        .add_node("texture_copy", copy::CopyNode::texture(Handle::default()))
+        .add_slot_edge("mock_node", "positions", "multi", "positions")
+        .add_slot_edge("mock_node", "velocities", "multi", "velocities")
+        .add_slot_edge("mock_node", "copied", "buffer_copy", copy::SLOT_NAME)
+        .add_slot_edge("mock_node", "passed", "ordering", ordering::SLOT_NAME)
+        .add_slot_edge("mock_node", "image", "texture_output", output::SLOT_NAME)
+        .add_slot_edge("mock_node", "frame", "texture_copy", copy::SLOT_NAME)
+        .build()
+        .unwrap();
+    app.world.spawn(sub_graph);
+    app.finish();
+    app.cleanup();
+
+    app.update();
+    let provider = app.world.get::<MockNodeProvider>(provider_entity).unwrap();
+    for slot in ["positions", "velocities", "copied"] {
+        assert_eq!(
+            provider.output_usages(slot),
+            BufferUsages::COPY_SRC,
+            "{slot}"
+        );
+    }
+    assert_eq!(provider.output_usages("passed"), BufferUsages::empty());
+    for slot in ["image", "frame"] {
+        assert_eq!(
+            provider.output_texture_usages(slot),
+            TextureUsages::COPY_SRC,
+            "{slot}"
+        );
+    }
+}
+
+#[test]
+fn nested_sub_graph_is_deployed_with_runner_node() {
+    let mut app = headless_app();
+    let mut nested = RenderGraph::default();
+    nested.set_input(vec![SlotInfo::new("data", SlotType::Buffer)]);
+    let provider = MockNodeProvider::new(0).with_output("data", SlotType::Buffer);
+    let provider_entity = app.world.spawn(provider.clone()).id();
+    let sub_graph = builder::SubGraphBuilder::default()
+        .name(SUB_GRAPH_NAME.into())
+        .add_node_provider("mock_node".into(), provider_entity, &provider)
+        .add_sub_graph("nested", nested)
+        .add_slot_edge("mock_node", "data", "nested", "data")
+        .build()
+        .unwrap();
+    app.world.spawn(sub_graph);
+    app.finish();
+    app.cleanup();
+
+    for _ in 0..3 {
+        app.update();
+    }
+    let render_graph = app.sub_app(RenderApp).world.resource::<RenderGraph>();
+    let sub_graph = render_graph.get_sub_graph(SUB_GRAPH_NAME).unwrap();
+    assert!(sub_graph.get_sub_graph("nested").is_some());
+    let runner = sub_graph
+        .get_node::<graph::RunNestedGraphNode>("nested")
+        .unwrap();
+    assert_eq!(bevy_render::render_graph::Node::input(runner).len(), 1);
+
+    let result = builder::SubGraphBuilder::default()
+        .name("duplicate".into())
+        .add_node("nested", output::OutputBuffer::default())
+        .add_sub_graph("nested", RenderGraph::default())
+        .build();
+    assert!(result.is_err());
+}
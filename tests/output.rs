@@ -0,0 +1,288 @@
+use bevy::prelude::*;
+use bevy_node_plumber::prelude::*;
+use bevy_node_plumber::NodeProvider;
+use bevy_render::render_graph::{Edge, Node, RenderGraph, SlotType};
+use bevy_render::render_resource::{BufferUsages, Extent3d, TextureFormat};
+use bevy_render::renderer::RenderDevice;
+use bevy_render::settings::WgpuFeatures;
+use std::borrow::Cow;
+
+mod common;
+
+#[test]
+fn output_buffer_with_usages_publishes_read_back_buffer() {
+    let read_back_only = output::OutputBuffer::default();
+    assert!(read_back_only.output().is_empty());
+
+    let reused =
+        output::OutputBuffer::default().with_usages(BufferUsages::STORAGE | BufferUsages::COPY_SRC);
+    let outputs = reused.output();
+    assert_eq!(outputs.len(), 1);
+    assert_eq!(outputs[0].name, output::OUT_SLOT_NAME);
+    assert_eq!(outputs[0].slot_type, SlotType::Buffer);
+    assert_eq!(reused.input()[0].name, output::SLOT_NAME);
+}
+
+#[test]
+fn output_buffer_range_is_combined_with_usages() {
+    let node = output::OutputBuffer::default()
+        .with_usages(BufferUsages::STORAGE | BufferUsages::COPY_SRC)
+        .with_range(4..12);
+    assert_eq!(node.output().len(), 1);
+}
+
+#[test]
+#[should_panic(expected = "must be aligned")]
+fn output_buffer_range_must_be_aligned() {
+    let _ = output::OutputBuffer::default().with_range(2..12);
+}
+
+#[test]
+fn output_buffers_are_connected_to_distinct_output_slots() {
+    let usages = BufferUsages::STORAGE | BufferUsages::COPY_SRC;
+    let compute_node = builder::ComputeNodeBuilder::default()
+        .shader(Handle::default())
+        .entry_point("main")
+        .dispatch_workgroups_strategy(DispatchWorkgroupsStrategy::Static(1, 1, 1))
+        .bind_resource()
+        .name("first")
+        .binding(0)
+        .output()
+        .build_buffer("first", 16, usages, false)
+        .add()
+        .bind_resource()
+        .name("second")
+        .binding(1)
+        .output()
+        .build_buffer("second", 16, usages, false)
+        .add()
+        .build()
+        .unwrap();
+
+    let mut graph = RenderGraph::default();
+    compute_node.add_node_to_graph(&mut graph, "compute".into());
+    graph.add_node("first_out", output::OutputBuffer::default());
+    graph.add_node("second_out", output::OutputBuffer::default());
+    graph.add_slot_edge("compute", "first", "first_out", output::SLOT_NAME);
+    graph.add_slot_edge("compute", "second", "second_out", output::SLOT_NAME);
+
+    let compute_outputs = &graph.get_node_state("compute").unwrap().output_slots;
+    let source_slot = |node: &'static str| {
+        let edges = &graph.get_node_state(node).unwrap().edges;
+        match edges.input_edges() {
+            [Edge::SlotEdge { output_index, .. }] => compute_outputs
+                .get_slot(*output_index)
+                .unwrap()
+                .name
+                .clone(),
+            edges => panic!("Single slot edge expected, found {:?}", edges),
+        }
+    };
+    assert_eq!(source_slot("first_out"), "first");
+    assert_eq!(source_slot("second_out"), "second");
+}
+
+#[test]
+fn shared_buffer_is_empty_until_run() {
+    let shared = output::SharedBufferNode::default();
+    assert!(shared.buffer().is_none());
+    assert!(shared.output().is_empty());
+    assert_eq!(shared.input()[0].name, output::SLOT_NAME);
+    assert_eq!(shared.input()[0].slot_type, SlotType::Buffer);
+}
+
+#[test]
+fn wait_mapped_is_pending_until_buffer_is_mapped() {
+    use std::future::Future;
+    use std::task::Poll;
+
+    let output = output::OutputBuffer::default();
+    let waiting = output.clone();
+    let mut wait = Box::pin(waiting.wait_mapped());
+    let pending = bevy::tasks::block_on(std::future::poll_fn(|cx| {
+        Poll::Ready(wait.as_mut().poll(cx).is_pending())
+    }));
+    assert!(pending);
+    assert!(!output.buffer_ready());
+}
+
+#[test]
+fn take_pod_vec_requires_mapped_buffer() {
+    let output = output::OutputBuffer::default();
+    assert!(matches!(
+        output.take_pod_vec::<u32>(),
+        Err(output::OutputError::MappedBufferNotFound)
+    ));
+}
+
+#[test]
+fn peek_leaves_the_buffer_mapped() {
+    let Some(mut app) = common::gpu_app() else {
+        return;
+    };
+    let values = input::BufferVecNode::<u32>::new(BufferUsages::STORAGE | BufferUsages::COPY_SRC);
+    for value in [1, 2, 3] {
+        values.push(value);
+    }
+    let output = output::OutputBuffer::default();
+    app.world.spawn(output.clone());
+    let sub_graph = builder::SubGraphBuilder::default()
+        .name(Cow::Borrowed("peek"))
+        .trigger(graph::SubGraphTrigger::once())
+        .add_node("values", values)
+        .add_node("output", output.clone())
+        .add_node_edge(RenderGraph::INPUT_NODE_NAME, "values")
+        .add_slot_edge("values", input::SLOT_NAME, "output", output::SLOT_NAME)
+        .build()
+        .unwrap();
+    app.world.spawn(sub_graph);
+    app.finish();
+    app.cleanup();
+
+    let mut bytes = vec![1u8];
+    assert!(matches!(
+        output.peek_bytes_into(&mut bytes),
+        Err(output::OutputError::MappedBufferNotFound)
+    ));
+    assert_eq!(bytes, vec![1u8]);
+    for _ in 0..100 {
+        app.update();
+        if output.buffer_ready() {
+            break;
+        }
+    }
+
+    assert_eq!(output.peek_pod_vec::<u32>().unwrap(), vec![1, 2, 3]);
+    assert_eq!(output.peek_pod_vec::<u32>().unwrap(), vec![1, 2, 3]);
+    output.peek_bytes_into(&mut bytes).unwrap();
+    assert_eq!(bytes, bevy::core::cast_slice::<u32, u8>(&[1, 2, 3]));
+    assert!(output.buffer_ready());
+    assert_eq!(output.take_pod_vec::<u32>().unwrap(), vec![1, 2, 3]);
+    assert!(matches!(
+        output.peek_pod_vec::<u32>(),
+        Err(output::OutputError::MappedBufferNotFound)
+    ));
+}
+
+#[test]
+fn multi_output_buffer_declares_named_input_slots() {
+    let multi = output::MultiOutputBuffer::new(["positions", "velocities"]);
+    let inputs = multi.input();
+    assert_eq!(inputs.len(), 2);
+    assert_eq!(inputs[0].name, "positions");
+    assert_eq!(inputs[1].name, "velocities");
+    assert!(inputs.iter().all(|slot| slot.slot_type == SlotType::Buffer));
+    assert!(multi.output().is_empty());
+
+    assert!(multi.get("positions").is_some());
+    assert!(!multi.buffer_ready("positions"));
+    assert!(!multi.buffers_ready());
+    assert!(matches!(
+        multi.take_buffer_as::<u32>("positions"),
+        Err(output::OutputError::MappedBufferNotFound)
+    ));
+    assert!(matches!(
+        multi.take_buffer_as::<u32>("missing"),
+        Err(output::OutputError::SlotNotFound(name)) if name == "missing"
+    ));
+}
+
+#[test]
+fn output_texture_reads_back_texture_of_input_slot() {
+    let Some(mut app) = common::gpu_app() else {
+        return;
+    };
+    let shader = common::add_shader(&mut app, common::GRADIENT_SHADER);
+    // Rows of 12 bytes are padded to 256 bytes in the staging buffer
+    let size = Extent3d {
+        width: 3,
+        height: 2,
+        depth_or_array_layers: 1,
+    };
+    let compute_node = common::gradient_node(&shader, size);
+    let compute_entity = app.world.spawn(compute_node.clone()).id();
+    let output = output::OutputTexture::default();
+    app.world.spawn(output.clone());
+    let sub_graph = builder::SubGraphBuilder::default()
+        .name(Cow::Borrowed("texture_readback"))
+        .add_node_provider("compute".into(), compute_entity, &compute_node)
+        .add_node("output", output.clone())
+        .add_slot_edge("compute", "image", "output", output::SLOT_NAME)
+        .build()
+        .unwrap();
+    app.world.spawn(sub_graph);
+    app.finish();
+    app.cleanup();
+
+    for _ in 0..100 {
+        app.update();
+        if output.image_ready() {
+            break;
+        }
+    }
+    let image = output.take_image().unwrap();
+    assert_eq!(image.texture_descriptor.size, size);
+    assert_eq!(image.texture_descriptor.format, TextureFormat::Rgba8Unorm);
+    let expected: Vec<u8> = (0..2)
+        .flat_map(|y| (0..3).flat_map(move |x| common::gradient_pixel(x, y)))
+        .collect();
+    assert_eq!(image.data, expected);
+}
+
+#[test]
+fn read_back_buffer_is_reused_by_compute_node() {
+    let Some(mut app) = common::gpu_app() else {
+        return;
+    };
+    let shader = common::add_shader(&mut app, common::CHAIN_SHADER);
+    let plus_one = common::chain_node(&shader, "plus_one");
+    let double = common::chain_node(&shader, "double");
+    let plus_one_entity = app.world.spawn(plus_one.clone()).id();
+    let double_entity = app.world.spawn(double.clone()).id();
+    let values = input::BufferVecNode::<u32>::new(BufferUsages::STORAGE);
+    for value in [1, 2, 3] {
+        values.push(value);
+    }
+    let read_back = output::OutputBuffer::default().with_usages(BufferUsages::STORAGE);
+    let result = output::OutputBuffer::default();
+    app.world.spawn(read_back.clone());
+    app.world.spawn(result.clone());
+    let sub_graph = builder::SubGraphBuilder::default()
+        .name(Cow::Borrowed("read_back_round_trip"))
+        .add_node("values", values)
+        .add_node_provider("plus_one".into(), plus_one_entity, &plus_one)
+        .add_node("read_back", read_back.clone())
+        .add_node_provider("double".into(), double_entity, &double)
+        .add_node("result", result.clone())
+        .add_node_edge(RenderGraph::INPUT_NODE_NAME, "values")
+        .add_slot_edge("values", input::SLOT_NAME, "plus_one", "input")
+        .add_slot_edge("plus_one", "output", "read_back", output::SLOT_NAME)
+        .add_slot_edge("read_back", output::OUT_SLOT_NAME, "double", "input")
+        .add_slot_edge("double", "output", "result", output::SLOT_NAME)
+        .build()
+        .unwrap();
+    let sub_graph_entity = app.world.spawn(sub_graph).id();
+    app.finish();
+    app.cleanup();
+    let mappable = app
+        .world
+        .resource::<RenderDevice>()
+        .features()
+        .contains(WgpuFeatures::MAPPABLE_PRIMARY_BUFFERS);
+
+    for _ in 0..100 {
+        app.update();
+        if result.buffer_ready() && read_back.buffer_ready() {
+            break;
+        }
+    }
+    let sub_graph = app.world.get::<graph::SubGraph>(sub_graph_entity).unwrap();
+    if mappable {
+        assert_eq!(read_back.take_pod_vec::<u32>().unwrap(), vec![2, 3, 4]);
+        assert_eq!(result.take_pod_vec::<u32>().unwrap(), vec![4, 6, 8]);
+    } else {
+        // The additional usages are not silently dropped, the sub graph is not deployed
+        assert!(sub_graph.is_stuck());
+        assert!(!common::is_deployed(&app, "read_back_round_trip"));
+    }
+}
@@ -0,0 +1,40 @@
+use bevy::prelude::*;
+use bevy_node_plumber::prelude::*;
+
+#[derive(Resource, Default)]
+struct ChangedNodes(usize);
+
+fn count_changed_nodes(
+    query: Query<(), Changed<compute::ComputeNode>>,
+    mut changed: ResMut<ChangedNodes>,
+) {
+    changed.0 = query.iter().count();
+}
+
+#[test]
+fn modified_shader_marks_compute_node_changed() {
+    let mut app = App::new();
+    app.add_event::<AssetEvent<Shader>>()
+        .init_resource::<ChangedNodes>()
+        .add_plugins(compute::AutoReloadShadersPlugin)
+        .add_systems(Update, count_changed_nodes);
+    let shader = Handle::<Shader>::weak_from_u128(0x5ade_0001);
+    let other = Handle::<Shader>::weak_from_u128(0x5ade_0002);
+    let node = builder::ComputeNodeBuilder::default()
+        .shader(shader.clone())
+        .entry_point("main")
+        .dispatch_workgroups_strategy(DispatchWorkgroupsStrategy::Static(1, 1, 1))
+        .build()
+        .unwrap();
+    app.world.spawn(node);
+    app.update();
+    assert_eq!(app.world.resource::<ChangedNodes>().0, 1);
+
+    let changed = |app: &mut App, id: AssetId<Shader>| {
+        app.world.send_event(AssetEvent::Modified { id });
+        app.update();
+        app.world.resource::<ChangedNodes>().0
+    };
+    assert_eq!(changed(&mut app, other.id()), 0);
+    assert_eq!(changed(&mut app, shader.id()), 1);
+}
@@ -0,0 +1,502 @@
+use bevy::prelude::*;
+use bevy_node_plumber::diagnostic;
+use bevy_node_plumber::prelude::*;
+use bevy_render::render_graph::{
+    Node, NodeRunError, RenderGraph, RenderGraphContext, SlotInfo, SlotType,
+};
+use bevy_render::render_resource::{BufferDescriptor, BufferId, BufferUsages};
+use bevy_render::renderer::RenderContext;
+use bevy_render::RenderApp;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+mod common;
+
+struct NoiseNode;
+
+impl Node for NoiseNode {
+    fn output(&self) -> Vec<SlotInfo> {
+        vec![SlotInfo::new("noise", SlotType::Buffer)]
+    }
+
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        _render_context: &mut RenderContext,
+        _world: &World,
+    ) -> Result<(), NodeRunError> {
+        Ok(())
+    }
+}
+
+/// Generator without outputs that counts its runs.
+#[derive(Clone, Default)]
+struct CountingNoiseNode(Arc<AtomicUsize>);
+
+impl Node for CountingNoiseNode {
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        _render_context: &mut RenderContext,
+        _world: &World,
+    ) -> Result<(), NodeRunError> {
+        self.0.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+#[test]
+fn zero_input_sub_graph_is_deployed_and_run() {
+    let noise = CountingNoiseNode::default();
+    let sub_graph = builder::SubGraphBuilder::default()
+        .name("noise_generator".into())
+        .add_node("noise", noise.clone())
+        .add_node_edge(RenderGraph::INPUT_NODE_NAME, "noise")
+        .build()
+        .unwrap();
+
+    assert_eq!(sub_graph.name(), "noise_generator");
+    assert!(sub_graph.providers().is_empty());
+    assert!(matches!(
+        sub_graph.providers_state_summary(),
+        graph::ProviderState::CanCreateNode
+    ));
+
+    let Some(mut app) = common::gpu_app() else {
+        return;
+    };
+    let sub_graph_entity = app.world.spawn(sub_graph).id();
+    app.finish();
+    app.cleanup();
+    let mut reader = app
+        .world
+        .resource::<Events<graph::SubGraphRun>>()
+        .get_reader();
+    let mut runs = Vec::new();
+    for _ in 0..10 {
+        app.update();
+        runs.extend(
+            reader
+                .read(app.world.resource::<Events<graph::SubGraphRun>>())
+                .cloned(),
+        );
+    }
+
+    assert!(common::is_deployed(&app, "noise_generator"));
+    assert!(!runs.is_empty());
+    assert!(runs.iter().all(|run| run.entity == sub_graph_entity));
+    assert!(noise.0.load(Ordering::Relaxed) >= runs.len());
+}
+
+#[test]
+fn runner_name_defaults_to_sub_graph_name() {
+    let default_runner = builder::SubGraphBuilder::default()
+        .name("generator".into())
+        .build()
+        .unwrap();
+    assert_eq!(default_runner.runner_name(), "generator");
+
+    let custom_runner = builder::SubGraphBuilder::default()
+        .name("generator".into())
+        .runner_name("generator_runner")
+        .build()
+        .unwrap();
+    assert_eq!(custom_runner.runner_name(), "generator_runner");
+}
+
+#[test]
+fn mismatched_slot_edge_fails_validation() {
+    let provider =
+        bevy_node_plumber::mock::MockNodeProvider::new(0).with_input("view", SlotType::TextureView);
+    let result = builder::SubGraphBuilder::default()
+        .name("mismatched".into())
+        .add_node("noise", NoiseNode)
+        .add_node_provider("consumer".into(), Entity::PLACEHOLDER, &provider)
+        .add_slot_edge("noise", "noise", "consumer", "view")
+        .build();
+
+    let Err(builder::BuilderError::ValidationError(message)) = result else {
+        panic!("Slot type mismatch must be reported as a validation error");
+    };
+    assert!(message.contains("noise"));
+    assert!(message.contains("consumer"));
+    assert!(message.contains("Buffer"));
+    assert!(message.contains("TextureView"));
+}
+
+#[test]
+fn graph_description_lists_nodes_and_edges() {
+    let provider = bevy_node_plumber::mock::MockNodeProvider::new(0)
+        .with_input("noise", SlotType::Buffer)
+        .with_output("result", SlotType::Buffer);
+    let mut graph = RenderGraph::default();
+    graph.add_node("noise", NoiseNode);
+    bevy_node_plumber::NodeProvider::add_node_to_graph(&provider, &mut graph, "consumer".into());
+    graph.add_slot_edge("noise", "noise", "consumer", "noise");
+
+    let description = diagnostic::GraphDescription::from_render_graph("described", &graph);
+    let names: Vec<&str> = description
+        .nodes
+        .iter()
+        .map(|node| node.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["consumer", "noise"]);
+    assert_eq!(
+        description.edges,
+        vec![diagnostic::EdgeDescription {
+            output_node: "noise".to_string(),
+            output_slot: Some("noise".to_string()),
+            input_node: "consumer".to_string(),
+            input_slot: Some("noise".to_string()),
+        }]
+    );
+
+    let dot = description.to_dot();
+    assert!(dot.starts_with("digraph \"described\" {"));
+    assert!(dot.contains("\"noise\" -> \"consumer\" [label=\"noise -> noise\"];"));
+    assert!(dot.contains("result: Buffer"));
+}
+
+#[test]
+fn duplicate_node_names_are_rejected() {
+    let result = builder::SubGraphBuilder::default()
+        .name("duplicates".into())
+        .add_node("noise", NoiseNode)
+        .add_node("noise", NoiseNode)
+        .build();
+
+    assert!(matches!(
+        result,
+        Err(builder::BuilderError::ValidationError(message)) if message.contains("`noise`")
+    ));
+}
+
+#[test]
+fn replaced_node_keeps_single_name() {
+    let sub_graph = builder::SubGraphBuilder::default()
+        .name("replaced".into())
+        .add_node("noise", NoiseNode)
+        .replace_node("noise", NoiseNode)
+        .build();
+
+    assert!(sub_graph.is_ok());
+}
+
+/// Outputs two distinct entities, so the order of the received values can be checked.
+struct EntityPairNode;
+
+impl Node for EntityPairNode {
+    fn output(&self) -> Vec<SlotInfo> {
+        vec![
+            SlotInfo::new("first", SlotType::Entity),
+            SlotInfo::new("second", SlotType::Entity),
+        ]
+    }
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        _render_context: &mut RenderContext,
+        _world: &World,
+    ) -> Result<(), NodeRunError> {
+        graph.set_output("first", Entity::from_raw(1))?;
+        graph.set_output("second", Entity::from_raw(2))?;
+        Ok(())
+    }
+}
+
+/// Records the entities received by its `first` and `second` slots.
+#[derive(Clone, Default)]
+struct EntityPairProbeNode(Arc<std::sync::Mutex<Option<(Entity, Entity)>>>);
+
+impl Node for EntityPairProbeNode {
+    fn input(&self) -> Vec<SlotInfo> {
+        EntityPairNode.output()
+    }
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        _render_context: &mut RenderContext,
+        _world: &World,
+    ) -> Result<(), NodeRunError> {
+        *self.0.lock().unwrap() = Some((
+            graph.get_input_entity("first")?,
+            graph.get_input_entity("second")?,
+        ));
+        Ok(())
+    }
+}
+
+#[test]
+fn sub_graph_inputs_are_ordered_by_slot_name() {
+    let Some(mut app) = common::gpu_app() else {
+        return;
+    };
+    app.sub_app_mut(RenderApp)
+        .world
+        .resource_mut::<RenderGraph>()
+        .add_node("pair", EntityPairNode);
+    // One of the orders differs from the order of the sub graph input slots
+    let orders = [["first", "second"], ["second", "first"]];
+    let probes: Vec<EntityPairProbeNode> = orders
+        .iter()
+        .enumerate()
+        .map(|(index, order)| {
+            let probe = EntityPairProbeNode::default();
+            let builder = builder::SubGraphBuilder::default()
+                .name(format!("ordered_inputs_{index}").into())
+                .add_node("probe", probe.clone())
+                .add_slot_edge(RenderGraph::INPUT_NODE_NAME, "first", "probe", "first")
+                .add_slot_edge(RenderGraph::INPUT_NODE_NAME, "second", "probe", "second");
+            let sub_graph = order
+                .iter()
+                .fold(builder, |builder, slot| {
+                    builder.add_outer_input_slot_edge(
+                        "pair",
+                        *slot,
+                        (*slot).into(),
+                        SlotType::Entity,
+                    )
+                })
+                .build()
+                .unwrap();
+            app.world.spawn(sub_graph);
+            probe
+        })
+        .collect();
+    app.finish();
+    app.cleanup();
+
+    for _ in 0..100 {
+        if probes.iter().all(|probe| probe.0.lock().unwrap().is_some()) {
+            break;
+        }
+        app.update();
+    }
+    for probe in probes {
+        assert_eq!(
+            *probe.0.lock().unwrap(),
+            Some((Entity::from_raw(1), Entity::from_raw(2)))
+        );
+    }
+}
+
+#[test]
+fn sub_graph_run_event_carries_name_and_entity() {
+    let Some(mut app) = common::gpu_app() else {
+        return;
+    };
+    let request = Arc::new(AtomicBool::new(false));
+    let sub_graph = builder::SubGraphBuilder::default()
+        .name("reported_run".into())
+        .add_node("noise", CountingNoiseNode::default())
+        .add_node_edge(RenderGraph::INPUT_NODE_NAME, "noise")
+        .trigger(graph::SubGraphTrigger::Manual(request.clone()))
+        .build()
+        .unwrap();
+    let sub_graph_entity = app.world.spawn(sub_graph).id();
+    app.finish();
+    app.cleanup();
+    while !common::is_deployed(&app, "reported_run") {
+        app.update();
+    }
+    let mut reader = app
+        .world
+        .resource::<Events<graph::SubGraphRun>>()
+        .get_reader();
+
+    request.store(true, Ordering::Relaxed);
+    let mut runs = Vec::new();
+    for _ in 0..3 {
+        app.update();
+        runs.extend(
+            reader
+                .read(app.world.resource::<Events<graph::SubGraphRun>>())
+                .cloned(),
+        );
+    }
+
+    assert_eq!(
+        runs,
+        vec![graph::SubGraphRun {
+            entity: sub_graph_entity,
+            name: "reported_run".into(),
+        }]
+    );
+}
+
+#[test]
+fn skipped_middle_node_keeps_feeding_downstream_nodes() {
+    let Some(mut app) = common::gpu_app() else {
+        return;
+    };
+    let shader = common::add_shader(&mut app, common::CHAIN_SHADER);
+    let plus_one = common::chain_node(&shader, "plus_one");
+    let double = common::chain_node(&shader, "double");
+    let plus_one_entity = app.world.spawn(plus_one.clone()).id();
+    let double_entity = app.world.spawn(double.clone()).id();
+    let values = input::BufferVecNode::<u32>::new(BufferUsages::STORAGE);
+    for value in [1, 2, 3] {
+        values.push(value);
+    }
+    let output = output::OutputBuffer::default();
+    let request = Arc::new(AtomicBool::new(false));
+    let sub_graph = builder::SubGraphBuilder::default()
+        .name("skipped_middle".into())
+        .add_node("values", values.clone())
+        .add_node_provider("plus_one".into(), plus_one_entity, &plus_one)
+        .add_node_provider("double".into(), double_entity, &double)
+        .add_node("output", output.clone())
+        .add_node_edge(RenderGraph::INPUT_NODE_NAME, "values")
+        .add_slot_edge("values", input::SLOT_NAME, "plus_one", "input")
+        .add_slot_edge("plus_one", "output", "double", "input")
+        .add_slot_edge("double", "output", "output", output::SLOT_NAME)
+        .trigger(graph::SubGraphTrigger::Manual(request.clone()))
+        .build()
+        .unwrap();
+    app.world.spawn(sub_graph);
+    app.world.spawn(output.clone());
+    app.finish();
+    app.cleanup();
+    for _ in 0..100 {
+        if common::is_deployed(&app, "skipped_middle") {
+            break;
+        }
+        app.update();
+    }
+    assert!(common::is_deployed(&app, "skipped_middle"));
+
+    assert_eq!(
+        common::run_and_read(&mut app, &request, &output),
+        vec![4, 6, 8]
+    );
+
+    // The skipped node publishes the buffer written by its previous run
+    plus_one.switch().set_enabled(false);
+    values.clear();
+    for value in [10, 20, 30] {
+        values.push(value);
+    }
+    assert_eq!(
+        common::run_and_read(&mut app, &request, &output),
+        vec![4, 6, 8]
+    );
+
+    plus_one.switch().set_enabled(true);
+    assert_eq!(
+        common::run_and_read(&mut app, &request, &output),
+        vec![22, 42, 62]
+    );
+}
+
+#[derive(Debug, PartialEq)]
+enum BufferEvent {
+    Created(BufferId),
+    Received(BufferId),
+}
+
+type BufferEvents = Arc<std::sync::Mutex<Vec<BufferEvent>>>;
+
+/// Creates a new buffer on each run, so the published value identifies the run.
+struct FreshBufferNode(BufferEvents);
+
+impl Node for FreshBufferNode {
+    fn output(&self) -> Vec<SlotInfo> {
+        vec![SlotInfo::new("buffer", SlotType::Buffer)]
+    }
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        _world: &World,
+    ) -> Result<(), NodeRunError> {
+        let buffer = render_context
+            .render_device()
+            .create_buffer(&BufferDescriptor {
+                label: Some("fresh_buffer"),
+                size: 4,
+                usage: BufferUsages::STORAGE,
+                mapped_at_creation: false,
+            });
+        self.0
+            .lock()
+            .unwrap()
+            .push(BufferEvent::Created(buffer.id()));
+        graph.set_output("buffer", buffer)?;
+        Ok(())
+    }
+}
+
+/// Node of the main Render Graph that receives the outer output slot of the runner node.
+struct OuterSlotProbeNode(BufferEvents);
+
+impl Node for OuterSlotProbeNode {
+    fn input(&self) -> Vec<SlotInfo> {
+        vec![SlotInfo::new("buffer", SlotType::Buffer)]
+    }
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        _render_context: &mut RenderContext,
+        _world: &World,
+    ) -> Result<(), NodeRunError> {
+        let buffer = graph.get_input_buffer("buffer")?;
+        self.0
+            .lock()
+            .unwrap()
+            .push(BufferEvent::Received(buffer.id()));
+        Ok(())
+    }
+}
+
+/// Runs the sub graph once and returns the events of the frame before the run, of the run
+/// and of the frame after the run.
+fn outer_slot_events(name: &'static str) -> Option<Vec<BufferEvent>> {
+    let mut app = common::gpu_app()?;
+    let events = BufferEvents::default();
+    let request = Arc::new(AtomicBool::new(false));
+    let sub_graph = builder::SubGraphBuilder::default()
+        .name(name.into())
+        .trigger(graph::SubGraphTrigger::Manual(request.clone()))
+        .add_node("fresh", FreshBufferNode(events.clone()))
+        .add_outer_output_slot_edge("fresh", "buffer", "buffer".into(), SlotType::Buffer)
+        .build()
+        .unwrap();
+    app.world.spawn(sub_graph);
+    app.finish();
+    app.cleanup();
+    while !common::is_deployed(&app, name) {
+        app.update();
+    }
+    let mut render_graph = app
+        .sub_app_mut(RenderApp)
+        .world
+        .resource_mut::<RenderGraph>();
+    render_graph.add_node("probe", OuterSlotProbeNode(events.clone()));
+    render_graph.add_slot_edge(name, "buffer", "probe", "buffer");
+
+    app.update();
+    request.store(true, Ordering::Relaxed);
+    app.update();
+    app.update();
+    let events = std::mem::take(&mut *events.lock().unwrap());
+    Some(events)
+}
+
+#[test]
+fn outer_output_slot_is_one_run_behind() {
+    let Some(events) = outer_slot_events("outer_output") else {
+        return;
+    };
+    let [BufferEvent::Received(fallback), BufferEvent::Created(created), BufferEvent::Received(fallback_again), BufferEvent::Received(received)] =
+        &events[..]
+    else {
+        panic!("Unexpected events {:?}", events);
+    };
+    assert_eq!(fallback, fallback_again);
+    assert_ne!(fallback, created);
+    assert_eq!(created, received);
+}
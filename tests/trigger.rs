@@ -0,0 +1,188 @@
+use bevy::prelude::{App, Events, Resource, World};
+use bevy_node_plumber::mock::{HeadlessRenderPlugin, MockNodeProvider};
+use bevy_node_plumber::prelude::*;
+use bevy_node_plumber::NodeProviderPlugin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+mod common;
+
+const FRAMES: usize = 10;
+
+fn count_runs(trigger: &graph::SubGraphTrigger, frames: usize) -> usize {
+    (0..frames).filter(|_| trigger.should_run()).count()
+}
+
+#[test]
+fn manual_trigger_runs_sub_graph_exactly_once_per_request() {
+    let mut app = App::new();
+    app.add_plugins(HeadlessRenderPlugin)
+        .add_plugins(graph::SubGraphPlugin)
+        .add_plugins(NodeProviderPlugin::<MockNodeProvider>::default());
+    let flag = Arc::new(AtomicBool::new(false));
+    let provider = MockNodeProvider::new(0);
+    let provider_entity = app.world.spawn(provider.clone()).id();
+    let sub_graph = builder::SubGraphBuilder::default()
+        .name("manual_trigger".into())
+        .trigger(graph::SubGraphTrigger::Manual(flag.clone()))
+        .add_node_provider("mock_node".into(), provider_entity, &provider)
+        .build()
+        .unwrap();
+    app.world.spawn(sub_graph);
+    app.finish();
+    app.cleanup();
+    while !common::is_deployed(&app, "manual_trigger") {
+        app.update();
+    }
+
+    let mut reader = app
+        .world
+        .resource::<Events<graph::SubGraphRun>>()
+        .get_reader();
+    let mut count_sub_graph_runs = |app: &mut App| {
+        (0..FRAMES)
+            .map(|_| {
+                app.update();
+                reader
+                    .read(app.world.resource::<Events<graph::SubGraphRun>>())
+                    .count()
+            })
+            .sum::<usize>()
+    };
+
+    assert_eq!(count_sub_graph_runs(&mut app), 0);
+
+    flag.store(true, Ordering::Relaxed);
+    assert_eq!(count_sub_graph_runs(&mut app), 1);
+    assert!(!flag.load(Ordering::Relaxed));
+
+    flag.store(true, Ordering::Relaxed);
+    flag.store(true, Ordering::Relaxed);
+    assert_eq!(count_sub_graph_runs(&mut app), 1);
+}
+
+#[test]
+fn manual_trigger_clone_shares_request() {
+    let flag = Arc::new(AtomicBool::new(true));
+    let trigger = graph::SubGraphTrigger::Manual(flag);
+    let extracted = trigger.clone();
+
+    assert_eq!(
+        count_runs(&extracted, FRAMES) + count_runs(&trigger, FRAMES),
+        1
+    );
+}
+
+#[test]
+fn always_trigger_runs_every_frame() {
+    assert_eq!(count_runs(&graph::SubGraphTrigger::Always, FRAMES), FRAMES);
+    assert_eq!(
+        count_runs(&graph::SubGraphTrigger::default(), FRAMES),
+        FRAMES
+    );
+}
+
+#[test]
+fn pending_state_is_not_consumed() {
+    let flag = Arc::new(AtomicBool::new(true));
+    let trigger = graph::SubGraphTrigger::Manual(flag);
+
+    assert!(trigger.is_pending());
+    assert_eq!(trigger.remaining_runs(), Some(1));
+    assert!(trigger.should_run());
+    assert!(!trigger.is_pending());
+    assert_eq!(trigger.remaining_runs(), Some(0));
+
+    assert!(graph::SubGraphTrigger::Always.is_pending());
+    assert_eq!(graph::SubGraphTrigger::Always.remaining_runs(), None);
+}
+
+#[test]
+fn manual_with_ack_trigger_runs_once_and_tracks_completion() {
+    let request = Arc::new(AtomicBool::new(true));
+    let trigger = graph::SubGraphTrigger::ManualWithAck {
+        request: request.clone(),
+        completed: Default::default(),
+    };
+
+    assert_eq!(trigger.completed_runs(), Some(0));
+    assert_eq!(count_runs(&trigger, FRAMES), 1);
+    assert!(!trigger.is_pending());
+
+    assert_eq!(graph::SubGraphTrigger::Always.completed_runs(), None);
+}
+
+#[test]
+fn every_n_frames_trigger_runs_on_cadence() {
+    let trigger = graph::SubGraphTrigger::every_n_frames(3);
+    let extracted = trigger.clone();
+
+    let runs: Vec<bool> = (0..7).map(|_| extracted.should_run()).collect();
+    assert_eq!(runs, vec![true, false, false, true, false, false, true]);
+    assert!(!trigger.is_pending());
+    assert_eq!(trigger.remaining_runs(), None);
+}
+
+#[test]
+fn fixed_interval_trigger_waits_for_interval() {
+    let trigger = graph::SubGraphTrigger::fixed_interval(std::time::Duration::from_secs(3600));
+
+    assert_eq!(count_runs(&trigger, FRAMES), 0);
+    assert!(!trigger.is_pending());
+
+    let trigger = graph::SubGraphTrigger::fixed_interval(std::time::Duration::ZERO);
+    assert_eq!(count_runs(&trigger, FRAMES), FRAMES);
+}
+
+#[derive(Resource)]
+struct Enabled(bool);
+
+#[test]
+fn when_trigger_evaluates_condition_against_world() {
+    let trigger =
+        graph::SubGraphTrigger::when(|world| world.get_resource::<Enabled>().is_some_and(|e| e.0));
+    let mut world = World::new();
+
+    assert!(!trigger.should_run_in(&world));
+    world.insert_resource(Enabled(true));
+    assert!(!trigger.should_run());
+    world.insert_resource(Enabled(false));
+    assert!(!trigger.should_run_in(&world));
+    world.insert_resource(Enabled(true));
+    assert!(trigger.should_run_in(&world));
+    assert!(trigger.clone().should_run_in(&world));
+    assert!(trigger.is_pending());
+    assert_eq!(trigger.remaining_runs(), None);
+}
+
+#[test]
+fn when_resource_trigger_reads_typed_resource() {
+    let trigger = graph::SubGraphTrigger::when_resource(|enabled: &Enabled| enabled.0);
+    let mut world = World::new();
+
+    assert!(!trigger.should_run_in(&world));
+    world.insert_resource(Enabled(true));
+    assert!(trigger.should_run_in(&world));
+    world.insert_resource(Enabled(false));
+    assert!(!trigger.should_run_in(&world));
+}
+
+#[test]
+fn once_trigger_is_not_consumed_until_run() {
+    let trigger = graph::SubGraphTrigger::once();
+
+    assert!(trigger.is_pending());
+    assert_eq!(trigger.remaining_runs(), Some(1));
+    assert_eq!(trigger.completed_runs(), Some(0));
+    assert_eq!(count_runs(&trigger.clone(), FRAMES), FRAMES);
+    assert!(trigger.is_pending());
+
+    let graph::SubGraphTrigger::Once(completed) = &trigger else {
+        unreachable!();
+    };
+    completed.store(true, Ordering::Release);
+    assert_eq!(count_runs(&trigger, FRAMES), 0);
+    assert!(!trigger.is_pending());
+    assert_eq!(trigger.remaining_runs(), Some(0));
+    assert_eq!(trigger.completed_runs(), Some(1));
+}